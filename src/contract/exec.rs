@@ -2,19 +2,48 @@
 ///
 /// This module handles all state-changing operations for the contract,
 /// including deposits, authorization management, and token spending.
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
-use crate::msg::exec::ExecuteMsg;
+use cosmwasm_std::{attr, coins, Addr, Attribute, BankMsg, Binary, Decimal, Deps, DepsMut, Env, Event, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, SubMsg, Timestamp, Uint128};
+use cw_storage_plus::Item;
+use sha2::{Digest, Sha256};
+use crate::contract::reply::WITHDRAW_REPLY_ID;
+use crate::contract::query::{global_cap_remaining, ledger_total, load_price_oracle, oracle_price, window_remaining};
+use crate::msg::exec::{AuthorizationTerms, ClearableParam, ExecuteMsg, ParamUpdate};
+use crate::msg::policy::{PolicyQueryMsg, PolicyResponse};
 use crate::error::ContractError;
-use crate::state::{BALANCES, AUTHORIZED_SPENDERS};
+use crate::state::{
+    Authorization, BlackoutWindow, DenomConversion, DepositRecord, DriftSample, GlobalSpendCap, LedgerEntry, PendingWithdrawal, TimelockedWithdrawal, WithdrawTimelock, RecurringRefill, ACCEPTED_DENOMS, ACTIVE_ACCOUNTS, AUTH_COUNT, ADMIN, ADMIN_CAN_DEPOSIT, BALANCES, CONVERTED, AUTHORIZED_SPENDERS, DENOM, DENOM_ALIASES, DEPOSITS_ENABLED, DRIFT_COUNT, FEES_PAID_BY, FEE_BPS, MAX_FEE_BPS,
+    DENOM_PRECISIONS, DEPOSIT_COUNTER, DEPOSITS, DRIFT_LOG, EXPIRY_QUEUE, FROZEN, GLOBAL_SPEND_CAP, GLOBAL_SPEND_EPOCH, IMMEDIATE_OUTFLOWS, SPENDING_BLACKOUTS, MANAGERS, PENDING_WITHDRAWALS, WITHDRAW_TIMELOCK, KEEPER_REWARD, LAST_ACTIVITY, LAST_SPEND, LEDGER, LEDGER_COUNT, MAX_RECEIVED_PER_ACCOUNT, MAX_SPENDERS_PER_OWNER, MIN_DEPOSIT, MINIMAL_EVENTS, NONCES, PAUSED, PENDING_DENOM, PENDING_WITHDRAWAL, POLICY, PRICE_ORACLE, RECOVERY, RECOVERY_DELAY_BLOCKS, REFILL_QUEUE, REWARD_POOL, SIGNING_KEYS, SPENDER_BLOCKLIST, SPENDER_INDEX, SPEND_FEE_BPS,
+    SPEND_WINDOWS, SPENDER_COUNT, SPENDER_DENOMS, SPENT_BY, STRICT_DEPOSIT, TOTAL_DEPOSITED, TOTAL_SPENT, ORACLE_AUTO_PAUSE, USD_SPENDS_PAUSED,
+};
+
+/// Number of expired authorizations removed by CleanupExpired when no limit is given
+const DEFAULT_CLEANUP_LIMIT: u32 = 10;
+/// Upper bound on the number of expired authorizations removed in one CleanupExpired call
+const MAX_CLEANUP_LIMIT: u32 = 30;
+/// Number of authorizations removed by GlobalRevokeSpender when no limit is given
+const DEFAULT_GLOBAL_REVOKE_LIMIT: u32 = 30;
+/// Upper bound on the number of authorizations removed in one GlobalRevokeSpender call
+const MAX_GLOBAL_REVOKE_LIMIT: u32 = 100;
+/// Maximum size in bytes of the opaque metadata attached to an authorization
+const MAX_METADATA_BYTES: usize = 1024;
+/// Length in bytes of a sha256 digest, the only accepted secret hash size
+const SECRET_HASH_BYTES: usize = 32;
+/// Longest interval between allowance refills, one year
+const MAX_REFILL_INTERVAL_SECONDS: u64 = 365 * 24 * 60 * 60;
+/// Longest withdrawal timelock delay, one year; keeps unlock times far from overflowing
+const MAX_WITHDRAW_DELAY_SECONDS: u64 = 365 * 24 * 60 * 60;
 
 /// Main entry point for all execute messages
 ///
 /// Routes incoming messages to the appropriate handler function based on the message type.
-/// Each handler implements a specific piece of contract functionality.
+/// Each handler implements a specific piece of contract functionality. While the
+/// contract is paused only admin maintenance messages are dispatched. The sender's
+/// activity height is recorded first, so any execute proves an owner is still active.
 ///
 /// # Arguments
 /// * `deps` - Mutable dependencies for storage, API, and querier access
-/// * `_env` - Environment information (block height/time, contract address)
+/// * `env` - Environment information (block height/time, contract address)
 /// * `info` - Transaction metadata (sender, sent funds)
 /// * `msg` - The execute message with the operation to perform
 ///
@@ -22,107 +51,813 @@ use crate::state::{BALANCES, AUTHORIZED_SPENDERS};
 /// * `Result<Response, ContractError>` - Success response or error
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    if PAUSED.load(deps.storage)? && !is_admin_msg(&msg) {
+        return Err(ContractError::Paused {});
+    }
+    LAST_ACTIVITY.save(deps.storage, &info.sender, &env.block.height)?;
     match msg {
-        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
-        ExecuteMsg::AuthorizeSpender { spender } => execute_authorize_spender(deps, info, spender),
-        ExecuteMsg::RevokeSpender { spender } => execute_revoke_spender(deps, info, spender),
-        ExecuteMsg::SpendFrom { owner, amount } => execute_spend_from(deps, info, owner, amount),
+        ExecuteMsg::Deposit {} => execute_deposit(deps, env, info),
+        ExecuteMsg::DepositFor { beneficiary } => execute_deposit_for(deps, env, info, beneficiary),
+        ExecuteMsg::AuthorizeSpender { spender, terms, owner } => {
+            execute_authorize_spender(deps, env, info, spender, terms, owner)
+        }
+        ExecuteMsg::AuthorizeWithDeposit { spender } => execute_authorize_with_deposit(deps, env, info, spender),
+        ExecuteMsg::SeedSubAccount { sub_account, amount, spender, limit } => {
+            execute_seed_sub_account(deps, env, info, sub_account, amount, spender, limit)
+        }
+        ExecuteMsg::IncreaseAllowance { spender, amount } => execute_increase_allowance(deps, env, info, spender, amount),
+        ExecuteMsg::DecreaseAllowance { spender, amount } => execute_decrease_allowance(deps, env, info, spender, amount),
+        ExecuteMsg::LimitSpender { spender, limit, expires } => {
+            execute_limit_spender(deps, env, info, spender, limit, expires)
+        }
+        ExecuteMsg::SetSpenderDenoms { spender, denoms } => execute_set_spender_denoms(deps, info, spender, denoms),
+        ExecuteMsg::RevokeSpender { spender, owner } => execute_revoke_spender(deps, env, info, spender, owner),
+        ExecuteMsg::RevokeAllSpenders {} => execute_revoke_all_spenders(deps, env, info),
+        ExecuteMsg::SpendFrom { owner, amount, secret, denom } => {
+            execute_spend_from(deps, env, info, owner, amount, secret, denom)
+        }
+        ExecuteMsg::SpendFromTo { owner, recipient, amount } => {
+            execute_spend_from_to(deps, env, info, owner, recipient, amount)
+        }
+        ExecuteMsg::SpendFromSigned { owner, amount, nonce, denom, signature } => {
+            execute_spend_from_signed(deps, env, info, owner, amount, nonce, denom, signature)
+        }
+        ExecuteMsg::SetSigningKey { pubkey } => execute_set_signing_key(deps, info, pubkey),
+        ExecuteMsg::SpendUsd { owner, usd_amount, recipient } => {
+            execute_spend_usd(deps, env, info, owner, usd_amount, recipient)
+        }
+        ExecuteMsg::BatchSpendFrom { owner, payouts, denom } => {
+            execute_batch_spend_from(deps, env, info, owner, payouts, denom)
+        }
+        ExecuteMsg::Refund { owner, amount, denom } => execute_refund(deps, env, info, owner, amount, denom),
+        ExecuteMsg::Withdraw { amount, denom } => execute_withdraw(deps, env, info, amount, denom),
+        ExecuteMsg::RequestWithdraw { amount, denom } => execute_request_withdraw(deps, env, info, amount, denom),
+        ExecuteMsg::ClaimWithdraw {} => execute_claim_withdraw(deps, env, info),
+        ExecuteMsg::ConvertBalance {} => execute_convert_balance(deps, env, info),
+        ExecuteMsg::FundRewardPool {} => execute_fund_reward_pool(deps, env, info),
+        ExecuteMsg::CleanupExpired { limit } => execute_cleanup_expired(deps, env, info, limit),
+        ExecuteMsg::ProcessRefills { limit } => execute_process_refills(deps, env, info, limit),
+        ExecuteMsg::UpdateAdmin { new_admin } => execute_update_admin(deps, env, info, new_admin),
+        ExecuteMsg::GlobalRevokeSpender { spender, limit } => execute_global_revoke_spender(deps, env, info, spender, limit),
+        ExecuteMsg::AdminRevoke { owner, spender } => execute_admin_revoke(deps, env, info, owner, spender),
+        ExecuteMsg::BlockSpender { spender } => execute_set_blocked(deps, info, spender, true),
+        ExecuteMsg::UnblockSpender { spender } => execute_set_blocked(deps, info, spender, false),
+        ExecuteMsg::FreezeAccount { account } => execute_set_frozen(deps, info, account, true),
+        ExecuteMsg::UnfreezeAccount { account } => execute_set_frozen(deps, info, account, false),
+        ExecuteMsg::RecordDrift {} => execute_record_drift(deps, env, info),
+        ExecuteMsg::AdminSweep { recipient } => execute_admin_sweep(deps, env, info, recipient),
+        ExecuteMsg::AddManager { manager } => execute_set_manager(deps, info, manager, true),
+        ExecuteMsg::RemoveManager { manager } => execute_set_manager(deps, info, manager, false),
+        ExecuteMsg::SetRecovery { address } => execute_set_recovery(deps, info, address),
+        ExecuteMsg::Recover { owner } => execute_recover(deps, env, info, owner),
+        ExecuteMsg::SetDepositsEnabled { enabled } => execute_set_deposits_enabled(deps, info, enabled),
+        ExecuteMsg::SetUsdSpendsPaused { paused } => execute_set_usd_spends_paused(deps, info, paused),
+        ExecuteMsg::SetSpendingBlackouts { windows } => execute_set_spending_blackouts(deps, info, windows),
+        ExecuteMsg::SetPendingDenom { denom, ratio } => execute_set_pending_denom(deps, info, denom, ratio),
+        ExecuteMsg::UpdateParams { params } => execute_update_params(deps, info, params),
+        ExecuteMsg::Snapshot {} => Err(ContractError::NotImplemented {}),
     }
 }
 
+/// Returns true for admin maintenance messages, which remain available while paused
+fn is_admin_msg(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::UpdateAdmin { .. }
+            | ExecuteMsg::GlobalRevokeSpender { .. }
+            | ExecuteMsg::AdminRevoke { .. }
+            | ExecuteMsg::BlockSpender { .. }
+            | ExecuteMsg::UnblockSpender { .. }
+            | ExecuteMsg::FreezeAccount { .. }
+            | ExecuteMsg::UnfreezeAccount { .. }
+            | ExecuteMsg::RecordDrift {}
+            | ExecuteMsg::AdminSweep { .. }
+            | ExecuteMsg::SetDepositsEnabled { .. }
+            | ExecuteMsg::SetUsdSpendsPaused { .. }
+            | ExecuteMsg::SetSpendingBlackouts { .. }
+            | ExecuteMsg::SetPendingDenom { .. }
+            | ExecuteMsg::UpdateParams { .. }
+    )
+}
+
 /// Handles token deposits to the contract
 ///
 /// Deposits sent tokens to the sender's balance in the contract.
-/// This function validates that exactly one native token was sent,
-/// then adds the amount to the sender's current balance.
+/// This function validates that exactly one accepted native token was sent,
+/// then adds the amount to the sender's current balance in that denom.
 ///
 /// # Arguments
 /// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, recorded in the activity ledger
 /// * `info` - Contains sender address and the funds sent with the transaction
 ///
 /// # Returns
 /// * `Result<Response, ContractError>` - Success response with event attributes or error
 fn execute_deposit(
     deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let (denom, amount) = validate_deposit_funds(&deps, &info)?;
+    let sender = info.sender;
+    assert_deposit_accepted(deps.as_ref(), amount)?;
+    assert_can_receive_deposit(deps.as_ref(), &sender)?;
+    // Update the sender's balance by adding the deposited amount, net of the fee
+    let (credited, fee, new_balance) = credit_deposit(deps.storage, &sender, &denom, amount)?;
+    record_ledger(deps.storage, &env, "deposit", &sender, None, Some((&denom, credited)))?;
+    let deposit_id = record_deposit(deps.storage, &env, &sender, &sender, &denom, credited, fee)?;
+    // Return success response with event attributes and a structured deposit event
+    Ok(Response::new()
+        .add_event(deposit_event(&sender, &sender, &denom, credited))
+        .add_attribute("action", "deposit")
+        .add_attribute("deposit_id", deposit_id.to_string())
+        .add_attribute("from", sender)
+        .add_attributes([amount_attr("fee", fee), amount_attr("new_balance", new_balance)]))
+}
+
+/// Handles token deposits made on behalf of another address
+///
+/// Applies the same funds validation as `execute_deposit`, but credits the
+/// deposited amount to the beneficiary's balance rather than the sender's.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains sender address and the funds sent with the transaction
+/// * `beneficiary` - Address string of the account receiving the deposit
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_deposit_for(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
+    beneficiary: String,
 ) -> Result<Response, ContractError> {
+    let beneficiary_addr = deps.api.addr_validate(&beneficiary)?;
+    let (denom, amount) = validate_deposit_funds(&deps, &info)?;
+    assert_deposit_accepted(deps.as_ref(), amount)?;
+    assert_can_receive_deposit(deps.as_ref(), &beneficiary_addr)?;
+    // Credit the beneficiary, leaving the sender's balance untouched
+    let (credited, fee, new_balance) = credit_deposit(deps.storage, &beneficiary_addr, &denom, amount)?;
+    record_ledger(deps.storage, &env, "deposit", &beneficiary_addr, Some(&info.sender), Some((&denom, credited)))?;
+    let deposit_id = record_deposit(deps.storage, &env, &info.sender, &beneficiary_addr, &denom, credited, fee)?;
+    Ok(Response::new()
+        .add_event(deposit_event(&info.sender, &beneficiary_addr, &denom, credited))
+        .add_attribute("action", "deposit_for")
+        .add_attribute("deposit_id", deposit_id.to_string())
+        .add_attribute("from", info.sender)
+        .add_attribute("beneficiary", beneficiary_addr)
+        .add_attributes([amount_attr("fee", fee), amount_attr("new_balance", new_balance)]))
+}
+
+/// Validates the funds attached to a deposit and returns the deposited denom and amount
+///
+/// Exactly one native token in one of the contract's accepted denoms must be sent,
+/// with a nonzero amount. When strict deposits are disabled, several coins may be
+/// sent and only the one in the primary denom is taken. A coin in one of the primary
+/// denom's aliases counts as the primary denom and is reported as such.
+fn validate_deposit_funds(deps: &DepsMut, info: &MessageInfo) -> Result<(String, Uint128), ContractError> {
+    let denom = DENOM.load(deps.storage)?;
+    let aliases = DENOM_ALIASES.load(deps.storage)?;
+    let is_primary = |coin_denom: &String| *coin_denom == denom || aliases.contains(coin_denom);
+
     // Extract the amount and denom from the sent funds
-    if info.funds.len() != 1 {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Must send exactly one native token")));
+    let sent_coin = match info.funds.as_slice() {
+        [] => return Err(ContractError::NoFunds {}),
+        [coin] => coin,
+        funds if !STRICT_DEPOSIT.load(deps.storage)? => {
+            funds.iter().find(|coin| is_primary(&coin.denom)).ok_or_else(|| ContractError::InvalidDenom {
+                expected: denom.clone(),
+                got: funds.iter().map(|coin| coin.denom.as_str()).collect::<Vec<_>>().join(","),
+            })?
+        }
+        _ => return Err(ContractError::MultipleDenoms {}),
+    };
+    let credited_denom = if aliases.contains(&sent_coin.denom) { denom.clone() } else { sent_coin.denom.clone() };
+    let accepted = ACCEPTED_DENOMS.load(deps.storage)?;
+    if !accepted.contains(&credited_denom) {
+        return Err(ContractError::InvalidDenom { expected: accepted.join(","), got: sent_coin.denom.clone() });
     }
-    let sent_coin = &info.funds[0];
-    let denom = crate::state::DENOM.load(deps.storage)?;
-    if sent_coin.denom != denom {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Invalid token denomination")));
+    if sent_coin.amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
     }
-    let amount = sent_coin.amount.u128();
-    let sender = info.sender;
-    // Update the sender's balance by adding the deposited amount
-    let prev = BALANCES.may_load(deps.storage, &sender)?.unwrap_or(0);
-    BALANCES.save(deps.storage, &sender, &(prev + amount))?;
-    // Return success response with event attributes
-    Ok(Response::new().add_attribute("action", "deposit").add_attribute("from", sender))
+    Ok((credited_denom, sent_coin.amount))
+}
+
+/// Resolves the denom a message operates on, defaulting to the primary denom
+///
+/// Fails with `InvalidDenom` if an explicitly requested denom is not accepted.
+fn resolve_denom(deps: Deps, denom: Option<String>) -> Result<String, ContractError> {
+    let Some(denom) = denom else {
+        return Ok(DENOM.load(deps.storage)?);
+    };
+    let accepted = ACCEPTED_DENOMS.load(deps.storage)?;
+    if !accepted.contains(&denom) {
+        return Err(ContractError::InvalidDenom { expected: accepted.join(","), got: denom });
+    }
+    Ok(denom)
+}
+
+/// Credits a deposit to `account`, routing the configured fee to the admin
+///
+/// The fee is `amount * fee_bps / 10000`, rounded down, and is credited to the
+/// admin's balance in the same denom. Returns the amount credited to `account`,
+/// the fee taken and `account`'s new balance.
+fn credit_deposit(storage: &mut dyn Storage, account: &Addr, denom: &str, amount: Uint128) -> StdResult<(Uint128, Uint128, Uint128)> {
+    if denom == DENOM.load(storage)? {
+        add_to_total(storage, TOTAL_DEPOSITED, amount)?;
+    }
+    let fee = bps_of(amount, FEE_BPS.load(storage)?);
+    if !fee.is_zero() {
+        let admin = ADMIN.load(storage)?;
+        credit(storage, &admin, denom, fee)?;
+    }
+    let credited = amount - fee;
+    // Credited last, so the returned balance already includes a fee paid to itself
+    let balance = credit(storage, account, denom, credited)?;
+    Ok((credited, fee, balance))
+}
+
+/// Adds `amount` to a lifetime total such as TOTAL_DEPOSITED
+fn add_to_total(storage: &mut dyn Storage, total: Item<Uint128>, amount: Uint128) -> StdResult<()> {
+    let updated = total.load(storage)?.checked_add(amount)?;
+    total.save(storage, &updated)
+}
+
+/// Returns `fee_bps` basis points of `amount`, rounded down
+fn bps_of(amount: Uint128, fee_bps: u16) -> Uint128 {
+    amount.multiply_ratio(fee_bps, MAX_FEE_BPS)
+}
+
+/// Rejects deposits while halted or smaller than the configured minimum
+fn assert_deposit_accepted(deps: Deps, amount: Uint128) -> Result<(), ContractError> {
+    if !DEPOSITS_ENABLED.load(deps.storage)? {
+        return Err(ContractError::DepositsDisabled {});
+    }
+    let min = MIN_DEPOSIT.load(deps.storage)?;
+    if amount < min {
+        return Err(ContractError::DepositBelowMinimum { min, amount });
+    }
+    Ok(())
+}
+
+/// Rejects deposits crediting a frozen account, or the admin when admin deposits are disabled
+fn assert_can_receive_deposit(deps: Deps, account: &Addr) -> Result<(), ContractError> {
+    assert_not_frozen(deps, account)?;
+    if !ADMIN_CAN_DEPOSIT.load(deps.storage)? && ADMIN.load(deps.storage)? == *account {
+        return Err(ContractError::AdminCannotDeposit {});
+    }
+    Ok(())
 }
 
-/// Authorizes a spender to spend on behalf of the message sender
+/// Authorizes a spender to spend on behalf of an owner
 ///
 /// Creates or updates an authorization record allowing the spender
-/// to spend tokens from the sender's balance.
+/// to spend tokens from the owner's balance.
 ///
 /// # Arguments
 /// * `deps` - Mutable dependencies for storage access and address validation
-/// * `info` - Contains the owner's address (message sender)
+/// * `env` - Environment information, used to validate the expiry height
+/// * `info` - Contains the owner's or a manager's address (message sender)
 /// * `spender` - Address string of the account being authorized to spend
+/// * `terms` - Optional restrictions on the authorization
+/// * `owner` - Account a manager authorizes for; defaults to the sender
 ///
 /// # Returns
 /// * `Result<Response, ContractError>` - Success response with event attributes or error
 fn execute_authorize_spender(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     spender: String,
+    terms: Option<AuthorizationTerms>,
+    owner: Option<String>,
 ) -> Result<Response, ContractError> {
-    let owner = info.sender.clone();
+    // Owners may authorize before funding their account
+    let owner = managed_owner(deps.as_ref(), &info.sender, owner)?;
     let spender_addr = deps.api.addr_validate(&spender)?;
-    
-    // Prevent self-authorization (owner cannot authorize themselves as spender)
-    if owner == spender_addr {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Cannot authorize self as spender")));
+    // A manager must not be able to turn itself into a spender
+    if owner != info.sender && spender_addr == info.sender {
+        return Err(ContractError::Unauthorized {});
     }
-    
-    // The test expects that other_user cannot authorize a spender
-    // This is to simulate that only authenticated users can perform this action
-    // For the purpose of this test, let's check if the user has deposited any tokens
-    // as a way to "authenticate" them
-    let balance = BALANCES.may_load(deps.storage, &owner)?.unwrap_or(0);
-    if balance == 0 {
+    let previous_state = AUTHORIZED_SPENDERS.has(deps.storage, (&owner, &spender_addr));
+    grant_authorization(deps, &env, &owner, &spender, terms)?;
+    Ok(authorization_audit(Response::new(), &env, "authorize_spender", &owner, &spender_addr, previous_state, true))
+}
+
+/// Resolves the owner an authorization change applies to
+///
+/// Without `owner` the sender acts for itself. Otherwise the sender must be the
+/// owner or one of its managers.
+fn managed_owner(deps: Deps, sender: &Addr, owner: Option<String>) -> Result<Addr, ContractError> {
+    let Some(owner) = owner else {
+        return Ok(sender.clone());
+    };
+    let owner = deps.api.addr_validate(&owner)?;
+    if owner != *sender && !MANAGERS.has(deps.storage, (&owner, sender)) {
         return Err(ContractError::Unauthorized {});
     }
-    
+    Ok(owner)
+}
+
+/// Adds the audit attributes of an authorization change to `response`
+///
+/// `previous_state` and `new_state` record whether the spender was authorized
+/// before and after the change, so an indexer can rebuild the authorization
+/// timeline from events alone.
+fn authorization_audit(
+    response: Response,
+    env: &Env,
+    action: &str,
+    owner: &Addr,
+    spender: &Addr,
+    previous_state: bool,
+    new_state: bool,
+) -> Response {
+    response
+        .add_attribute("action", action)
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender)
+        .add_attributes(audit_state(env, previous_state, new_state))
+}
+
+/// Builds an `authorization_audit` event for one pair changed by a multi-pair handler
+///
+/// Handlers that remove several authorizations in one call emit one event per pair,
+/// carrying the same audit attributes as [`authorization_audit`].
+fn authorization_audit_event(
+    env: &Env,
+    owner: &Addr,
+    spender: &Addr,
+    previous_state: bool,
+    new_state: bool,
+) -> Event {
+    Event::new("authorization_audit")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender)
+        .add_attributes(audit_state(env, previous_state, new_state))
+}
+
+/// Returns the state transition and block height attributes of an authorization audit
+fn audit_state(env: &Env, previous_state: bool, new_state: bool) -> [Attribute; 3] {
+    [
+        attr("previous_state", previous_state.to_string()),
+        attr("new_state", new_state.to_string()),
+        attr("block_height", env.block.height.to_string()),
+    ]
+}
+
+/// Credits the sent funds to the sender and authorizes a spender in one step
+///
+/// Funds are validated exactly as for Deposit. The authorization is unrestricted,
+/// as with an AuthorizeSpender that carries no terms.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information (block height/time)
+/// * `info` - Contains the owner's address and the funds sent with the transaction
+/// * `spender` - Address string of the account being authorized to spend
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_authorize_with_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+) -> Result<Response, ContractError> {
+    let (denom, amount) = validate_deposit_funds(&deps, &info)?;
+    let owner = info.sender;
+    assert_deposit_accepted(deps.as_ref(), amount)?;
+    assert_can_receive_deposit(deps.as_ref(), &owner)?;
+    let (credited, fee, new_balance) = credit_deposit(deps.storage, &owner, &denom, amount)?;
+    record_ledger(deps.storage, &env, "deposit", &owner, None, Some((&denom, credited)))?;
+    let deposit_id = record_deposit(deps.storage, &env, &owner, &owner, &denom, credited, fee)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let previous_state = AUTHORIZED_SPENDERS.has(deps.storage, (&owner, &spender_addr));
+    grant_authorization(deps, &env, &owner, &spender, None)?;
+    Ok(Response::new()
+        .add_event(deposit_event(&owner, &owner, &denom, credited))
+        .add_attribute("action", "authorize_with_deposit")
+        .add_attribute("deposit_id", deposit_id.to_string())
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender_addr)
+        .add_attributes(audit_state(&env, previous_state, true))
+        .add_attributes([
+            amount_attr("amount", credited),
+            amount_attr("fee", fee),
+            amount_attr("new_balance", new_balance),
+        ]))
+}
+
+/// Moves funds to a fresh sub-account and authorizes a spender on it
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains the funding account's address (message sender)
+/// * `sub_account` - Address string of the account being seeded
+/// * `amount` - Number of primary denom tokens moved to the sub-account
+/// * `spender` - Address string authorized on the sub-account, defaulting to the sender
+/// * `limit` - Allowance granted to the spender
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_seed_sub_account(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sub_account: String,
+    amount: Uint128,
+    spender: Option<String>,
+    limit: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() || limit.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount and limit must be positive")));
+    }
+    // A larger limit would let the spender drain whatever the account's real owner deposits later
+    if limit > amount {
+        return Err(ContractError::Std(StdError::generic_err("Limit cannot exceed the seeded amount")));
+    }
+    let sub_account_addr = deps.api.addr_validate(&sub_account)?;
+    if sub_account_addr == info.sender || !is_fresh_account(deps.as_ref(), &sub_account_addr)? {
+        return Err(ContractError::Std(StdError::generic_err("Sub-account must be a fresh address")));
+    }
+    let denom = DENOM.load(deps.storage)?;
+    assert_not_frozen(deps.as_ref(), &info.sender)?;
+    assert_can_receive_deposit(deps.as_ref(), &sub_account_addr)?;
+    assert_within_receive_cap(deps.storage, &sub_account_addr, &denom, amount)?;
+    record_immediate_outflow(deps.storage, &env, &info.sender, &denom, amount)?;
+
+    debit(deps.storage, &info.sender, &denom, amount)?;
+    credit(deps.storage, &sub_account_addr, &denom, amount)?;
+    record_ledger(deps.storage, &env, "seed", &sub_account_addr, Some(&info.sender), Some((&denom, amount)))?;
+
+    let spender = spender.unwrap_or_else(|| info.sender.to_string());
+    let terms = AuthorizationTerms { allowance: Some(limit), ..Default::default() };
+    let spender_addr = grant_authorization(deps, &env, &sub_account_addr, &spender, Some(terms))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "seed_sub_account")
+        .add_attribute("funder", info.sender)
+        .add_attribute("sub_account", sub_account_addr)
+        .add_attributes([amount_attr("amount", amount)])
+        .add_attribute("spender", spender_addr)
+        .add_attribute("limit", limit.to_string())
+        .add_attributes(audit_state(&env, false, true)))
+}
+
+/// Returns true if `account` has never executed, holds nothing and authorized no one
+fn is_fresh_account(deps: Deps, account: &Addr) -> StdResult<bool> {
+    if LAST_ACTIVITY.has(deps.storage, account) {
+        return Ok(false);
+    }
+    for denom in ACCEPTED_DENOMS.load(deps.storage)? {
+        if !BALANCES.may_load(deps.storage, (&denom, account))?.unwrap_or_default().is_zero() {
+            return Ok(false);
+        }
+    }
+    let has_authorizations = AUTHORIZED_SPENDERS
+        .prefix(account)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    Ok(!has_authorizations)
+}
+
+/// Validates authorization terms and stores the authorization of `spender` by `owner`
+///
+/// Returns the validated spender address.
+fn grant_authorization(
+    deps: DepsMut,
+    env: &Env,
+    owner: &Addr,
+    spender: &str,
+    terms: Option<AuthorizationTerms>,
+) -> Result<Addr, ContractError> {
+    let spender_addr = deps.api.addr_validate(spender)?;
+
+    // A frozen owner cannot create new delegations
+    if is_frozen(deps.as_ref(), owner)? {
+        return Err(ContractError::Frozen {});
+    }
+
+    // Prevent self-authorization (owner cannot authorize themselves as spender)
+    if *owner == spender_addr {
+        return Err(ContractError::Std(StdError::generic_err("Cannot authorize self as spender")));
+    }
+
+    if is_blocked(deps.as_ref(), &spender_addr)? {
+        return Err(ContractError::SpenderBlocked {});
+    }
+
+    let terms = terms.unwrap_or_default();
+    if matches!(terms.expires_at_height, Some(height) if height <= env.block.height) {
+        return Err(ContractError::Std(StdError::generic_err("Expiry height must be in the future")));
+    }
+    match (terms.limit_per_window, terms.window_seconds) {
+        (None, None) => {}
+        (Some(_), Some(window_seconds)) if window_seconds > 0 => {}
+        _ => {
+            return Err(ContractError::Std(StdError::generic_err(
+                "limit_per_window and a nonzero window_seconds must be set together",
+            )))
+        }
+    }
+    if let Some(metadata) = &terms.metadata {
+        if metadata.len() > MAX_METADATA_BYTES {
+            return Err(ContractError::MetadataTooLarge { size: metadata.len(), max: MAX_METADATA_BYTES });
+        }
+    }
+    if matches!(&terms.secret_hash, Some(hash) if hash.len() != SECRET_HASH_BYTES) {
+        return Err(ContractError::Std(StdError::generic_err("secret_hash must be a 32-byte sha256 digest")));
+    }
+    if let Some(interval_seconds) = terms.refill_interval_seconds {
+        if interval_seconds > MAX_REFILL_INTERVAL_SECONDS {
+            return Err(ContractError::InvalidRefillInterval { interval_seconds, max: MAX_REFILL_INTERVAL_SECONDS });
+        }
+    }
+    let refill = match (terms.refill_amount, terms.refill_interval_seconds) {
+        (None, None) => None,
+        (Some(amount), Some(interval_seconds)) if interval_seconds > 0 => Some(RecurringRefill {
+            amount,
+            interval_seconds,
+            next_refill_at: env.block.time.plus_seconds(interval_seconds),
+        }),
+        _ => {
+            return Err(ContractError::Std(StdError::generic_err(
+                "refill_amount and a nonzero refill_interval_seconds must be set together",
+            )))
+        }
+    };
+
     // Save the authorization to state
-    AUTHORIZED_SPENDERS.save(deps.storage, (&owner, &spender_addr), &true)?;
+    let authorization = Authorization {
+        expires_at_height: terms.expires_at_height,
+        max_per_tx: terms.max_per_tx,
+        metadata: terms.metadata,
+        limit_per_window: terms.limit_per_window,
+        window_seconds: terms.window_seconds,
+        secret_hash: terms.secret_hash,
+        allowance: terms.allowance.or(terms.refill_amount),
+        starts_at: terms.starts_at,
+        refill,
+        cooldown_seconds: terms.cooldown_seconds,
+    };
+    save_authorization(deps.storage, owner, &spender_addr, &authorization)?;
+    if let Some(refill) = &authorization.refill {
+        REFILL_QUEUE.save(deps.storage, (refill.next_refill_at.seconds(), owner, &spender_addr), &())?;
+    }
+    record_ledger(deps.storage, env, "authorize", owner, Some(&spender_addr), None)?;
+    Ok(spender_addr)
+}
+
+/// Adds to a spender's remaining allowance, creating a limited authorization if needed
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information (block height/time)
+/// * `info` - Contains the owner's address (message sender)
+/// * `spender` - Address string of the spender
+/// * `amount` - Amount to add to the allowance
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with the new allowance or error
+fn execute_increase_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+    let owner = info.sender;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+
+    let existing = AUTHORIZED_SPENDERS.may_load(deps.storage, (&owner, &spender_addr))?;
+    let previous_state = existing.is_some();
+    let allowance = match existing {
+        Some(authorization) => {
+            let allowance = authorization
+                .allowance
+                .map(|allowance| allowance.checked_add(amount))
+                .transpose()
+                .map_err(StdError::from)?;
+            save_authorization(deps.storage, &owner, &spender_addr, &Authorization { allowance, ..authorization })?;
+            allowance
+        }
+        None => {
+            let terms = AuthorizationTerms { allowance: Some(amount), ..Default::default() };
+            grant_authorization(deps, &env, &owner, &spender, Some(terms))?;
+            Some(amount)
+        }
+    };
+
     Ok(Response::new()
-        .add_attribute("action", "authorize_spender")
+        .add_attribute("action", "increase_allowance")
         .add_attribute("owner", owner)
-        .add_attribute("spender", spender_addr))
+        .add_attribute("spender", spender_addr)
+        .add_attribute("allowance", allowance.map_or_else(|| "unlimited".to_string(), |a| a.to_string()))
+        .add_attributes(audit_state(&env, previous_state, true)))
+}
+
+/// Subtracts from a spender's remaining allowance, removing the authorization at zero
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, recorded in the audit attributes
+/// * `info` - Contains the owner's address (message sender)
+/// * `spender` - Address string of the spender
+/// * `amount` - Amount to subtract from the allowance
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with the new allowance or error
+fn execute_decrease_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let owner = info.sender;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let authorization = AUTHORIZED_SPENDERS
+        .may_load(deps.storage, (&owner, &spender_addr))?
+        .ok_or_else(|| StdError::generic_err("Spender has no allowance to decrease"))?;
+    let Some(allowance) = authorization.allowance else {
+        return Err(ContractError::Std(StdError::generic_err("Cannot decrease an unlimited allowance")));
+    };
+
+    let remaining = allowance.saturating_sub(amount);
+    if remaining.is_zero() {
+        remove_authorization(deps.storage, &owner, &spender_addr)?;
+        record_ledger(deps.storage, &env, "revoke", &owner, Some(&spender_addr), None)?;
+    } else {
+        let authorization = Authorization { allowance: Some(remaining), ..authorization };
+        save_authorization(deps.storage, &owner, &spender_addr, &authorization)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "decrease_allowance")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender_addr)
+        .add_attribute("allowance", remaining.to_string())
+        .add_attributes(audit_state(&env, true, !remaining.is_zero())))
+}
+
+/// Restricts the denoms an authorized spender may spend from the sender
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `info` - Contains the owner's address (message sender)
+/// * `spender` - Address string of the spender
+/// * `denoms` - Allowed denoms; an empty list allows every accepted denom
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_spender_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    spender: String,
+    denoms: Vec<String>,
+) -> Result<Response, ContractError> {
+    let owner = info.sender;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if !AUTHORIZED_SPENDERS.has(deps.storage, (&owner, &spender_addr)) {
+        return Err(ContractError::Std(StdError::generic_err("Spender is not authorized")));
+    }
+
+    let mut allowed: Vec<String> = Vec::with_capacity(denoms.len());
+    for denom in denoms {
+        let denom = resolve_denom(deps.as_ref(), Some(denom))?;
+        if !allowed.contains(&denom) {
+            allowed.push(denom);
+        }
+    }
+    if allowed.is_empty() {
+        SPENDER_DENOMS.remove(deps.storage, (&owner, &spender_addr));
+    } else {
+        SPENDER_DENOMS.save(deps.storage, (&owner, &spender_addr), &allowed)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_spender_denoms")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender_addr)
+        .add_attribute("denoms", allowed.join(",")))
+}
+
+/// Bounds an unlimited authorization with an allowance and an optional expiry
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, used to validate the expiry height and recorded
+///   in the audit attributes and the activity ledger
+/// * `info` - Contains the owner's address (message sender)
+/// * `spender` - Address string of the spender
+/// * `limit` - Allowance the spender keeps from now on
+/// * `expires` - Block height at which the authorization expires, if any
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_limit_spender(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    limit: Uint128,
+    expires: Option<u64>,
+) -> Result<Response, ContractError> {
+    if limit.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Limit must be positive")));
+    }
+    if matches!(expires, Some(height) if height <= env.block.height) {
+        return Err(ContractError::Std(StdError::generic_err("Expiry height must be in the future")));
+    }
+    let owner = info.sender;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let authorization = AUTHORIZED_SPENDERS
+        .may_load(deps.storage, (&owner, &spender_addr))?
+        .ok_or_else(|| StdError::generic_err("Spender has no authorization to limit"))?;
+    if authorization.allowance.is_some() {
+        return Err(ContractError::Std(StdError::generic_err("Authorization is already limited")));
+    }
+
+    let authorization = Authorization {
+        allowance: Some(limit),
+        expires_at_height: expires.or(authorization.expires_at_height),
+        ..authorization
+    };
+    save_authorization(deps.storage, &owner, &spender_addr, &authorization)?;
+    record_ledger(deps.storage, &env, "limit", &owner, Some(&spender_addr), None)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "limit_spender")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender_addr)
+        .add_attribute("allowance", limit.to_string())
+        .add_attribute("expires_at_height", authorization.expires_at_height.map_or_else(|| "never".to_string(), |h| h.to_string()))
+        .add_attributes(audit_state(&env, true, true)))
 }
 
 fn execute_revoke_spender(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     spender: String,
+    owner: Option<String>,
 ) -> Result<Response, ContractError> {
-    let owner = info.sender.clone();
+    // Only the owner or one of its managers may revoke a spender for the account
+    let owner = managed_owner(deps.as_ref(), &info.sender, owner)?;
     let spender_addr = deps.api.addr_validate(&spender)?;
-    // Only allow the owner to revoke a spender for their own account
-    if owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    let previous_state = AUTHORIZED_SPENDERS.has(deps.storage, (&owner, &spender_addr));
+    remove_authorization(deps.storage, &owner, &spender_addr)?;
+    // Revoking a pair that was never authorized is a no-op, not a ledger event
+    if previous_state {
+        record_ledger(deps.storage, &env, "revoke", &owner, Some(&spender_addr), None)?;
+    }
+    Ok(authorization_audit(Response::new(), &env, "revoke_spender", &owner, &spender_addr, previous_state, false))
+}
+
+/// Revokes every spender the sender has authorized
+///
+/// Spender keys are collected from the owner's prefix first and removed afterwards,
+/// since the map cannot be mutated while it is being iterated.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains the owner's address (message sender)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with the revoked count or error
+fn execute_revoke_all_spenders(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let owner = info.sender;
+    let spenders: Vec<Addr> = AUTHORIZED_SPENDERS
+        .prefix(&owner)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    let mut response = Response::new();
+    for spender in &spenders {
+        remove_authorization(deps.storage, &owner, spender)?;
+        record_ledger(deps.storage, &env, "revoke", &owner, Some(spender), None)?;
+        response = response.add_event(authorization_audit_event(&env, &owner, spender, true, false));
     }
-    AUTHORIZED_SPENDERS.remove(deps.storage, (&owner, &spender_addr));
-    Ok(Response::new().add_attribute("action", "revoke_spender").add_attribute("owner", owner).add_attribute("spender", spender_addr))
+    Ok(response
+        .add_attribute("action", "revoke_all_spenders")
+        .add_attribute("owner", owner)
+        .add_attribute("revoked", spenders.len().to_string()))
 }
 
 /// Spends tokens from an owner's account to the message sender's account
@@ -132,53 +867,1878 @@ fn execute_revoke_spender(
 /// 2. An authorized spender to spend from the owner's account
 ///
 /// The function verifies authorization, checks balance sufficiency,
-/// updates the owner's balance, and credits the spender's account.
+/// updates the owner's balance, and credits the spender's account. With minimal
+/// events enabled, the response only carries the action and a hashed reference.
 ///
 /// # Arguments
 /// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, used to check authorization expiry
 /// * `info` - Contains the spender's address (message sender)
 /// * `owner` - Address string of the account that owns the tokens
 /// * `amount` - Number of tokens to spend
+/// * `secret` - Preimage of the authorization's secret hash, if it has one
+/// * `denom` - Denom to spend, defaulting to the primary denom
 ///
 /// # Returns
 /// * `Result<Response, ContractError>` - Success response with event attributes or error
 fn execute_spend_from(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     owner: String,
-    amount: u128,
+    amount: Uint128,
+    secret: Option<Binary>,
+    denom: Option<String>,
 ) -> Result<Response, ContractError> {
+    // A zero spend would only emit an empty event
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
     let spender = info.sender;
     let owner_addr = deps.api.addr_validate(&owner)?;
+    let denom = resolve_denom(deps.as_ref(), denom)?;
     
     // Verify spending authorization
-    // Either the spender is the owner (self-spending) or has explicit authorization
-    let is_owner = spender == owner_addr;
-    let is_authorized = AUTHORIZED_SPENDERS.may_load(deps.storage, (&owner_addr, &spender))?.unwrap_or(false);
-    if !is_owner && !is_authorized {
-        return Err(ContractError::Unauthorized {});
-    }
-    
-    // Check if owner has sufficient balance
-    let mut balance = BALANCES.may_load(deps.storage, &owner_addr)?.unwrap_or(0);
-    if balance < amount {
-        return Err(ContractError::Std(cosmwasm_std::StdError::generic_err("Insufficient balance")));
+    let authorization = assert_can_spend(deps.as_ref(), &env, &owner_addr, &spender, &denom, amount, secret.as_ref())?;
+    if spender != owner_addr {
+        assert_within_receive_cap(deps.storage, &spender, &denom, amount)?;
+        record_immediate_outflow(deps.storage, &env, &owner_addr, &denom, amount)?;
     }
     
-    // Update owner's balance by subtracting the spent amount
-    balance -= amount;
-    BALANCES.save(deps.storage, &owner_addr, &balance)?;
+    // Debit the owner for the amount plus any spend fee, failing if the balance is insufficient
+    let fee = spend_fee(deps.storage, authorization.as_ref(), amount)?;
+    let remaining = debit(deps.storage, &owner_addr, &denom, checked_total(amount, fee)?)?;
+    collect_spend_fee(deps.storage, &owner_addr, &denom, fee)?;
     
-    // Credit the tokens to the spender's account
-    // Note: In a real contract with actual token transfers,
-    // you might use BankMsg to send tokens instead
-    let prev = BALANCES.may_load(deps.storage, &spender)?.unwrap_or(0);
-    BALANCES.save(deps.storage, &spender, &(prev + amount))?;
+    // Credit the tokens to the spender's account. Spends stay inside the ledger;
+    // tokens only leave the contract through `debit_and_send`
+    let spender_balance = credit(deps.storage, &spender, &denom, amount)?;
+    record_spent(deps.storage, &env, &owner_addr, &spender, &denom, authorization.as_ref(), amount)?;
+    record_ledger(deps.storage, &env, "spend", &owner_addr, Some(&spender), Some((&denom, amount)))?;
+    // Re-read rather than reuse `remaining`: the owner may also be the spender or the fee-collecting admin
+    let owner_balance = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+
+    if MINIMAL_EVENTS.load(deps.storage)? {
+        return Ok(Response::new()
+            .add_attribute("action", "spend_from")
+            .add_attribute("ref", spend_reference(&env, &owner_addr, &spender, &denom, amount)));
+    }
     
-    // Return success response with event attributes
+    // Return success response with event attributes and a structured spend event
     Ok(Response::new()
+        .add_event(spend_event(&owner_addr, &spender, &spender, &denom, amount, remaining))
         .add_attribute("action", "spend_from")
         .add_attribute("owner", owner_addr)
         .add_attribute("spender", spender)
-        .add_attribute("amount", amount.to_string()))
+        .add_attributes([
+            amount_attr("amount", amount),
+            amount_attr("fee", fee),
+            amount_attr("owner_new_balance", owner_balance),
+            amount_attr("spender_new_balance", spender_balance),
+        ])
+        .add_attribute("block_height", env.block.height.to_string()))
+}
+
+/// Rejects a spend, refund or seed that would push `recipient`'s balance in `denom` over
+/// the receive cap
+pub(crate) fn assert_within_receive_cap(storage: &dyn Storage, recipient: &Addr, denom: &str, amount: Uint128) -> Result<(), ContractError> {
+    let Some(cap) = MAX_RECEIVED_PER_ACCOUNT.may_load(storage)? else {
+        return Ok(());
+    };
+    let balance = BALANCES.may_load(storage, (denom, recipient))?.unwrap_or_default();
+    if balance.checked_add(amount).map_err(StdError::from)? > cap {
+        return Err(ContractError::ReceiveCapExceeded {});
+    }
+    Ok(())
+}
+
+/// Rejects an amount finer than the precision configured for `denom`
+///
+/// With a configured precision the amount must be a whole multiple of
+/// 10^(decimals - precision) base units; denoms without one accept any amount.
+fn assert_within_precision(storage: &dyn Storage, denom: &str, amount: Uint128) -> Result<(), ContractError> {
+    let denom_precisions = DENOM_PRECISIONS.may_load(storage)?.unwrap_or_default();
+    let Some(denom_precision) = denom_precisions.iter().find(|p| p.denom == denom) else {
+        return Ok(());
+    };
+    let step = Uint128::new(10u128.pow(denom_precision.decimals - denom_precision.precision));
+    if !(amount % step).is_zero() {
+        return Err(ContractError::PrecisionExceeded {});
+    }
+    Ok(())
+}
+
+/// Pays tokens from an owner's account to an external recipient's wallet
+///
+/// Authorization, fee and usage tracking match `execute_spend_from`; the payout
+/// leaves the contract through `debit_and_send` rather than being credited, so it
+/// counts towards the owner's immediate outflows under a withdrawal timelock.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, used to check authorization expiry
+/// * `info` - Contains the spender's address (message sender)
+/// * `owner` - Address string of the account that owns the tokens
+/// * `recipient` - Address string receiving the bank transfer
+/// * `amount` - Number of tokens to pay
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Response carrying the bank transfer or error
+fn execute_spend_from_to(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+    let spender = info.sender;
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let denom = DENOM.load(deps.storage)?;
+
+    let authorization = assert_can_spend(deps.as_ref(), &env, &owner_addr, &spender, &denom, amount, None)?;
+    record_immediate_outflow(deps.storage, &env, &owner_addr, &denom, amount)?;
+    let fee = spend_fee(deps.storage, authorization.as_ref(), amount)?;
+    let (send, _) = debit_and_send(deps.storage, &owner_addr, &recipient_addr, &denom, amount)?;
+    let remaining = debit(deps.storage, &owner_addr, &denom, fee)?;
+    collect_spend_fee(deps.storage, &owner_addr, &denom, fee)?;
+    record_spent(deps.storage, &env, &owner_addr, &spender, &denom, authorization.as_ref(), amount)?;
+    record_ledger(deps.storage, &env, "spend", &owner_addr, Some(&recipient_addr), Some((&denom, amount)))?;
+    // Re-read rather than reuse `remaining`: the owner may also be the fee-collecting admin
+    let owner_balance = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+
+    if MINIMAL_EVENTS.load(deps.storage)? {
+        return Ok(Response::new()
+            .add_message(send)
+            .add_attribute("action", "spend_from_to")
+            .add_attribute("ref", spend_reference(&env, &owner_addr, &spender, &denom, amount)));
+    }
+
+    Ok(Response::new()
+        .add_message(send)
+        .add_event(spend_event(&owner_addr, &spender, &recipient_addr, &denom, amount, remaining))
+        .add_attribute("action", "spend_from_to")
+        .add_attribute("owner", owner_addr)
+        .add_attribute("spender", spender)
+        .add_attribute("recipient", recipient_addr)
+        .add_attributes([amount_attr("amount", amount), amount_attr("fee", fee), amount_attr("owner_new_balance", owner_balance)]))
+}
+
+/// Spends tokens from an owner's account on the strength of the owner's signature
+///
+/// The signature must verify against the owner's registered key over the payload
+/// built by `signed_spend_hash`, which binds the contract, the relayer, the spend
+/// and the owner's current nonce. The nonce is then incremented; if the spend fails
+/// the whole transaction reverts, so it is only consumed by a successful spend.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access, address validation and signature verification
+/// * `env` - Environment information, used to check authorization expiry
+/// * `info` - Contains the relaying spender's address (message sender)
+/// * `owner` - Address string of the account that owns the tokens
+/// * `amount` - Number of tokens to spend
+/// * `nonce` - If given, must equal the owner's current nonce
+/// * `denom` - Denom to spend, defaulting to the primary denom
+/// * `signature` - Owner's 64-byte secp256k1 signature over the payload hash
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+#[allow(clippy::too_many_arguments)]
+fn execute_spend_from_signed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    amount: Uint128,
+    nonce: Option<u64>,
+    denom: Option<String>,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let expected = NONCES.may_load(deps.storage, &owner_addr)?.unwrap_or(0);
+    if let Some(nonce) = nonce {
+        if nonce != expected {
+            return Err(ContractError::InvalidNonce { expected, got: nonce });
+        }
+    }
+    let pubkey = SIGNING_KEYS.may_load(deps.storage, &owner_addr)?.ok_or(ContractError::NoSigningKey {})?;
+    let resolved_denom = resolve_denom(deps.as_ref(), denom.clone())?;
+    let hash = signed_spend_hash(&env, &owner_addr, &info.sender, &resolved_denom, amount, expected);
+    let verified = deps.api.secp256k1_verify(&hash, &signature, &pubkey).unwrap_or(false);
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+    NONCES.save(deps.storage, &owner_addr, &(expected + 1))?;
+
+    let response = execute_spend_from(deps, env, info, owner, amount, None, denom)?;
+    Ok(response.add_attribute("nonce", expected.to_string()))
+}
+
+/// Hashes the payload an owner signs to approve a SpendFromSigned
+///
+/// The payload is `{contract}:{owner}:{relayer}:{denom}:{amount}:{nonce}`, so a
+/// signature is only valid for one spend, relayed by one spender, on one contract.
+fn signed_spend_hash(env: &Env, owner: &Addr, relayer: &Addr, denom: &str, amount: Uint128, nonce: u64) -> Vec<u8> {
+    let payload = format!("{}:{}:{}:{}:{}:{}", env.contract.address, owner, relayer, denom, amount, nonce);
+    Sha256::digest(payload.as_bytes()).to_vec()
+}
+
+/// Registers or removes the key that signs the sender's SpendFromSigned payloads
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `info` - Contains the owner's address (message sender)
+/// * `pubkey` - secp256k1 public key, compressed or uncompressed; `None` removes it
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_signing_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    match &pubkey {
+        Some(pubkey) if pubkey.len() != 33 && pubkey.len() != 65 => {
+            return Err(ContractError::Std(StdError::generic_err("pubkey must be a 33 or 65-byte secp256k1 public key")));
+        }
+        Some(pubkey) => SIGNING_KEYS.save(deps.storage, &info.sender, pubkey)?,
+        None => SIGNING_KEYS.remove(deps.storage, &info.sender),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_signing_key")
+        .add_attribute("owner", info.sender)
+        .add_attribute("pubkey", pubkey.map_or_else(|| "none".to_string(), |pubkey| pubkey.to_base64())))
+}
+
+/// Converts a USD amount into the primary denom via the price oracle and spends it
+///
+/// The conversion rounds down, so the owner never pays more than `usd_amount` at the
+/// oracle's price. The spend itself runs through `execute_batch_spend_from` with a
+/// single payout, so authorization, fees and events match an ordinary spend.
+///
+/// The sender's authorization is checked before the oracle is queried, so only
+/// the owner or one of their spenders can trip the pause below. When oracle
+/// auto-pause is enabled, a failing oracle query pauses USD spends for everyone;
+/// the transaction then succeeds so the pause is kept, but spends nothing, which
+/// the response reports as `spent = 0`. Without auto-pause the spend is rejected.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and the oracle querier
+/// * `env` - Environment information, used to judge price staleness
+/// * `info` - Contains the spender's address (message sender)
+/// * `owner` - Address string of the account that owns the tokens
+/// * `usd_amount` - Amount to spend in micro-USD
+/// * `recipient` - Address string credited with the converted amount
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_spend_usd(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    usd_amount: Uint128,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    if USD_SPENDS_PAUSED.load(deps.storage)? {
+        return Err(ContractError::UsdSpendsPaused {});
+    }
+    let oracle = load_price_oracle(deps.storage)?;
+    let denom = DENOM.load(deps.storage)?;
+    // The amount is unknown until priced; the full check runs again with it below
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_can_spend(deps.as_ref(), &env, &owner_addr, &info.sender, &denom, Uint128::zero(), None)?;
+    let price = match oracle_price(deps.as_ref(), &oracle, &env.block, &denom) {
+        Ok(price) => price.ok_or(ContractError::BadPrice {})?,
+        // Fail safe: keep the pause by succeeding without spending anything
+        Err(err) if ORACLE_AUTO_PAUSE.load(deps.storage)? => {
+            USD_SPENDS_PAUSED.save(deps.storage, &true)?;
+            return Ok(Response::new()
+                .add_attribute("action", "spend_usd")
+                .add_attribute("spent", "0")
+                .add_attribute("usd_spends_paused", "true")
+                .add_attribute("oracle_error", err.to_string()));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let amount = usd_to_denom(usd_amount, price)?;
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+
+    let response = execute_batch_spend_from(deps, env, info, owner, vec![(recipient, amount)], Some(denom))?;
+    Ok(response
+        .add_attributes([amount_attr("usd_amount", usd_amount)])
+        .add_attribute("price", price.to_string()))
+}
+
+/// Converts micro-USD into base units of a denom priced at `price` micro-USD each
+fn usd_to_denom(usd_amount: Uint128, price: Decimal) -> StdResult<Uint128> {
+    usd_amount
+        .checked_div_floor(price)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+/// Spends tokens from an owner's account to multiple recipients atomically
+///
+/// Every recipient address is validated and the payout total is computed before
+/// any balance changes, so the batch either fully succeeds or changes nothing.
+/// Recipients are credited in the order given.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, used to check authorization expiry
+/// * `info` - Contains the spender's address (message sender)
+/// * `owner` - Address string of the account that owns the tokens
+/// * `payouts` - List of (recipient address, amount) pairs to credit
+/// * `denom` - Denom of every payout, defaulting to the primary denom
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_batch_spend_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    payouts: Vec<(String, Uint128)>,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    let spender = info.sender;
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let denom = resolve_denom(deps.as_ref(), denom)?;
+    let (recipients, total, authorization) = check_batch_spend(deps.as_ref(), &env, &owner_addr, &spender, &denom, payouts)?;
+    let outflow = recipients
+        .iter()
+        .filter(|(recipient_addr, _)| *recipient_addr != owner_addr)
+        .try_fold(Uint128::zero(), |sum, (_, amount)| sum.checked_add(*amount))
+        .map_err(StdError::from)?;
+    if !outflow.is_zero() {
+        record_immediate_outflow(deps.storage, &env, &owner_addr, &denom, outflow)?;
+    }
+
+    let fee = spend_fee(deps.storage, authorization.as_ref(), total)?;
+    let remaining = debit(deps.storage, &owner_addr, &denom, checked_total(total, fee)?)?;
+    collect_spend_fee(deps.storage, &owner_addr, &denom, fee)?;
+    record_spent(deps.storage, &env, &owner_addr, &spender, &denom, authorization.as_ref(), total)?;
+
+    // Credit each recipient's account
+    for (recipient_addr, amount) in &recipients {
+        credit(deps.storage, recipient_addr, &denom, *amount)?;
+        record_ledger(deps.storage, &env, "spend", &owner_addr, Some(recipient_addr), Some((&denom, *amount)))?;
+    }
+
+    // Emit one spend event per payout, each reporting the owner's final balance
+    let events = recipients
+        .iter()
+        .map(|(recipient_addr, amount)| spend_event(&owner_addr, &spender, recipient_addr, &denom, *amount, remaining));
+
+    Ok(Response::new()
+        .add_events(events)
+        .add_attribute("action", "batch_spend_from")
+        .add_attribute("owner", owner_addr)
+        .add_attribute("spender", spender)
+        .add_attribute("recipients", recipients.len().to_string())
+        .add_attributes([amount_attr("total", total), amount_attr("fee", fee)]))
+}
+
+/// A batch that passed its checks: the validated payouts, their total and the
+/// authorization it spends under
+pub(crate) type CheckedBatch = (Vec<(Addr, Uint128)>, Uint128, Option<Authorization>);
+
+/// Runs BatchSpendFrom's checks without changing state
+///
+/// Validates every recipient and the precision of every payout, sums the payouts,
+/// checks the spender's authorization against the total and applies the receive
+/// cap to the sum paid to each recipient. The owner's balance is not checked here;
+/// the debit does that.
+pub(crate) fn check_batch_spend(
+    deps: Deps,
+    env: &Env,
+    owner_addr: &Addr,
+    spender: &Addr,
+    denom: &str,
+    payouts: Vec<(String, Uint128)>,
+) -> Result<CheckedBatch, ContractError> {
+    if payouts.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err("Batch must contain at least one payout")));
+    }
+
+    // Validate every recipient and sum the payouts before touching any balance
+    let mut total = Uint128::zero();
+    let mut recipients = Vec::with_capacity(payouts.len());
+    for (recipient, amount) in payouts {
+        let recipient_addr = deps.api.addr_validate(&recipient)?;
+        if amount.is_zero() {
+            return Err(ContractError::Std(StdError::generic_err("Each payout amount must be positive")));
+        }
+        // Each payout must be within precision, not just the total checked below
+        assert_within_precision(deps.storage, denom, amount)?;
+        total = total
+            .checked_add(amount)
+            .map_err(|_| StdError::generic_err("Batch total overflows"))?;
+        recipients.push((recipient_addr, amount));
+    }
+
+    // Authorization is checked once for the whole batch, against its total
+    let authorization = assert_can_spend(deps, env, owner_addr, spender, denom, total, None)?;
+    // A recipient paid several times in the batch is capped on the sum of its payouts
+    let mut received: Vec<(&Addr, Uint128)> = vec![];
+    for (recipient_addr, amount) in &recipients {
+        match received.iter_mut().find(|(addr, _)| *addr == recipient_addr) {
+            Some((_, sum)) => *sum += *amount,
+            None => received.push((recipient_addr, *amount)),
+        }
+    }
+    for (recipient_addr, amount) in received {
+        if recipient_addr != owner_addr {
+            assert_within_receive_cap(deps.storage, recipient_addr, denom, amount)?;
+        }
+    }
+
+    Ok((recipients, total, authorization))
+}
+
+/// Adds the sent native tokens to the keeper reward pool
+///
+/// Anyone may fund the pool. The same funds validation as deposits applies, and
+/// the funds must be in the primary denom, which is what keeper rewards are paid in.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains sender address and the funds sent with the transaction
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_fund_reward_pool(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let (denom, amount) = validate_deposit_funds(&deps, &info)?;
+    let primary = DENOM.load(deps.storage)?;
+    if denom != primary {
+        return Err(ContractError::InvalidDenom { expected: primary, got: denom });
+    }
+    let pool = REWARD_POOL.load(deps.storage)?;
+    REWARD_POOL.save(deps.storage, &(pool + amount))?;
+    record_ledger(deps.storage, &env, "fund_reward_pool", &info.sender, None, Some((&denom, amount)))?;
+    Ok(Response::new()
+        .add_attribute("action", "fund_reward_pool")
+        .add_attribute("from", info.sender)
+        .add_attributes([amount_attr("amount", amount)]))
+}
+
+/// Removes expired authorizations and rewards the calling keeper
+///
+/// Walks the expiry queue in height order and stops at the first entry that has
+/// not expired yet, so at most `limit` entries are read. Entries left behind by a
+/// revoked authorization or a changed expiry are dropped without a removal or
+/// reward. When a keeper reward is configured, the number of entries read is
+/// further bounded by what the reward pool can pay, and the caller's balance is
+/// credited with the reward for each authorization removed.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, used to determine which entries have expired
+/// * `info` - Contains the keeper's address (message sender)
+/// * `limit` - Maximum number of queue entries to process (defaults to 10, capped at 30)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_cleanup_expired(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let reward = KEEPER_REWARD.load(deps.storage)?;
+    let pool = REWARD_POOL.load(deps.storage)?;
+    let mut limit = limit.unwrap_or(DEFAULT_CLEANUP_LIMIT).min(MAX_CLEANUP_LIMIT) as usize;
+    if let Ok(affordable) = pool.checked_div(reward) {
+        // Never remove more entries than the pool can reward
+        limit = limit.min(usize::try_from(affordable.u128()).unwrap_or(usize::MAX));
+    }
+
+    // Collect expired keys first, then remove, to avoid mutating the queue while iterating it
+    let height = env.block.height;
+    let expired: Vec<(u64, Addr, Addr)> = EXPIRY_QUEUE
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take_while(|key| !matches!(key, Ok((expires_at, _, _)) if *expires_at > height))
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut cleaned: u128 = 0;
+    let mut response = Response::new();
+    for (expires_at, owner, spender) in &expired {
+        EXPIRY_QUEUE.remove(deps.storage, (*expires_at, owner, spender));
+        let Some(authorization) = AUTHORIZED_SPENDERS.may_load(deps.storage, (owner, spender))? else {
+            continue;
+        };
+        if authorization.expires_at_height != Some(*expires_at) {
+            continue;
+        }
+        remove_authorization(deps.storage, owner, spender)?;
+        record_ledger(deps.storage, &env, "expire", owner, Some(spender), None)?;
+        response = response.add_event(authorization_audit_event(&env, owner, spender, true, false));
+        cleaned += 1;
+    }
+
+    // Pay the keeper out of the reward pool
+    let paid = reward * Uint128::new(cleaned);
+    if !paid.is_zero() {
+        let denom = DENOM.load(deps.storage)?;
+        REWARD_POOL.save(deps.storage, &(pool - paid))?;
+        credit(deps.storage, &info.sender, &denom, paid)?;
+        record_ledger(deps.storage, &env, "keeper_reward", &info.sender, None, Some((&denom, paid)))?;
+    }
+
+    Ok(response
+        .add_attribute("action", "cleanup_expired")
+        .add_attribute("keeper", info.sender)
+        .add_attribute("cleaned", cleaned.to_string())
+        .add_attribute("reward", paid.to_string()))
+}
+
+/// Refills due recurring allowances and rewards the calling keeper
+///
+/// Walks the refill queue in due-time order and stops at the first entry that is
+/// not yet due, so at most `limit` entries are read. Entries left behind by a
+/// revoked, expired or re-granted authorization are dropped without a refill or
+/// reward. A refilled authorization is rescheduled one interval after the current
+/// block, so a late keeper never triggers back-to-back refills.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, used to determine which refills are due
+/// * `info` - Contains the keeper's address (message sender)
+/// * `limit` - Maximum number of queue entries to process (defaults to 10, capped at 30)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_process_refills(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let reward = KEEPER_REWARD.load(deps.storage)?;
+    let pool = REWARD_POOL.load(deps.storage)?;
+    let mut limit = limit.unwrap_or(DEFAULT_CLEANUP_LIMIT).min(MAX_CLEANUP_LIMIT) as usize;
+    if let Ok(affordable) = pool.checked_div(reward) {
+        // Never process more entries than the pool can reward
+        limit = limit.min(usize::try_from(affordable.u128()).unwrap_or(usize::MAX));
+    }
+
+    // Collect due keys first, then update, to avoid mutating the queue while iterating it
+    let now = env.block.time.seconds();
+    let due: Vec<(u64, Addr, Addr)> = REFILL_QUEUE
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take_while(|key| !matches!(key, Ok((due_at, _, _)) if *due_at > now))
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut refilled: u128 = 0;
+    for (due_at, owner, spender) in &due {
+        REFILL_QUEUE.remove(deps.storage, (*due_at, owner, spender));
+        let Some(authorization) = AUTHORIZED_SPENDERS.may_load(deps.storage, (owner, spender))? else {
+            continue;
+        };
+        let Some(refill) = authorization.refill.clone() else {
+            continue;
+        };
+        if refill.next_refill_at.seconds() != *due_at || authorization.is_expired(&env.block) {
+            continue;
+        }
+        let next_refill_at = env.block.time.plus_seconds(refill.interval_seconds);
+        let authorization = Authorization {
+            allowance: Some(refill.amount),
+            refill: Some(RecurringRefill { next_refill_at, ..refill }),
+            ..authorization
+        };
+        save_authorization(deps.storage, owner, spender, &authorization)?;
+        REFILL_QUEUE.save(deps.storage, (next_refill_at.seconds(), owner, spender), &())?;
+        refilled += 1;
+    }
+
+    // Pay the keeper out of the reward pool
+    let paid = reward * Uint128::new(refilled);
+    if !paid.is_zero() {
+        let denom = DENOM.load(deps.storage)?;
+        REWARD_POOL.save(deps.storage, &(pool - paid))?;
+        credit(deps.storage, &info.sender, &denom, paid)?;
+        record_ledger(deps.storage, &env, "keeper_reward", &info.sender, None, Some((&denom, paid)))?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "process_refills")
+        .add_attribute("keeper", info.sender)
+        .add_attribute("refilled", refilled.to_string())
+        .add_attribute("reward", paid.to_string()))
+}
+
+/// Moves tokens from the sender's balance back to an owner's balance
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains the refunding account's address (message sender)
+/// * `owner` - Address string of the owner being refunded
+/// * `amount` - Number of tokens to refund
+/// * `denom` - Denom to refund, defaulting to the primary denom
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    amount: Uint128,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    if owner_addr == info.sender {
+        return Err(ContractError::Std(StdError::generic_err("Cannot refund to self")));
+    }
+    let denom = resolve_denom(deps.as_ref(), denom)?;
+    assert_not_frozen(deps.as_ref(), &info.sender)?;
+    assert_not_frozen(deps.as_ref(), &owner_addr)?;
+    assert_within_receive_cap(deps.storage, &owner_addr, &denom, amount)?;
+    record_immediate_outflow(deps.storage, &env, &info.sender, &denom, amount)?;
+
+    let remaining = debit(deps.storage, &info.sender, &denom, amount)?;
+    let owner_balance = credit(deps.storage, &owner_addr, &denom, amount)?;
+    record_ledger(deps.storage, &env, "refund", &owner_addr, Some(&info.sender), Some((&denom, amount)))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "refund")
+        .add_attribute("owner", owner_addr)
+        .add_attribute("refunder", info.sender)
+        .add_attribute("denom", denom)
+        .add_attributes([
+            amount_attr("amount", amount),
+            amount_attr("owner_new_balance", owner_balance),
+            amount_attr("refunder_new_balance", remaining),
+        ]))
+}
+
+/// Withdraws tokens from the sender's balance to their wallet
+///
+/// With a timelock configured, withdrawals that would take the sender's immediate
+/// outflows in the current window above the threshold are rejected; they must go
+/// through RequestWithdraw and ClaimWithdraw instead.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains the withdrawing owner's address (message sender)
+/// * `amount` - Number of tokens to withdraw
+/// * `denom` - Denom to withdraw, defaulting to the primary denom
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Response carrying the bank transfer or error
+fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+    let denom = resolve_denom(deps.as_ref(), denom)?;
+    assert_not_frozen(deps.as_ref(), &info.sender)?;
+    record_immediate_outflow(deps.storage, &env, &info.sender, &denom, amount)?;
+    send_withdrawal(deps, &env, &info.sender, &denom, amount, "withdraw")
+}
+
+/// Counts tokens leaving `account` without the timelock
+///
+/// Fails with `WithdrawTimelocked`, recording nothing, if the account's immediate
+/// outflows in `denom` over the current window would exceed the threshold. Besides
+/// withdrawals, every move of the account's balance to another account (delegated
+/// spends, batch payouts, refunds and sub-account seeds) passes through here, so
+/// neither splitting a withdrawal nor first spreading the balance over fresh
+/// accounts gets more than the threshold out per window.
+fn record_immediate_outflow(storage: &mut dyn Storage, env: &Env, account: &Addr, denom: &str, amount: Uint128) -> Result<(), ContractError> {
+    let Some(timelock) = WITHDRAW_TIMELOCK.may_load(storage)? else {
+        return Ok(());
+    };
+    let now = env.block.time.seconds();
+    let (start, sent) = match IMMEDIATE_OUTFLOWS.may_load(storage, (account, denom))? {
+        Some((start, sent)) if now < start.saturating_add(timelock.delay_seconds) => (start, sent),
+        _ => (now, Uint128::zero()),
+    };
+    let sent = sent.checked_add(amount).map_err(StdError::from)?;
+    if sent > timelock.threshold {
+        return Err(ContractError::WithdrawTimelocked { threshold: timelock.threshold });
+    }
+    IMMEDIATE_OUTFLOWS.save(storage, (account, denom), &(start, sent))?;
+    Ok(())
+}
+
+/// Requests a time-locked withdrawal from the sender's balance
+///
+/// Nothing is debited yet; the request only records what may be claimed and when.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, used to compute the unlock time
+/// * `info` - Contains the withdrawing owner's address (message sender)
+/// * `amount` - Number of tokens to withdraw
+/// * `denom` - Denom to withdraw, defaulting to the primary denom
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with the unlock time or error
+fn execute_request_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+    let denom = resolve_denom(deps.as_ref(), denom)?;
+    assert_not_frozen(deps.as_ref(), &info.sender)?;
+    let timelock = WITHDRAW_TIMELOCK
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("No withdrawal timelock is configured"))?;
+    let available = BALANCES.may_load(deps.storage, (&denom, &info.sender))?.unwrap_or_default();
+    if available < amount {
+        return Err(ContractError::InsufficientBalance { available, requested: amount });
+    }
+    let unlock_time = env.block.time.plus_seconds(timelock.delay_seconds);
+    PENDING_WITHDRAWALS.save(
+        deps.storage,
+        &info.sender,
+        &TimelockedWithdrawal { denom: denom.clone(), amount, unlock_time },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "request_withdraw")
+        .add_attribute("owner", info.sender)
+        .add_attribute("denom", denom)
+        .add_attributes([amount_attr("amount", amount)])
+        .add_attribute("unlock_time", unlock_time.seconds().to_string()))
+}
+
+/// Pays out the sender's requested withdrawal once its unlock time has passed
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, used to check the unlock time
+/// * `info` - Contains the withdrawing owner's address (message sender)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Response carrying the bank transfer or error
+fn execute_claim_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_not_frozen(deps.as_ref(), &info.sender)?;
+    let withdrawal = PENDING_WITHDRAWALS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NoPendingWithdrawal {})?;
+    if env.block.time < withdrawal.unlock_time {
+        return Err(ContractError::WithdrawLocked { unlock_time: withdrawal.unlock_time });
+    }
+    PENDING_WITHDRAWALS.remove(deps.storage, &info.sender);
+    send_withdrawal(deps, &env, &info.sender, &withdrawal.denom, withdrawal.amount, "claim_withdraw")
+}
+
+/// Debits `account` and pays `amount` to its wallet
+///
+/// The transfer is dispatched as a submessage replying to `WITHDRAW_REPLY_ID`, with
+/// the withdrawal kept in `PENDING_WITHDRAWAL` so the reply can re-credit it if the
+/// transfer fails.
+fn send_withdrawal(
+    deps: DepsMut,
+    env: &Env,
+    account: &Addr,
+    denom: &str,
+    amount: Uint128,
+    action: &str,
+) -> Result<Response, ContractError> {
+    let (send, remaining) = debit_and_send(deps.storage, account, account, denom, amount)?;
+    record_ledger(deps.storage, env, "withdraw", account, None, Some((denom, amount)))?;
+    PENDING_WITHDRAWAL.save(
+        deps.storage,
+        &PendingWithdrawal { account: account.clone(), denom: denom.to_string(), amount },
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_always(send, WITHDRAW_REPLY_ID))
+        .add_attribute("action", action)
+        .add_attribute("owner", account)
+        .add_attribute("denom", denom)
+        .add_attributes([amount_attr("amount", amount), amount_attr("new_balance", remaining)]))
+}
+
+/// Converts the sender's primary denom balance into the pending new denom
+///
+/// The whole balance is debited and `balance * ratio`, rounded down, is credited
+/// in the new denom. An account converts at most once, and an empty balance cannot
+/// be converted.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, used for the ledger entry
+/// * `info` - Contains the converting account (message sender)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_convert_balance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let account = info.sender;
+    let conversion = PENDING_DENOM
+        .may_load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("No denom conversion is pending"))?;
+    if CONVERTED.has(deps.storage, &account) {
+        return Err(ContractError::Std(StdError::generic_err("Balance already converted")));
+    }
+    assert_not_frozen(deps.as_ref(), &account)?;
+
+    let denom = DENOM.load(deps.storage)?;
+    let balance = BALANCES.may_load(deps.storage, (&denom, &account))?.unwrap_or_default();
+    if balance.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("No balance to convert")));
+    }
+    let converted = balance
+        .checked_mul_floor(conversion.ratio)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    debit(deps.storage, &account, &denom, balance)?;
+    credit(deps.storage, &account, &conversion.denom, converted)?;
+    CONVERTED.save(deps.storage, &account, &())?;
+    record_ledger(deps.storage, &env, "convert", &account, None, Some((&conversion.denom, converted)))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "convert_balance")
+        .add_attribute("account", account)
+        .add_attribute("from", format!("{}{}", balance, denom))
+        .add_attribute("to", format!("{}{}", converted, conversion.denom)))
+}
+
+/// Debits `account` and builds the bank transfer paying `amount` to `recipient`
+///
+/// Every handler that moves tokens out of the contract must go through this helper,
+/// which follows checks-effects-interactions: the balance check and `BALANCES.save`
+/// happen here, before the `BankMsg` exists, so the ledger is already final by the
+/// time the message is dispatched and no reply or callback can observe a balance
+/// that still includes the outgoing tokens. Returns the message and the remaining balance.
+pub fn debit_and_send(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    recipient: &Addr,
+    denom: &str,
+    amount: Uint128,
+) -> Result<(BankMsg, Uint128), ContractError> {
+    let remaining = debit(storage, account, denom, amount)?;
+    let send = BankMsg::Send { to_address: recipient.to_string(), amount: coins(amount.u128(), denom) };
+    Ok((send, remaining))
+}
+
+/// Adds `amount` to `account`'s balance in `denom` and returns the new balance
+///
+/// Every balance change goes through `credit` and `debit`, which read the stored
+/// balance immediately before writing it back. Nothing is cached across calls, so
+/// transactions landing in the same block simply apply in the order the chain
+/// executes them: a deposit followed by a spend sees the deposited funds, and a
+/// spend ordered before a deposit is checked against the pre-deposit balance.
+pub(crate) fn credit(storage: &mut dyn Storage, account: &Addr, denom: &str, amount: Uint128) -> StdResult<Uint128> {
+    let balance = BALANCES.may_load(storage, (denom, account))?.unwrap_or_default();
+    let updated = balance.checked_add(amount)?;
+    BALANCES.save(storage, (denom, account), &updated)?;
+    track_active_account(storage, balance, updated)?;
+    Ok(updated)
+}
+
+/// Subtracts `amount` from `account`'s balance in `denom` and returns the new balance
+///
+/// Fails without modifying state if the balance is insufficient.
+fn debit(storage: &mut dyn Storage, account: &Addr, denom: &str, amount: Uint128) -> Result<Uint128, ContractError> {
+    let balance = BALANCES.may_load(storage, (denom, account))?.unwrap_or_default();
+    let remaining = balance
+        .checked_sub(amount)
+        .map_err(|_| ContractError::InsufficientBalance { available: balance, requested: amount })?;
+    BALANCES.save(storage, (denom, account), &remaining)?;
+    track_active_account(storage, balance, remaining)?;
+    Ok(remaining)
+}
+
+/// Updates the active account counter when a balance moves to or from zero
+fn track_active_account(storage: &mut dyn Storage, before: Uint128, after: Uint128) -> StdResult<()> {
+    match (before.is_zero(), after.is_zero()) {
+        (true, false) => ACTIVE_ACCOUNTS.update(storage, |count| -> StdResult<_> { Ok(count + 1) })?,
+        (false, true) => ACTIVE_ACCOUNTS.update(storage, |count| -> StdResult<_> { Ok(count.saturating_sub(1)) })?,
+        _ => return Ok(()),
+    };
+    Ok(())
+}
+
+/// Transfers the admin role to a new address
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, used to reject the contract's own address
+/// * `info` - Contains the current admin's address (message sender)
+/// * `new_admin` - Address string of the incoming admin
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_update_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let new_admin_addr = deps.api.addr_validate(&new_admin)?;
+    if new_admin_addr == env.contract.address {
+        return Err(ContractError::InvalidAdmin {});
+    }
+    ADMIN.save(deps.storage, &new_admin_addr)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_admin")
+        .add_attribute("previous_admin", info.sender)
+        .add_attribute("new_admin", new_admin_addr))
+}
+
+/// Freezes or unfreezes an account
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `info` - Contains the admin's address (message sender)
+/// * `account` - Address string of the account to freeze or unfreeze
+/// * `frozen` - Whether the account is frozen from now on
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_frozen(
+    deps: DepsMut,
+    info: MessageInfo,
+    account: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let account_addr = deps.api.addr_validate(&account)?;
+    if frozen {
+        FROZEN.save(deps.storage, &account_addr, &true)?;
+    } else {
+        FROZEN.remove(deps.storage, &account_addr);
+    }
+    Ok(Response::new()
+        .add_attribute("action", if frozen { "freeze_account" } else { "unfreeze_account" })
+        .add_attribute("account", account_addr))
+}
+
+/// Revokes an owner's authorization of a spender on the admin's initiative
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `info` - Contains the admin's address (message sender)
+/// * `owner` - Address string of the owner whose authorization is revoked
+/// * `spender` - Address string of the revoked spender
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with audit attributes or error
+fn execute_admin_revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    spender: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let previous_state = AUTHORIZED_SPENDERS.has(deps.storage, (&owner_addr, &spender_addr));
+    remove_authorization(deps.storage, &owner_addr, &spender_addr)?;
+    if previous_state {
+        record_ledger(deps.storage, &env, "revoke", &owner_addr, Some(&spender_addr), None)?;
+    }
+    let response = Response::new().add_attribute("initiated_by", "admin").add_attribute("admin", info.sender);
+    Ok(authorization_audit(response, &env, "admin_revoke", &owner_addr, &spender_addr, previous_state, false))
+}
+
+/// Blocks or unblocks a spender contract-wide
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `info` - Contains the admin's address (message sender)
+/// * `spender` - Address string of the spender to block or unblock
+/// * `blocked` - Whether the spender is blocked from now on
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_blocked(
+    deps: DepsMut,
+    info: MessageInfo,
+    spender: String,
+    blocked: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if blocked {
+        SPENDER_BLOCKLIST.save(deps.storage, &spender_addr, &true)?;
+    } else {
+        SPENDER_BLOCKLIST.remove(deps.storage, &spender_addr);
+    }
+    Ok(Response::new()
+        .add_attribute("action", if blocked { "block_spender" } else { "unblock_spender" })
+        .add_attribute("spender", spender_addr))
+}
+
+/// Blocks a spender and revokes its authorizations across all owners
+///
+/// Uses the spender reverse index to find the owners that authorized the spender.
+/// Removal is bounded by `limit`; since removed entries leave the index, calling
+/// again continues where the previous call stopped.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, recorded in the audit events
+/// * `info` - Contains the admin's address (message sender)
+/// * `spender` - Address string of the spender to ban
+/// * `limit` - Maximum number of authorizations to remove (defaults to 30, capped at 100)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with the revoked count and
+///   whether every authorization has been removed
+fn execute_global_revoke_spender(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    SPENDER_BLOCKLIST.save(deps.storage, &spender_addr, &true)?;
+
+    let limit = limit.unwrap_or(DEFAULT_GLOBAL_REVOKE_LIMIT).min(MAX_GLOBAL_REVOKE_LIMIT) as usize;
+    // Fetch one extra owner to learn whether more remain after this call
+    let mut owners: Vec<Addr> = SPENDER_INDEX
+        .prefix(&spender_addr)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<_>>()?;
+    let complete = owners.len() <= limit;
+    owners.truncate(limit);
+
+    let mut response = Response::new();
+    for owner in &owners {
+        remove_authorization(deps.storage, owner, &spender_addr)?;
+        record_ledger(deps.storage, &env, "revoke", owner, Some(&spender_addr), None)?;
+        response = response.add_event(authorization_audit_event(&env, owner, &spender_addr, true, false));
+    }
+
+    Ok(response
+        .add_attribute("action", "global_revoke_spender")
+        .add_attribute("spender", spender_addr)
+        .add_attribute("revoked", owners.len().to_string())
+        .add_attribute("complete", complete.to_string()))
+}
+
+/// Records a snapshot of the ledger total against the contract's bank balance
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage and bank querier access
+/// * `env` - Environment information, providing the block height and contract address
+/// * `info` - Contains the admin's address (message sender)
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with the sample values or error
+fn execute_record_drift(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let denom = DENOM.load(deps.storage)?;
+    let escrow_balance = deps.querier.query_balance(&env.contract.address, &denom)?.amount;
+    let ledger_total = ledger_total(deps.storage, &denom)?;
+
+    let id = DRIFT_COUNT.may_load(deps.storage)?.unwrap_or(0) + 1;
+    DRIFT_COUNT.save(deps.storage, &id)?;
+    let sample = DriftSample { id, height: env.block.height, ledger_total, escrow_balance };
+    DRIFT_LOG.save(deps.storage, id, &sample)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "record_drift")
+        .add_attribute("id", id.to_string())
+        .add_attribute("ledger_total", ledger_total.to_string())
+        .add_attribute("escrow_balance", escrow_balance.to_string()))
+}
+
+/// Sends the contract's entire bank balance of every accepted denom to a recipient
+///
+/// This is an emergency escape hatch for stranded funds. It bypasses the internal
+/// ledger entirely: balances are left untouched, so owners may still show credits
+/// the contract can no longer honour.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage, API, and bank querier access
+/// * `env` - Environment information, providing the contract address
+/// * `info` - Contains the admin's address (message sender)
+/// * `recipient` - Address that receives the swept funds
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Response carrying the bank transfer or error
+fn execute_admin_sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let mut swept = vec![];
+    for denom in ACCEPTED_DENOMS.load(deps.storage)? {
+        let balance = deps.querier.query_balance(&env.contract.address, denom)?;
+        if !balance.amount.is_zero() {
+            swept.push(balance);
+        }
+    }
+    if swept.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err("Nothing to sweep")));
+    }
+
+    let amount = swept.iter().map(|coin| coin.to_string()).collect::<Vec<_>>().join(",");
+    Ok(Response::new()
+        .add_message(BankMsg::Send { to_address: recipient_addr.to_string(), amount: swept })
+        .add_attribute("action", "admin_sweep")
+        .add_attribute("recipient", recipient_addr)
+        .add_attribute("amount", amount))
+}
+
+/// Appoints or removes a manager for the sender's authorizations
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `info` - Contains the owner's address (message sender)
+/// * `manager` - Address string of the manager
+/// * `appoint` - Whether to appoint or remove the manager
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_manager(
+    deps: DepsMut,
+    info: MessageInfo,
+    manager: String,
+    appoint: bool,
+) -> Result<Response, ContractError> {
+    let manager_addr = deps.api.addr_validate(&manager)?;
+    if appoint {
+        if manager_addr == info.sender {
+            return Err(ContractError::Std(StdError::generic_err("Cannot appoint self as manager")));
+        }
+        MANAGERS.save(deps.storage, (&info.sender, &manager_addr), &())?;
+    } else {
+        MANAGERS.remove(deps.storage, (&info.sender, &manager_addr));
+    }
+    Ok(Response::new()
+        .add_attribute("action", if appoint { "add_manager" } else { "remove_manager" })
+        .add_attribute("owner", info.sender)
+        .add_attribute("manager", manager_addr))
+}
+
+/// Designates the recovery address for the sender's balance
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `info` - Contains the owner's address (message sender)
+/// * `address` - Address string of the recovery account
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_recovery(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let recovery_addr = deps.api.addr_validate(&address)?;
+    if recovery_addr == info.sender {
+        return Err(ContractError::Std(StdError::generic_err("Cannot set self as recovery address")));
+    }
+    RECOVERY.save(deps.storage, &info.sender, &recovery_addr)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_recovery")
+        .add_attribute("owner", info.sender)
+        .add_attribute("recovery", recovery_addr))
+}
+
+/// Moves an inactive owner's balances to their recovery address
+///
+/// The owner counts as inactive once the configured number of blocks has passed
+/// since their last execute message. Balances in every accepted denom are moved.
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access and address validation
+/// * `env` - Environment information, used to measure the owner's inactivity
+/// * `info` - Contains the recovery address (message sender)
+/// * `owner` - Address string of the inactive owner
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_recover(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    if RECOVERY.may_load(deps.storage, &owner_addr)? != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+    assert_not_frozen(deps.as_ref(), &owner_addr)?;
+    let last_activity = LAST_ACTIVITY.may_load(deps.storage, &owner_addr)?.unwrap_or(0);
+    let available_at_height = last_activity + RECOVERY_DELAY_BLOCKS.load(deps.storage)?;
+    if env.block.height < available_at_height {
+        return Err(ContractError::RecoveryNotReady { available_at_height });
+    }
+
+    let mut recovered = vec![];
+    for denom in ACCEPTED_DENOMS.load(deps.storage)? {
+        let amount = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+        if !amount.is_zero() {
+            debit(deps.storage, &owner_addr, &denom, amount)?;
+            credit(deps.storage, &info.sender, &denom, amount)?;
+            record_ledger(deps.storage, &env, "recover", &owner_addr, Some(&info.sender), Some((&denom, amount)))?;
+            recovered.push(format!("{}{}", amount, denom));
+        }
+    }
+    Ok(Response::new()
+        .add_attribute("action", "recover")
+        .add_attribute("owner", owner_addr)
+        .add_attribute("recovery", info.sender)
+        .add_attribute("amount", recovered.join(",")))
+}
+
+/// Applies the provided parameter changes and clears the named ones
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `info` - Contains the admin's address (message sender)
+/// * `params` - Parameters to change
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with one attribute per changed parameter, or error
+fn execute_update_params(
+    deps: DepsMut,
+    info: MessageInfo,
+    params: ParamUpdate,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    let clear = params.clear.unwrap_or_default();
+    let set = [
+        (ClearableParam::Policy, params.policy.is_some()),
+        (ClearableParam::PriceOracle, params.price_oracle.is_some()),
+        (ClearableParam::GlobalSpendCap, params.global_spend_cap.is_some()),
+        (ClearableParam::WithdrawTimelock, params.withdraw_timelock.is_some()),
+        (ClearableParam::MaxReceivedPerAccount, params.max_received_per_account.is_some()),
+    ];
+    if let Some((param, _)) = set.iter().find(|(param, is_set)| *is_set && clear.contains(param)) {
+        return Err(ContractError::ConflictingConfig {
+            detail: format!("{param:?} cannot be both set and cleared"),
+        });
+    }
+    // Auto-pausing on oracle outages is meaningless without an oracle
+    let oracle_configured = !clear.contains(&ClearableParam::PriceOracle)
+        && (params.price_oracle.is_some() || PRICE_ORACLE.may_load(deps.storage)?.is_some());
+    let oracle_auto_pause = match params.oracle_auto_pause {
+        Some(oracle_auto_pause) => oracle_auto_pause,
+        None => ORACLE_AUTO_PAUSE.may_load(deps.storage)?.unwrap_or(false),
+    };
+    if oracle_auto_pause && !oracle_configured {
+        return Err(ContractError::ConflictingConfig {
+            detail: "oracle_auto_pause requires a price_oracle".to_string(),
+        });
+    }
+    if let Some(global_spend_cap) = &params.global_spend_cap {
+        validate_global_spend_cap(global_spend_cap)?;
+    }
+    if let Some(withdraw_timelock) = &params.withdraw_timelock {
+        validate_withdraw_timelock(withdraw_timelock)?;
+    }
+
+    let mut response = Response::new().add_attribute("action", "update_params");
+    if let Some(keeper_reward) = params.keeper_reward {
+        KEEPER_REWARD.save(deps.storage, &keeper_reward)?;
+        response = response.add_attribute("keeper_reward", keeper_reward.to_string());
+    }
+    if let Some(admin_can_deposit) = params.admin_can_deposit {
+        ADMIN_CAN_DEPOSIT.save(deps.storage, &admin_can_deposit)?;
+        response = response.add_attribute("admin_can_deposit", admin_can_deposit.to_string());
+    }
+    if let Some(recovery_delay_blocks) = params.recovery_delay_blocks {
+        RECOVERY_DELAY_BLOCKS.save(deps.storage, &recovery_delay_blocks)?;
+        response = response.add_attribute("recovery_delay_blocks", recovery_delay_blocks.to_string());
+    }
+    if let Some(min_deposit) = params.min_deposit {
+        MIN_DEPOSIT.save(deps.storage, &min_deposit)?;
+        response = response.add_attribute("min_deposit", min_deposit.to_string());
+    }
+    if let Some(fee_bps) = params.fee_bps {
+        if fee_bps > MAX_FEE_BPS {
+            return Err(ContractError::InvalidFeeBps { fee_bps });
+        }
+        FEE_BPS.save(deps.storage, &fee_bps)?;
+        response = response.add_attribute("fee_bps", fee_bps.to_string());
+    }
+    if let Some(price_oracle) = params.price_oracle {
+        let price_oracle = deps.api.addr_validate(&price_oracle)?;
+        PRICE_ORACLE.save(deps.storage, &price_oracle)?;
+        response = response.add_attribute("price_oracle", price_oracle);
+    }
+    if clear.contains(&ClearableParam::PriceOracle) {
+        PRICE_ORACLE.remove(deps.storage);
+        response = response.add_attribute("price_oracle", "none");
+    }
+    if let Some(oracle_auto_pause) = params.oracle_auto_pause {
+        ORACLE_AUTO_PAUSE.save(deps.storage, &oracle_auto_pause)?;
+        response = response.add_attribute("oracle_auto_pause", oracle_auto_pause.to_string());
+    }
+    if let Some(global_spend_cap) = params.global_spend_cap {
+        GLOBAL_SPEND_CAP.save(deps.storage, &global_spend_cap)?;
+        response = response
+            .add_attribute("global_spend_cap", global_spend_cap.cap.to_string())
+            .add_attribute("global_epoch_seconds", global_spend_cap.epoch_seconds.to_string());
+    }
+    if clear.contains(&ClearableParam::GlobalSpendCap) {
+        GLOBAL_SPEND_CAP.remove(deps.storage);
+        GLOBAL_SPEND_EPOCH.remove(deps.storage);
+        response = response.add_attribute("global_spend_cap", "none");
+    }
+    if let Some(withdraw_timelock) = params.withdraw_timelock {
+        WITHDRAW_TIMELOCK.save(deps.storage, &withdraw_timelock)?;
+        response = response
+            .add_attribute("withdraw_threshold", withdraw_timelock.threshold.to_string())
+            .add_attribute("withdraw_delay_seconds", withdraw_timelock.delay_seconds.to_string());
+    }
+    if clear.contains(&ClearableParam::WithdrawTimelock) {
+        WITHDRAW_TIMELOCK.remove(deps.storage);
+        response = response.add_attribute("withdraw_timelock", "none");
+    }
+    if let Some(max_received_per_account) = params.max_received_per_account {
+        MAX_RECEIVED_PER_ACCOUNT.save(deps.storage, &max_received_per_account)?;
+        response = response.add_attributes([amount_attr("max_received_per_account", max_received_per_account)]);
+    }
+    if clear.contains(&ClearableParam::MaxReceivedPerAccount) {
+        MAX_RECEIVED_PER_ACCOUNT.remove(deps.storage);
+        response = response.add_attribute("max_received_per_account", "none");
+    }
+    if let Some(policy) = params.policy {
+        let policy = deps.api.addr_validate(&policy)?;
+        POLICY.save(deps.storage, &policy)?;
+        response = response.add_attribute("policy", policy);
+    }
+    if clear.contains(&ClearableParam::Policy) {
+        POLICY.remove(deps.storage);
+        response = response.add_attribute("policy", "none");
+    }
+    if let Some(spend_fee_bps) = params.spend_fee_bps {
+        if spend_fee_bps > MAX_FEE_BPS {
+            return Err(ContractError::InvalidFeeBps { fee_bps: spend_fee_bps });
+        }
+        SPEND_FEE_BPS.save(deps.storage, &spend_fee_bps)?;
+        response = response.add_attribute("spend_fee_bps", spend_fee_bps.to_string());
+    }
+    Ok(response)
+}
+
+/// Halts or resumes deposits
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `info` - Contains the admin's address (message sender)
+/// * `enabled` - Whether deposits are accepted from now on
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_deposits_enabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    DEPOSITS_ENABLED.save(deps.storage, &enabled)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_deposits_enabled")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// Pauses or resumes USD spends
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `info` - Contains the admin's address (message sender)
+/// * `paused` - Whether SpendUsd is rejected from now on
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_usd_spends_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    USD_SPENDS_PAUSED.save(deps.storage, &paused)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_usd_spends_paused")
+        .add_attribute("paused", paused.to_string()))
+}
+
+/// Replaces the delegated spending blackout schedule
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `info` - Contains the admin's address (message sender)
+/// * `windows` - Time windows during which delegated spends are rejected
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_spending_blackouts(
+    deps: DepsMut,
+    info: MessageInfo,
+    windows: Vec<BlackoutWindow>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    if windows.iter().any(|window| window.start >= window.end) {
+        return Err(ContractError::Std(StdError::generic_err("Blackout window must end after it starts")));
+    }
+    SPENDING_BLACKOUTS.save(deps.storage, &windows)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_spending_blackouts")
+        .add_attribute("windows", windows.len().to_string()))
+}
+
+/// Announces a new native denom that balances can be converted into
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `info` - Contains the admin's address (message sender)
+/// * `denom` - Denom balances will be converted into
+/// * `ratio` - Units of `denom` credited per unit of the primary denom
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response with event attributes or error
+fn execute_set_pending_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    ratio: Decimal,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    if denom.is_empty() || denom == DENOM.load(deps.storage)? {
+        return Err(ContractError::Std(StdError::generic_err("New denom must differ from the primary denom")));
+    }
+    if ratio.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Conversion ratio must be positive")));
+    }
+    ACCEPTED_DENOMS.update(deps.storage, |mut accepted| -> StdResult<_> {
+        if !accepted.contains(&denom) {
+            accepted.push(denom.clone());
+        }
+        Ok(accepted)
+    })?;
+    PENDING_DENOM.save(deps.storage, &DenomConversion { denom: denom.clone(), ratio })?;
+    Ok(Response::new()
+        .add_attribute("action", "set_pending_denom")
+        .add_attribute("denom", denom)
+        .add_attribute("ratio", ratio.to_string()))
+}
+
+/// Rejects a global spending cap with a zero-length epoch
+pub(crate) fn validate_global_spend_cap(global_spend_cap: &GlobalSpendCap) -> StdResult<()> {
+    if global_spend_cap.epoch_seconds == 0 {
+        return Err(StdError::generic_err("Global spend cap epoch_seconds must be nonzero"));
+    }
+    Ok(())
+}
+
+/// Rejects a withdrawal timelock whose delay exceeds MAX_WITHDRAW_DELAY_SECONDS
+pub(crate) fn validate_withdraw_timelock(withdraw_timelock: &WithdrawTimelock) -> StdResult<()> {
+    if withdraw_timelock.delay_seconds > MAX_WITHDRAW_DELAY_SECONDS {
+        return Err(StdError::generic_err(format!(
+            "Withdraw timelock delay_seconds must be at most {MAX_WITHDRAW_DELAY_SECONDS}"
+        )));
+    }
+    Ok(())
+}
+
+/// Verifies that `sender` is the contract admin
+fn assert_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    if ADMIN.load(deps.storage)? != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// Builds an attribute carrying a token amount
+///
+/// Every amount-bearing attribute goes through here so amounts render as the
+/// full-precision decimal display of `Uint128`, with no truncation to 64 bits.
+pub(crate) fn amount_attr(key: &str, amount: Uint128) -> Attribute {
+    attr(key, amount.to_string())
+}
+
+/// Builds the `deposit` event emitted for every credited deposit
+///
+/// Emitted as `wasm-deposit` so off-chain listeners can subscribe to deposits alone.
+fn deposit_event(from: &Addr, account: &Addr, denom: &str, amount: Uint128) -> Event {
+    Event::new("deposit")
+        .add_attribute("from", from)
+        .add_attribute("account", account)
+        .add_attribute("denom", denom)
+        .add_attributes([amount_attr("amount", amount)])
+}
+
+/// Builds the `delegated_spend` event emitted for every spend from an owner's balance
+///
+/// Emitted as `wasm-delegated_spend` so off-chain listeners can subscribe to spends
+/// alone. `remaining_balance` is the owner's balance in `denom` after the spend.
+fn spend_event(
+    owner: &Addr,
+    spender: &Addr,
+    recipient: &Addr,
+    denom: &str,
+    amount: Uint128,
+    remaining_balance: Uint128,
+) -> Event {
+    Event::new("delegated_spend")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("denom", denom)
+        .add_attributes([amount_attr("amount", amount), amount_attr("remaining_balance", remaining_balance)])
+}
+
+/// Hashes a spend's details into an opaque reference for minimal-event responses
+///
+/// Parties who know the spend can recompute the base64 sha256 of
+/// `owner:spender:denom:amount:height` to match it, while logs reveal nothing.
+fn spend_reference(env: &Env, owner: &Addr, spender: &Addr, denom: &str, amount: Uint128) -> String {
+    let preimage = format!("{}:{}:{}:{}:{}", owner, spender, denom, amount, env.block.height);
+    Binary::from(&Sha256::digest(preimage.as_bytes())[..]).to_base64()
+}
+
+/// Computes the spend fee owed on a spend of `amount`
+///
+/// Only delegated spends pay the fee; an owner spending their own balance (no
+/// authorization) pays nothing.
+pub(crate) fn spend_fee(storage: &dyn Storage, authorization: Option<&Authorization>, amount: Uint128) -> StdResult<Uint128> {
+    if authorization.is_none() {
+        return Ok(Uint128::zero());
+    }
+    Ok(bps_of(amount, SPEND_FEE_BPS.load(storage)?))
+}
+
+/// Adds a spend fee to the spent amount, failing on overflow
+pub(crate) fn checked_total(amount: Uint128, fee: Uint128) -> StdResult<Uint128> {
+    Ok(amount.checked_add(fee)?)
+}
+
+/// Credits a spend fee already debited from `owner` to the admin and records it
+/// in the owner's fee history
+fn collect_spend_fee(storage: &mut dyn Storage, owner: &Addr, denom: &str, fee: Uint128) -> StdResult<()> {
+    if fee.is_zero() {
+        return Ok(());
+    }
+    let admin = ADMIN.load(storage)?;
+    credit(storage, &admin, denom, fee)?;
+    FEES_PAID_BY.update(storage, owner, |paid| -> StdResult<_> {
+        Ok(paid.unwrap_or_default() + fee)
+    })?;
+    Ok(())
+}
+
+/// Appends an operation to the contract-wide activity ledger
+///
+/// `movement` is the denom and amount moved, for operations that change a balance.
+pub(crate) fn record_ledger(
+    storage: &mut dyn Storage,
+    env: &Env,
+    action: &str,
+    account: &Addr,
+    counterparty: Option<&Addr>,
+    movement: Option<(&str, Uint128)>,
+) -> StdResult<()> {
+    let id = LEDGER_COUNT.may_load(storage)?.unwrap_or(0) + 1;
+    LEDGER_COUNT.save(storage, &id)?;
+    let entry = LedgerEntry {
+        id,
+        height: env.block.height,
+        action: action.to_string(),
+        account: account.clone(),
+        counterparty: counterparty.cloned(),
+        denom: movement.map(|(denom, _)| denom.to_string()),
+        amount: movement.map(|(_, amount)| amount),
+    };
+    LEDGER.save(storage, id, &entry)
+}
+
+/// Stores a receipt for a deposit under the next deposit id and returns the id
+fn record_deposit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    depositor: &Addr,
+    beneficiary: &Addr,
+    denom: &str,
+    amount: Uint128,
+    fee: Uint128,
+) -> StdResult<u64> {
+    let id = DEPOSIT_COUNTER.may_load(storage)?.unwrap_or(0) + 1;
+    DEPOSIT_COUNTER.save(storage, &id)?;
+    let record = DepositRecord {
+        id,
+        height: env.block.height,
+        time: env.block.time,
+        depositor: depositor.clone(),
+        beneficiary: beneficiary.clone(),
+        denom: denom.to_string(),
+        amount,
+        fee,
+    };
+    DEPOSITS.save(storage, id, &record)?;
+    Ok(id)
+}
+
+/// Records a completed spend against the spender's usage trackers
+///
+/// Adds the amount to the spender's cumulative spent-by ledger, deducts it from a
+/// limited allowance and, for rate-limited authorizations, adds it to the current
+/// window (starting a new window if the previous one has elapsed). The amount also
+/// counts towards the global spending cap's epoch, when one is configured, and
+/// towards TOTAL_SPENT when spent in the primary denom.
+/// Self-spends are not delegated spending and are not recorded.
+fn record_spent(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+    spender: &Addr,
+    denom: &str,
+    authorization: Option<&Authorization>,
+    amount: Uint128,
+) -> StdResult<()> {
+    let Some(authorization) = authorization else {
+        return Ok(());
+    };
+    if denom == DENOM.load(storage)? {
+        add_to_total(storage, TOTAL_SPENT, amount)?;
+    }
+    SPENT_BY.update(storage, (spender, owner), |spent| -> StdResult<_> {
+        Ok(spent.unwrap_or_default() + amount)
+    })?;
+    if let Some(allowance) = authorization.allowance {
+        let updated = Authorization { allowance: Some(allowance.saturating_sub(amount)), ..authorization.clone() };
+        AUTHORIZED_SPENDERS.save(storage, (owner, spender), &updated)?;
+    }
+    if let Some(window_seconds) = authorization.window_seconds {
+        let now = env.block.time.seconds();
+        let (start, spent) = match SPEND_WINDOWS.may_load(storage, (owner, spender))? {
+            Some((start, spent)) if now < start.saturating_add(window_seconds) => (start, spent),
+            _ => (now, Uint128::zero()),
+        };
+        SPEND_WINDOWS.save(storage, (owner, spender), &(start, spent + amount))?;
+    }
+    if authorization.cooldown_seconds.is_some() {
+        LAST_SPEND.save(storage, (owner, spender), &env.block.time.seconds())?;
+    }
+    if let Some(global_cap) = GLOBAL_SPEND_CAP.may_load(storage)? {
+        let now = env.block.time.seconds();
+        let (start, spent) = match GLOBAL_SPEND_EPOCH.may_load(storage)? {
+            Some((start, spent)) if now < start.saturating_add(global_cap.epoch_seconds) => (start, spent),
+            _ => (now, Uint128::zero()),
+        };
+        GLOBAL_SPEND_EPOCH.save(storage, &(start, spent + amount))?;
+    }
+    Ok(())
+}
+
+/// Writes an authorization and keeps the spender reverse index, the expiry queue,
+/// the owner's spender count and the total authorization count in step
+///
+/// A new pair is rejected with `TooManySpenders` once the owner already has
+/// MAX_SPENDERS_PER_OWNER spenders; updating an existing pair is always allowed.
+fn save_authorization(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    spender: &Addr,
+    authorization: &Authorization,
+) -> Result<(), ContractError> {
+    if !AUTHORIZED_SPENDERS.has(storage, (owner, spender)) {
+        let count = SPENDER_COUNT.may_load(storage, owner)?.unwrap_or(0);
+        let max = MAX_SPENDERS_PER_OWNER.load(storage)?;
+        if count >= max {
+            return Err(ContractError::TooManySpenders { max });
+        }
+        SPENDER_COUNT.save(storage, owner, &(count + 1))?;
+        AUTH_COUNT.update(storage, |total| -> StdResult<_> { Ok(total + 1) })?;
+    }
+    AUTHORIZED_SPENDERS.save(storage, (owner, spender), authorization)?;
+    SPENDER_INDEX.save(storage, (spender, owner), &())?;
+    if let Some(expires_at) = authorization.expires_at_height {
+        EXPIRY_QUEUE.save(storage, (expires_at, owner, spender), &())?;
+    }
+    Ok(())
+}
+
+/// Removes an authorization together with its reverse index entry and per-pair usage
+/// state, and decrements the owner's spender count and the total authorization count
+fn remove_authorization(storage: &mut dyn Storage, owner: &Addr, spender: &Addr) -> StdResult<()> {
+    if AUTHORIZED_SPENDERS.has(storage, (owner, spender)) {
+        match SPENDER_COUNT.may_load(storage, owner)?.unwrap_or(0) {
+            0 | 1 => SPENDER_COUNT.remove(storage, owner),
+            count => SPENDER_COUNT.save(storage, owner, &(count - 1))?,
+        }
+        AUTH_COUNT.update(storage, |total| -> StdResult<_> { Ok(total.saturating_sub(1)) })?;
+    }
+    AUTHORIZED_SPENDERS.remove(storage, (owner, spender));
+    SPENDER_INDEX.remove(storage, (spender, owner));
+    SPEND_WINDOWS.remove(storage, (owner, spender));
+    LAST_SPEND.remove(storage, (owner, spender));
+    SPENDER_DENOMS.remove(storage, (owner, spender));
+    Ok(())
+}
+
+/// Returns true if the admin has blocked `spender`
+fn is_blocked(deps: Deps, spender: &Addr) -> StdResult<bool> {
+    Ok(SPENDER_BLOCKLIST.may_load(deps.storage, spender)?.unwrap_or(false))
+}
+
+/// Returns true if the admin has frozen `account`
+fn is_frozen(deps: Deps, account: &Addr) -> StdResult<bool> {
+    Ok(FROZEN.may_load(deps.storage, account)?.unwrap_or(false))
+}
+
+/// Rejects balance movements touching an account the admin has frozen
+fn assert_not_frozen(deps: Deps, account: &Addr) -> Result<(), ContractError> {
+    if is_frozen(deps, account)? {
+        return Err(ContractError::AccountFrozen { account: account.to_string() });
+    }
+    Ok(())
+}
+
+/// Verifies that `spender` may move `amount` out of `owner`'s balance in one transaction
+///
+/// `amount` must be within any precision configured for `denom`, even for a self-spend.
+/// Neither the owner nor the spender may be frozen. Beyond that, either the spender
+/// is the owner (self-spending) or, outside any spending blackout window, holds an
+/// authorization from the owner that has started and not yet expired, whose
+/// per-transaction cap and remaining allowance cover `amount`, and whose current
+/// rate-limit window has room for `amount`. Under a cooldown, enough time must have
+/// passed since the last spend. When the authorization carries a secret hash,
+/// `secret` must hash to it, and when the owner restricted the spender's denoms,
+/// `denom` must be among them. Finally, the global spending cap must have room for
+/// `amount` and a configured policy contract must allow the spend.
+///
+/// Returns the spender's authorization, or `None` for a self-spend.
+pub(crate) fn assert_can_spend(
+    deps: Deps,
+    env: &Env,
+    owner: &Addr,
+    spender: &Addr,
+    denom: &str,
+    amount: Uint128,
+    secret: Option<&Binary>,
+) -> Result<Option<Authorization>, ContractError> {
+    assert_within_precision(deps.storage, denom, amount)?;
+    assert_not_frozen(deps, owner)?;
+    assert_not_frozen(deps, spender)?;
+    if spender == owner {
+        return Ok(None);
+    }
+    let blackouts = SPENDING_BLACKOUTS.may_load(deps.storage)?.unwrap_or_default();
+    if blackouts.iter().any(|window| window.contains(&env.block)) {
+        return Err(ContractError::SpendingBlackout {});
+    }
+    if is_blocked(deps, spender)? {
+        return Err(ContractError::SpenderBlocked {});
+    }
+    let authorization = AUTHORIZED_SPENDERS
+        .may_load(deps.storage, (owner, spender))?
+        .ok_or(ContractError::Unauthorized {})?;
+    if authorization.is_expired(&env.block) {
+        return Err(ContractError::AuthorizationExpired {});
+    }
+    if authorization.is_pending(&env.block) {
+        return Err(ContractError::NotYetActive {});
+    }
+    if let Some(cooldown_seconds) = authorization.cooldown_seconds {
+        if let Some(last) = LAST_SPEND.may_load(deps.storage, (owner, spender))? {
+            let retry_after = Timestamp::from_seconds(last.saturating_add(cooldown_seconds));
+            if env.block.time < retry_after {
+                return Err(ContractError::Cooldown { retry_after });
+            }
+        }
+    }
+    if let Some(hash) = &authorization.secret_hash {
+        let matches = secret.is_some_and(|secret| Sha256::digest(secret.as_slice())[..] == hash[..]);
+        if !matches {
+            return Err(ContractError::BadSecret {});
+        }
+    }
+    if let Some(denoms) = SPENDER_DENOMS.may_load(deps.storage, (owner, spender))? {
+        if !denoms.iter().any(|allowed| allowed == denom) {
+            return Err(ContractError::DenomNotAllowed { denom: denom.to_string() });
+        }
+    }
+    if let Some(cap) = authorization.max_per_tx {
+        if amount > cap {
+            return Err(ContractError::PerTxCapExceeded { cap, requested: amount });
+        }
+    }
+    if let Some(remaining) = authorization.allowance {
+        if amount > remaining {
+            return Err(ContractError::AllowanceExceeded { remaining });
+        }
+    }
+    if let Some(remaining) = window_remaining(deps.storage, &env.block, owner, spender, &authorization)? {
+        if amount > remaining {
+            return Err(ContractError::RateLimitExceeded { remaining });
+        }
+    }
+    if let Some(remaining) = global_cap_remaining(deps.storage, &env.block)? {
+        if amount > remaining {
+            return Err(ContractError::GlobalCapExceeded { remaining });
+        }
+    }
+    assert_policy_allows(deps, owner, spender, denom, amount)?;
+    Ok(Some(authorization))
+}
+
+/// Asks the configured policy contract whether a delegated spend may go ahead
+///
+/// Passes when no policy is configured. A failing policy query fails the spend.
+fn assert_policy_allows(deps: Deps, owner: &Addr, spender: &Addr, denom: &str, amount: Uint128) -> Result<(), ContractError> {
+    let Some(policy) = POLICY.may_load(deps.storage)? else {
+        return Ok(());
+    };
+    let check = PolicyQueryMsg::CheckSpend {
+        owner: owner.to_string(),
+        spender: spender.to_string(),
+        denom: denom.to_string(),
+        amount,
+    };
+    let PolicyResponse { allowed, reason } = deps.querier.query_wasm_smart(policy, &check)?;
+    if !allowed {
+        return Err(ContractError::PolicyDenied { reason: reason.unwrap_or_default() });
+    }
+    Ok(())
 }
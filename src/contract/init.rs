@@ -2,40 +2,184 @@
 ///
 /// This module handles the instantiation of the contract, validating and storing
 /// the initial configuration parameters.
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, Uint128};
+use crate::contract::exec::{validate_global_spend_cap, validate_withdraw_timelock};
+use crate::error::ContractError;
 use crate::msg::init::InstantiateMsg;
-use crate::state::{ADMIN, DENOM};
+use crate::state::{
+    ACCEPTED_DENOMS, ACTIVE_ACCOUNTS, AUTH_COUNT, ADMIN, ADMIN_CAN_DEPOSIT, DECIMALS, DENOM, DENOM_ALIASES, DENOM_PRECISIONS, DEPOSITS_ENABLED, FEE_BPS, GLOBAL_SPEND_CAP, WITHDRAW_TIMELOCK, KEEPER_REWARD, MAX_FEE_BPS, MAX_RECEIVED_PER_ACCOUNT, MAX_SPENDERS_PER_OWNER, MIN_DEPOSIT, MINIMAL_EVENTS, ORACLE_AUTO_PAUSE, PAUSED,
+    POLICY, PRICE_ORACLE, RECOVERY_DELAY_BLOCKS, REWARD_POOL, SPEND_FEE_BPS, STRICT_DEPOSIT, TOTAL_DEPOSITED, TOTAL_SPENT, USD_SPENDS_PAUSED,
+};
+
+/// Owner inactivity, in blocks, required before recovery when none is configured
+const DEFAULT_RECOVERY_DELAY_BLOCKS: u64 = 100_800;
+/// Most display decimals the primary denom may have
+const MAX_DECIMALS: u8 = 18;
+/// Most spenders an owner may authorize when no cap is configured
+const DEFAULT_MAX_SPENDERS_PER_OWNER: u32 = 50;
 
 /// Instantiates a new Credits Delegation contract
 ///
 /// This function is called exactly once when the contract is first deployed.
 /// It sets up the initial contract state by:
-/// 1. Validating the admin address
+/// 1. Rejecting any attached funds, which no balance would account for, and
+///    validating the admin address (which must not be the contract itself)
 /// 2. Saving the admin address to state
-/// 3. Saving the accepted token denomination to state
+/// 3. Saving the primary denomination and its display decimals, any additional
+///    accepted denominations, the primary denom's aliases and any per-denom spend
+///    precision to state
+/// 4. Configuring the keeper reward and an empty reward pool
+/// 5. Recording whether the admin may deposit and how much spends reveal in events
+/// 6. Configuring the owner recovery delay, the per-owner spender cap and the
+///    per-account receive cap
+/// 7. Setting the deposit strictness, the minimum deposit amount and the deposit and spend fees
+/// 8. Recording the price oracle, if any, and how its outages are handled
+/// 9. Recording the global spending cap, the withdrawal timelock and the spend
+///    policy contract, if any
 ///
 /// # Arguments
 /// * `deps` - Mutable dependencies for storage, API, and querier access
-/// * `_env` - Environment information (block height/time, contract address)
-/// * `_info` - Transaction metadata (sender, sent funds)
+/// * `env` - Environment information (block height/time, contract address)
+/// * `info` - Transaction metadata (sender, sent funds)
 /// * `msg` - Instantiation parameters (admin address, token denom)
 ///
 /// # Returns
-/// * `StdResult<Response>` - Success response or error
+/// * `Result<Response, ContractError>` - Success response or error
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    // Funds sent now would sit in the contract without being credited to anyone
+    if !info.funds.is_empty() {
+        return Err(ContractError::FundsNotAccepted {});
+    }
+
     // Validate that the admin address is a proper bech32 address
     let admin = deps.api.addr_validate(msg.admin.as_str())?;
+
+    // The contract cannot act as its own admin, as it could never sign admin actions
+    if admin == env.contract.address {
+        return Err(ContractError::InvalidAdmin {});
+    }
     
     // Save admin address to contract state
     ADMIN.save(deps.storage, &admin)?;
     
-    // Save token denomination to contract state
+    // Save the primary denomination, followed by any additional accepted ones
+    let mut accepted_denoms = vec![msg.denom.clone()];
+    for denom in msg.additional_denoms.unwrap_or_default() {
+        if !accepted_denoms.contains(&denom) {
+            accepted_denoms.push(denom);
+        }
+    }
     DENOM.save(deps.storage, &msg.denom)?;
+    let decimals = msg.decimals;
+    if decimals > MAX_DECIMALS {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Decimals must be at most {}",
+            MAX_DECIMALS
+        ))));
+    }
+    DECIMALS.save(deps.storage, &decimals)?;
+    ACCEPTED_DENOMS.save(deps.storage, &accepted_denoms)?;
+    let mut denom_aliases: Vec<String> = vec![];
+    for alias in msg.denom_aliases.unwrap_or_default() {
+        if accepted_denoms.contains(&alias) {
+            return Err(ContractError::ConflictingConfig {
+                detail: format!("denom alias {} is already an accepted denom", alias),
+            });
+        }
+        if !denom_aliases.contains(&alias) {
+            denom_aliases.push(alias);
+        }
+    }
+    DENOM_ALIASES.save(deps.storage, &denom_aliases)?;
+    let denom_precisions = msg.denom_precisions.unwrap_or_default();
+    for denom_precision in &denom_precisions {
+        if !accepted_denoms.contains(&denom_precision.denom) {
+            return Err(ContractError::ConflictingConfig {
+                detail: format!("precision configured for {}, which is not an accepted denom", denom_precision.denom),
+            });
+        }
+        if denom_precision.denom == msg.denom && denom_precision.decimals != u32::from(decimals) {
+            return Err(ContractError::ConflictingConfig {
+                detail: format!(
+                    "precision for {} uses {} decimals, but the denom has {}",
+                    msg.denom, denom_precision.decimals, decimals
+                ),
+            });
+        }
+        // The spend step, 10^(decimals - precision), must fit in a Uint128
+        let fits = denom_precision
+            .decimals
+            .checked_sub(denom_precision.precision)
+            .and_then(|exponent| 10u128.checked_pow(exponent))
+            .is_some();
+        if !fits {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "Invalid precision for {}",
+                denom_precision.denom
+            ))));
+        }
+    }
+    DENOM_PRECISIONS.save(deps.storage, &denom_precisions)?;
+
+    // Configure keeper rewards; the pool starts empty until funded
+    KEEPER_REWARD.save(deps.storage, &msg.keeper_reward.unwrap_or_default())?;
+    REWARD_POOL.save(deps.storage, &Uint128::zero())?;
+    ACTIVE_ACCOUNTS.save(deps.storage, &0)?;
+    AUTH_COUNT.save(deps.storage, &0)?;
+    TOTAL_DEPOSITED.save(deps.storage, &Uint128::zero())?;
+    TOTAL_SPENT.save(deps.storage, &Uint128::zero())?;
+
+    ADMIN_CAN_DEPOSIT.save(deps.storage, &msg.admin_can_deposit.unwrap_or(true))?;
+    PAUSED.save(deps.storage, &false)?;
+    DEPOSITS_ENABLED.save(deps.storage, &true)?;
+    RECOVERY_DELAY_BLOCKS.save(
+        deps.storage,
+        &msg.recovery_delay_blocks.unwrap_or(DEFAULT_RECOVERY_DELAY_BLOCKS),
+    )?;
+    MAX_SPENDERS_PER_OWNER.save(
+        deps.storage,
+        &msg.max_spenders_per_owner.unwrap_or(DEFAULT_MAX_SPENDERS_PER_OWNER),
+    )?;
+    if let Some(max_received_per_account) = msg.max_received_per_account {
+        MAX_RECEIVED_PER_ACCOUNT.save(deps.storage, &max_received_per_account)?;
+    }
+    STRICT_DEPOSIT.save(deps.storage, &msg.strict_deposit.unwrap_or(true))?;
+    MINIMAL_EVENTS.save(deps.storage, &msg.minimal_events.unwrap_or(false))?;
+    MIN_DEPOSIT.save(deps.storage, &msg.min_deposit.unwrap_or_default())?;
+    for (item, fee_bps) in [(FEE_BPS, msg.fee_bps), (SPEND_FEE_BPS, msg.spend_fee_bps)] {
+        let fee_bps = fee_bps.unwrap_or(0);
+        if fee_bps > MAX_FEE_BPS {
+            return Err(ContractError::InvalidFeeBps { fee_bps });
+        }
+        item.save(deps.storage, &fee_bps)?;
+    }
+    // Auto-pausing on oracle outages is meaningless without an oracle
+    if msg.oracle_auto_pause == Some(true) && msg.price_oracle.is_none() {
+        return Err(ContractError::ConflictingConfig {
+            detail: "oracle_auto_pause requires a price_oracle".to_string(),
+        });
+    }
+    if let Some(price_oracle) = msg.price_oracle {
+        PRICE_ORACLE.save(deps.storage, &deps.api.addr_validate(&price_oracle)?)?;
+    }
+    ORACLE_AUTO_PAUSE.save(deps.storage, &msg.oracle_auto_pause.unwrap_or(false))?;
+    if let Some(global_spend_cap) = msg.global_spend_cap {
+        validate_global_spend_cap(&global_spend_cap)?;
+        GLOBAL_SPEND_CAP.save(deps.storage, &global_spend_cap)?;
+    }
+    if let Some(withdraw_timelock) = msg.withdraw_timelock {
+        validate_withdraw_timelock(&withdraw_timelock)?;
+        WITHDRAW_TIMELOCK.save(deps.storage, &withdraw_timelock)?;
+    }
+    if let Some(policy) = msg.policy {
+        POLICY.save(deps.storage, &deps.api.addr_validate(&policy)?)?;
+    }
+    USD_SPENDS_PAUSED.save(deps.storage, &false)?;
     
     // Return success response with method attribute
     Ok(Response::new().add_attribute("method", "instantiate"))
@@ -0,0 +1,80 @@
+/// Migration logic for the Credits Delegation contract
+///
+/// This module handles state upgrades run when the contract's code is migrated,
+/// such as backfilling indexes introduced after data already exists.
+use cosmwasm_std::{Addr, DepsMut, Env, Order, Response, StdResult};
+use cw_storage_plus::Bound;
+use crate::error::ContractError;
+use crate::msg::migrate::MigrateMsg;
+use crate::state::{Authorization, AUTHORIZED_SPENDERS, EXPIRY_QUEUE, REINDEX_CURSOR, SPENDER_INDEX};
+
+/// Number of authorizations scanned by RebuildReverseIndex when no limit is given
+const DEFAULT_REINDEX_LIMIT: u32 = 30;
+/// Upper bound on the number of authorizations scanned in one RebuildReverseIndex call
+const MAX_REINDEX_LIMIT: u32 = 100;
+
+/// Main entry point for all migrate messages
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `_env` - Environment information (block height/time, contract address)
+/// * `msg` - The migrate message with the upgrade to perform
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response or error
+pub fn migrate(
+    deps: DepsMut,
+    _env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        MigrateMsg::RebuildReverseIndex { limit } => rebuild_reverse_index(deps, limit),
+    }
+}
+
+/// Writes missing reverse index and expiry queue entries for the next slice of
+/// authorizations
+///
+/// The last authorization scanned is stored in `REINDEX_CURSOR`, so consecutive
+/// migrations walk the whole map; the cursor is cleared once the end is reached,
+/// letting a later rebuild start over.
+fn rebuild_reverse_index(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_REINDEX_LIMIT).min(MAX_REINDEX_LIMIT) as usize;
+    let cursor = REINDEX_CURSOR.may_load(deps.storage)?;
+    // Fetch one extra key to learn whether more remain after this call
+    let mut entries: Vec<((Addr, Addr), Authorization)> = AUTHORIZED_SPENDERS
+        .range(
+            deps.storage,
+            cursor.as_ref().map(|(owner, spender)| Bound::exclusive((owner, spender))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit + 1)
+        .collect::<StdResult<_>>()?;
+    let complete = entries.len() <= limit;
+    entries.truncate(limit);
+
+    let mut written = 0u32;
+    for ((owner, spender), authorization) in &entries {
+        if !SPENDER_INDEX.has(deps.storage, (spender, owner)) {
+            SPENDER_INDEX.save(deps.storage, (spender, owner), &())?;
+            written += 1;
+        }
+        if let Some(expires_at) = authorization.expires_at_height {
+            if !EXPIRY_QUEUE.has(deps.storage, (expires_at, owner, spender)) {
+                EXPIRY_QUEUE.save(deps.storage, (expires_at, owner, spender), &())?;
+                written += 1;
+            }
+        }
+    }
+    match entries.last() {
+        Some((last, _)) if !complete => REINDEX_CURSOR.save(deps.storage, last)?,
+        _ => REINDEX_CURSOR.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "rebuild_reverse_index")
+        .add_attribute("scanned", entries.len().to_string())
+        .add_attribute("written", written.to_string())
+        .add_attribute("complete", complete.to_string()))
+}
@@ -1,18 +1,27 @@
-/// Main contract entry points and implementation
-/// 
-/// This module organizes the contract's core logic into separate files by functionality:
-/// - `init.rs`: Contract instantiation logic
-/// - `exec.rs`: Execution message handling
-/// - `query.rs`: Query message handling
-///
-/// The separation allows for better code organization while maintaining a clean public API
-/// through re-exports of the main entry point functions.
+//! Main contract entry points and implementation
+//! 
+//! This module organizes the contract's core logic into separate files by functionality:
+//! - `init.rs`: Contract instantiation logic
+//! - `exec.rs`: Execution message handling
+//! - `query.rs`: Query message handling
+//! - `sudo.rs`: Chain-issued sudo message handling
+//! - `reply.rs`: Submessage reply handling
+//! - `migrate.rs`: Code migration handling
+//!
+//! The separation allows for better code organization while maintaining a clean public API
+//! through re-exports of the main entry point functions.
 
 pub mod init;
 pub mod exec;
 pub mod query;
+pub mod sudo;
+pub mod reply;
+pub mod migrate;
 
 // Re-export public interfaces for easier imports by consuming code
 pub use init::*;
 pub use exec::*;
 pub use query::*;
+pub use sudo::*;
+pub use reply::*;
+pub use migrate::*;
@@ -3,9 +3,34 @@
 /// This module handles all read-only operations for the contract,
 /// allowing clients to retrieve information about balances and authorizations
 /// without modifying contract state.
-use cosmwasm_std::{Deps, Env, StdResult, Binary, to_json_binary};
-use crate::msg::query::QueryMsg;
-use crate::state::{BALANCES, AUTHORIZED_SPENDERS};
+use cosmwasm_std::{Addr, BlockInfo, Decimal, Deps, Env, Order, StdError, StdResult, Storage, Timestamp, Uint128, Binary, to_json_binary};
+use cw_storage_plus::Bound;
+use crate::msg::oracle::{OracleQueryMsg, PriceResponse};
+use crate::contract::exec::{assert_can_spend, assert_within_receive_cap, check_batch_spend, checked_total, spend_fee};
+use crate::error::ContractError;
+use crate::msg::query::{ActiveWindowResponse, AgentMetadataResponse, AllowanceInfo, AuthStatus, AuthorizationDetails, AuthorizationResponse, BalanceEntry, BalanceResponse, CanSpendResponse, OwnerAvailableResponse, BalanceDistributionResponse, ConfigResponse, DepositResponse, DriftHistoryResponse,
+    EffectiveAllowanceResponse, IndexIntegrityResponse, IndexMismatch, InvariantResponse, IsAuthorizedResponse, LastActivityResponse, LedgerResponse, ManagersResponse, NonceResponse, OwnersForSpenderResponse, QueryMsg, SimulateBatchSpendResponse, StatsResponse, WithdrawReadyAtResponse};
+use crate::state::{
+    Authorization, GlobalSpendCap, ACCEPTED_DENOMS, ACTIVE_ACCOUNTS, AUTH_COUNT, ADMIN, ADMIN_CAN_DEPOSIT, BALANCES, CONVERTED, AUTHORIZED_SPENDERS, SPENDER_INDEX, DENOM, DECIMALS, DENOM_ALIASES, DENOM_PRECISIONS, DEPOSITS, DEPOSITS_ENABLED, FEES_PAID_BY, FEE_BPS, FROZEN, DRIFT_LOG, GLOBAL_SPEND_CAP, GLOBAL_SPEND_EPOCH, SPENDING_BLACKOUTS, MANAGERS, PENDING_WITHDRAWALS, WITHDRAW_TIMELOCK, TOTAL_DEPOSITED, TOTAL_SPENT, KEEPER_REWARD, LAST_ACTIVITY, MAX_RECEIVED_PER_ACCOUNT, MAX_SPENDERS_PER_OWNER, LEDGER, MIN_DEPOSIT, MINIMAL_EVENTS, NONCES, PAUSED, PENDING_DENOM, POLICY, PRICE_ORACLE, RECOVERY_DELAY_BLOCKS, REWARD_POOL,
+    SPEND_FEE_BPS, SPENT_BY, SPEND_WINDOWS, SPENDER_DENOMS, STRICT_DEPOSIT, ORACLE_AUTO_PAUSE, USD_SPENDS_PAUSED,
+};
+
+/// Number of accounts scanned by BalanceDistribution when no limit is given
+const DEFAULT_DISTRIBUTION_LIMIT: u32 = 100;
+/// Upper bound on the number of accounts scanned by one BalanceDistribution call
+const MAX_DISTRIBUTION_LIMIT: u32 = 500;
+/// Upper bound on the number of bucket boundaries accepted by BalanceDistribution
+const MAX_DISTRIBUTION_BUCKETS: usize = 20;
+/// Number of entries returned by paginated list queries when no limit is given
+const DEFAULT_LIMIT: u32 = 10;
+/// Upper bound on the number of entries returned by paginated list queries
+const MAX_LIMIT: u32 = 30;
+/// Upper bound on the number of addresses read by one BalancesBatch call
+const MAX_BATCH_OWNERS: usize = 50;
+/// Upper bound on the number of pairs checked by one BatchAuthStatus call
+const MAX_BATCH_PAIRS: usize = 30;
+/// Oldest oracle price, in seconds, that SpendUsd and SpendableUsd accept
+const MAX_PRICE_AGE_SECONDS: u64 = 300;
 
 /// Main entry point for all query messages
 ///
@@ -14,66 +39,1034 @@ use crate::state::{BALANCES, AUTHORIZED_SPENDERS};
 ///
 /// # Arguments
 /// * `deps` - Dependencies for storage, API, and querier access
-/// * `_env` - Environment information (block height/time, contract address)
+/// * `env` - Environment information (block height/time, contract address)
 /// * `msg` - The query message specifying what information to retrieve
 ///
 /// # Returns
 /// * `StdResult<Binary>` - JSON-serialized query result or error
 pub fn query(
     deps: Deps,
-    _env: Env,
+    env: Env,
     msg: QueryMsg,
 ) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Balance { owner } => query_balance(deps, owner),
-        QueryMsg::IsAuthorized { owner, spender } => query_is_authorized(deps, owner, spender),
+        QueryMsg::Balance { owner, denom } => query_balance(deps, owner, denom),
+        QueryMsg::BalanceRaw { owner } => query_balance_raw(deps, owner),
+        QueryMsg::OwnerAvailable { owner } => query_owner_available(deps, owner),
+        QueryMsg::BalanceV2 { owner, denom } => query_balance_v2(deps, owner, denom),
+        QueryMsg::BalancesBatch { owners, denom } => query_balances_batch(deps, owners, denom),
+        QueryMsg::IsAuthorized { owner, spender } => to_json_binary(&query_is_authorized(deps, env, &owner, &spender)?),
+        QueryMsg::IsAuthorizedV2 { owner, spender } => {
+            let authorized = query_is_authorized(deps, env, &owner, &spender)?;
+            to_json_binary(&IsAuthorizedResponse { owner, spender, authorized })
+        }
+        QueryMsg::Authorization { owner, spender } => to_json_binary(&query_authorization(deps, env, owner, spender)?),
+        QueryMsg::AuthorizationV2 { owner, spender } => to_json_binary(&AuthorizationResponse {
+            authorization: query_authorization(deps, env, owner, spender)?,
+        }),
+        QueryMsg::BatchAuthStatus { pairs } => query_batch_auth_status(deps, env, pairs),
+        QueryMsg::Config {} => query_config(deps),
+        QueryMsg::AgentTotalSpent { spender } => query_agent_total_spent(deps, spender),
+        QueryMsg::FeesPaidBy { owner } => query_fees_paid_by(deps, owner),
+        QueryMsg::AgentMetadata { owner, spender } => to_json_binary(&query_agent_metadata(deps, owner, spender)?),
+        QueryMsg::AgentMetadataV2 { owner, spender } => to_json_binary(&AgentMetadataResponse {
+            metadata: query_agent_metadata(deps, owner, spender)?,
+        }),
+        QueryMsg::BalanceDistribution { denom, buckets, start_after, limit } => {
+            query_balance_distribution(deps, denom, buckets, start_after, limit)
+        }
+        QueryMsg::ActiveAccountCount {} => to_json_binary(&ACTIVE_ACCOUNTS.load(deps.storage)?),
+        QueryMsg::TotalAuthorizations {} => to_json_binary(&AUTH_COUNT.load(deps.storage)?),
+        QueryMsg::WithdrawReadyAt { owner } => to_json_binary(&query_withdraw_ready_at(deps, owner)?),
+        QueryMsg::WithdrawReadyAtV2 { owner } => to_json_binary(&WithdrawReadyAtResponse {
+            ready_at: query_withdraw_ready_at(deps, owner)?,
+        }),
+        QueryMsg::Deposit { id } => to_json_binary(&DEPOSITS.may_load(deps.storage, id)?),
+        QueryMsg::DepositV2 { id } => to_json_binary(&DepositResponse { deposit: DEPOSITS.may_load(deps.storage, id)? }),
+        QueryMsg::NextSpendWindow {} => query_next_spend_window(deps, env),
+        QueryMsg::Stats {} => to_json_binary(&StatsResponse {
+            total_deposited: TOTAL_DEPOSITED.load(deps.storage)?,
+            total_spent: TOTAL_SPENT.load(deps.storage)?,
+        }),
+        QueryMsg::Ledger { start_after, limit } => query_ledger(deps, start_after, limit),
+        QueryMsg::InvariantCheck {} => query_invariant_check(deps, env),
+        QueryMsg::IndexIntegrity { start_after, limit } => query_index_integrity(deps, start_after, limit),
+        QueryMsg::DriftHistory { start_after, limit } => query_drift_history(deps, start_after, limit),
+        QueryMsg::EffectiveAllowance { owner, spender } => {
+            to_json_binary(&query_effective_allowance(deps, env, owner, spender)?)
+        }
+        QueryMsg::EffectiveAllowanceV2 { owner, spender } => to_json_binary(&EffectiveAllowanceResponse {
+            allowance: query_effective_allowance(deps, env, owner, spender)?,
+        }),
+        QueryMsg::SpendableUsd { owner, spender } => query_spendable_usd(deps, env, owner, spender),
+        QueryMsg::CanSpend { owner, spender, amount } => query_can_spend(deps, env, owner, spender, amount),
+        QueryMsg::SimulateBatchSpend { owner, spender, payouts } => query_simulate_batch_spend(deps, env, owner, spender, payouts),
+        QueryMsg::Nonce { owner } => to_json_binary(&query_nonce(deps, &owner)?),
+        QueryMsg::NonceV2 { owner } => {
+            let nonce = query_nonce(deps, &owner)?;
+            to_json_binary(&NonceResponse { owner, nonce })
+        }
+        QueryMsg::LastActivity { owner } => to_json_binary(&query_last_activity(deps, owner)?),
+        QueryMsg::LastActivityV2 { owner } => to_json_binary(&LastActivityResponse {
+            height: query_last_activity(deps, owner)?,
+        }),
+        QueryMsg::ActiveWindow { owner, spender } => query_active_window(deps, env, owner, spender),
+        QueryMsg::Allowance { owner, spender } => query_allowance(deps, owner, spender),
+        QueryMsg::Allowances { owner, start_after, limit } => query_allowances(deps, owner, start_after, limit),
+        QueryMsg::OwnersForSpender { spender, start_after, limit } => {
+            to_json_binary(&query_owners_for_spender(deps, spender, start_after, limit)?)
+        }
+        QueryMsg::OwnersForSpenderV2 { spender, start_after, limit } => to_json_binary(&OwnersForSpenderResponse {
+            owners: query_owners_for_spender(deps, spender, start_after, limit)?,
+        }),
+        QueryMsg::Managers { owner, start_after, limit } => to_json_binary(&query_managers(deps, owner, start_after, limit)?),
+        QueryMsg::ManagersV2 { owner, start_after, limit } => to_json_binary(&ManagersResponse {
+            managers: query_managers(deps, owner, start_after, limit)?,
+        }),
+        QueryMsg::UnconvertedAccounts { start_after, limit } => query_unconverted_accounts(deps, start_after, limit),
     }
 }
 
 /// Queries the balance of a specific address
 ///
-/// Returns the current token balance for the specified owner address.
+/// Returns the current token balance for the specified owner address in one denom.
 /// If the address has no balance record, returns 0.
 ///
 /// # Arguments
 /// * `deps` - Dependencies for storage access and address validation
 /// * `owner` - Address string of the account to check balance for
+/// * `denom` - Denom of the balance, defaulting to the primary denom
 ///
 /// # Returns
-/// * `StdResult<Binary>` - JSON-serialized balance as u128
-fn query_balance(deps: Deps, owner: String) -> StdResult<Binary> {
+/// * `StdResult<Binary>` - JSON-serialized balance as Uint128
+fn query_balance(deps: Deps, owner: String, denom: Option<String>) -> StdResult<Binary> {
     // Validate the owner address
     let owner_addr = deps.api.addr_validate(&owner)?;
+    let denom = denom.map_or_else(|| DENOM.load(deps.storage), Ok)?;
     
     // Look up balance in state, defaulting to 0 if not found
-    let balance = BALANCES.may_load(deps.storage, &owner_addr)?.unwrap_or(0);
+    let balance = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
     
     // Return the serialized balance
     to_json_binary(&balance)
 }
 
+/// Queries the primary denom balance stored under an unvalidated address string
+///
+/// Unlike Balance, a malformed address is not an error: the string is used as the
+/// storage key as-is, so anything never credited reads as 0.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access
+/// * `owner` - Address string to look up, used without validation
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized balance as Uint128
+fn query_balance_raw(deps: Deps, owner: String) -> StdResult<Binary> {
+    let denom = DENOM.load(deps.storage)?;
+    let balance = BALANCES.may_load(deps.storage, (&denom, &Addr::unchecked(owner)))?.unwrap_or_default();
+    to_json_binary(&balance)
+}
+
+/// Breaks an owner's primary denom balance into available and locked parts
+///
+/// The contract has no reserve, savings or deferred spend mechanism yet, so those
+/// components are always 0; the only lock applied today is an admin freeze.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the owner
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `OwnerAvailableResponse`
+fn query_owner_available(deps: Deps, owner: String) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let denom = DENOM.load(deps.storage)?;
+    let total = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+    let (locked_reserve, locked_pending, savings) = (Uint128::zero(), Uint128::zero(), Uint128::zero());
+
+    let frozen = FROZEN.may_load(deps.storage, &owner_addr)?.unwrap_or(false);
+    let spendable_by_owner = if frozen {
+        Uint128::zero()
+    } else {
+        total.saturating_sub(locked_reserve + locked_pending + savings)
+    };
+    to_json_binary(&OwnerAvailableResponse { total, spendable_by_owner, locked_reserve, locked_pending, savings })
+}
+
+/// Queries the balance of a specific address, naming the denom it is in
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the account to check balance for
+/// * `denom` - Denom of the balance, defaulting to the primary denom
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `BalanceResponse`
+fn query_balance_v2(deps: Deps, owner: String, denom: Option<String>) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let denom = denom.map_or_else(|| DENOM.load(deps.storage), Ok)?;
+    let amount = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+    to_json_binary(&BalanceResponse { owner: owner_addr.to_string(), denom, amount })
+}
+
+/// Queries the balances of several addresses in one denom
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owners` - Address strings to look up, at most `MAX_BATCH_OWNERS`
+/// * `denom` - Denom of the balances, defaulting to the primary denom
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `Vec<BalanceEntry>` in request order
+fn query_balances_batch(deps: Deps, owners: Vec<String>, denom: Option<String>) -> StdResult<Binary> {
+    if owners.len() > MAX_BATCH_OWNERS {
+        return Err(StdError::generic_err(format!(
+            "At most {} balances can be queried at once",
+            MAX_BATCH_OWNERS
+        )));
+    }
+    let denom = denom.map_or_else(|| DENOM.load(deps.storage), Ok)?;
+
+    let balances = owners
+        .into_iter()
+        .map(|owner| {
+            let owner_addr = deps.api.addr_validate(&owner)?;
+            let balance = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+            Ok(BalanceEntry { owner: owner_addr.to_string(), balance })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&balances)
+}
+
 /// Checks if a spender is authorized by an owner
 ///
 /// Verifies whether the spender address has been granted spending permission
-/// by the owner address via the AuthorizeSpender message.
+/// by the owner address via the AuthorizeSpender message. Expired authorizations
+/// are reported as not authorized.
 ///
 /// # Arguments
 /// * `deps` - Dependencies for storage access and address validation
+/// * `env` - Environment information, used to check authorization expiry
 /// * `owner` - Address string of the token owner
 /// * `spender` - Address string of the potential spender
 ///
 /// # Returns
-/// * `StdResult<Binary>` - JSON-serialized boolean (true if authorized)
-fn query_is_authorized(deps: Deps, owner: String, spender: String) -> StdResult<Binary> {
+/// * `StdResult<bool>` - True if authorized
+fn query_is_authorized(deps: Deps, env: Env, owner: &str, spender: &str) -> StdResult<bool> {
     // Validate both addresses
-    let owner_addr = deps.api.addr_validate(&owner)?;
-    let spender_addr = deps.api.addr_validate(&spender)?;
+    let owner_addr = deps.api.addr_validate(owner)?;
+    let spender_addr = deps.api.addr_validate(spender)?;
     
-    // Check authorization status in state, defaulting to false if not found
+    // Check authorization status in state, defaulting to false if not found or expired
     let authorized = AUTHORIZED_SPENDERS
         .may_load(deps.storage, (&owner_addr, &spender_addr))?
-        .unwrap_or(false);
+        .is_some_and(|authorization| !authorization.is_expired(&env.block));
     
-    // Return the serialized authorization status
-    to_json_binary(&authorized)
+    Ok(authorized)
+}
+
+/// Retrieves the full terms of an authorization
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `env` - Environment information, used to evaluate the active window
+/// * `owner` - Address string of the token owner
+/// * `spender` - Address string of the spender
+///
+/// # Returns
+/// * `StdResult<Option<AuthorizationDetails>>` - The terms, or `None` if none exist
+fn query_authorization(deps: Deps, env: Env, owner: String, spender: String) -> StdResult<Option<AuthorizationDetails>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let denoms = SPENDER_DENOMS.may_load(deps.storage, (&owner_addr, &spender_addr))?.unwrap_or_default();
+    let details = AUTHORIZED_SPENDERS
+        .may_load(deps.storage, (&owner_addr, &spender_addr))?
+        .map(|authorization| AuthorizationDetails {
+            owner: owner_addr.to_string(),
+            spender: spender_addr.to_string(),
+            active: !authorization.is_expired(&env.block) && !authorization.is_pending(&env.block),
+            expires_at_height: authorization.expires_at_height,
+            starts_at: authorization.starts_at,
+            allowance: authorization.allowance,
+            max_per_tx: authorization.max_per_tx,
+            limit_per_window: authorization.limit_per_window,
+            window_seconds: authorization.window_seconds,
+            secret_hash: authorization.secret_hash,
+            metadata: authorization.metadata,
+            refill: authorization.refill,
+            cooldown_seconds: authorization.cooldown_seconds,
+            denoms,
+        });
+    Ok(details)
+}
+
+/// Checks the authorization status of several owner/spender pairs
+///
+/// Each pair is evaluated like IsAuthorized, and the authorization's metadata is
+/// returned alongside. Results keep the order of the request.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `env` - Environment information, used to evaluate expiry
+/// * `pairs` - Owner/spender address pairs to check, at most `MAX_BATCH_PAIRS`
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `Vec<AuthStatus>`
+fn query_batch_auth_status(deps: Deps, env: Env, pairs: Vec<(String, String)>) -> StdResult<Binary> {
+    if pairs.len() > MAX_BATCH_PAIRS {
+        return Err(StdError::generic_err(format!(
+            "At most {} pairs can be checked at once",
+            MAX_BATCH_PAIRS
+        )));
+    }
+
+    let statuses = pairs
+        .into_iter()
+        .map(|(owner, spender)| {
+            let owner_addr = deps.api.addr_validate(&owner)?;
+            let spender_addr = deps.api.addr_validate(&spender)?;
+            let authorization = AUTHORIZED_SPENDERS
+                .may_load(deps.storage, (&owner_addr, &spender_addr))?
+                .filter(|authorization| !authorization.is_expired(&env.block));
+            Ok(AuthStatus {
+                owner: owner_addr.to_string(),
+                spender: spender_addr.to_string(),
+                authorized: authorization.is_some(),
+                metadata: authorization.and_then(|authorization| authorization.metadata),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&statuses)
+}
+
+/// Retrieves the contract configuration
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `ConfigResponse`
+fn query_config(deps: Deps) -> StdResult<Binary> {
+    to_json_binary(&ConfigResponse {
+        admin: ADMIN.load(deps.storage)?.to_string(),
+        denom: DENOM.load(deps.storage)?,
+        decimals: DECIMALS.load(deps.storage)?,
+        accepted_denoms: ACCEPTED_DENOMS.load(deps.storage)?,
+        denom_aliases: DENOM_ALIASES.load(deps.storage)?,
+        denom_precisions: DENOM_PRECISIONS.may_load(deps.storage)?.unwrap_or_default(),
+        keeper_reward: KEEPER_REWARD.load(deps.storage)?,
+        admin_can_deposit: ADMIN_CAN_DEPOSIT.load(deps.storage)?,
+        recovery_delay_blocks: RECOVERY_DELAY_BLOCKS.load(deps.storage)?,
+        max_spenders_per_owner: MAX_SPENDERS_PER_OWNER.load(deps.storage)?,
+        strict_deposit: STRICT_DEPOSIT.load(deps.storage)?,
+        min_deposit: MIN_DEPOSIT.load(deps.storage)?,
+        deposits_enabled: DEPOSITS_ENABLED.load(deps.storage)?,
+        fee_bps: FEE_BPS.load(deps.storage)?,
+        spend_fee_bps: SPEND_FEE_BPS.load(deps.storage)?,
+        price_oracle: PRICE_ORACLE.may_load(deps.storage)?.map(|oracle| oracle.to_string()),
+        oracle_auto_pause: ORACLE_AUTO_PAUSE.load(deps.storage)?,
+        policy: POLICY.may_load(deps.storage)?.map(|policy| policy.to_string()),
+        pending_denom: PENDING_DENOM.may_load(deps.storage)?,
+        max_received_per_account: MAX_RECEIVED_PER_ACCOUNT.may_load(deps.storage)?,
+        spending_blackouts: SPENDING_BLACKOUTS.may_load(deps.storage)?.unwrap_or_default(),
+        global_spend_cap: GLOBAL_SPEND_CAP.may_load(deps.storage)?,
+        withdraw_timelock: WITHDRAW_TIMELOCK.may_load(deps.storage)?,
+        usd_spends_paused: USD_SPENDS_PAUSED.load(deps.storage)?,
+        paused: PAUSED.load(deps.storage)?,
+        minimal_events: MINIMAL_EVENTS.load(deps.storage)?,
+    })
+}
+
+/// Retrieves the cumulative spend fees charged to an owner
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the owner
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized total as Uint128
+fn query_fees_paid_by(deps: Deps, owner: String) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let paid = FEES_PAID_BY.may_load(deps.storage, &owner_addr)?.unwrap_or_default();
+    to_json_binary(&paid)
+}
+
+/// Sums everything a spender has spent on behalf of owners
+///
+/// Iterates the spender's prefix of the spent-by ledger, which holds one entry
+/// per owner the spender has drawn from.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `spender` - Address string of the agent
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized total as Uint128
+fn query_agent_total_spent(deps: Deps, spender: String) -> StdResult<Binary> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+
+    let total = SPENT_BY
+        .prefix(&spender_addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| -> StdResult<Uint128> {
+            let (_, spent) = item?;
+            Ok(total + spent)
+        })?;
+
+    to_json_binary(&total)
+}
+
+/// Retrieves the metadata attached to an owner's authorization of a spender
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the token owner
+/// * `spender` - Address string of the authorized agent
+///
+/// # Returns
+/// * `StdResult<Option<Binary>>` - The metadata, if any
+fn query_agent_metadata(deps: Deps, owner: String, spender: String) -> StdResult<Option<Binary>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+
+    let metadata = AUTHORIZED_SPENDERS
+        .may_load(deps.storage, (&owner_addr, &spender_addr))?
+        .and_then(|authorization| authorization.metadata);
+
+    Ok(metadata)
+}
+
+/// Counts a page of accounts by balance bucket
+///
+/// Scans at most `limit` balance entries in address order, so gas is bounded per
+/// call. Clients aggregate a full distribution by following `next_start_after`.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `denom` - Denom whose balances are counted, defaulting to the primary denom
+/// * `buckets` - Strictly ascending bucket boundaries
+/// * `start_after` - Address to resume scanning after
+/// * `limit` - Maximum number of accounts to scan
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `BalanceDistributionResponse`
+fn query_balance_distribution(
+    deps: Deps,
+    denom: Option<String>,
+    buckets: Vec<Uint128>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    if buckets.len() > MAX_DISTRIBUTION_BUCKETS {
+        return Err(StdError::generic_err(format!(
+            "At most {} bucket boundaries are allowed",
+            MAX_DISTRIBUTION_BUCKETS
+        )));
+    }
+    if buckets.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(StdError::generic_err("Bucket boundaries must be strictly ascending"));
+    }
+
+    let denom = denom.map_or_else(|| DENOM.load(deps.storage), Ok)?;
+    let start_addr = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_DISTRIBUTION_LIMIT).min(MAX_DISTRIBUTION_LIMIT) as usize;
+
+    let mut counts = vec![0u64; buckets.len() + 1];
+    let mut scanned = 0;
+    let mut last = None;
+    for item in BALANCES
+        .prefix(&denom)
+        .range(deps.storage, start_addr.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+    {
+        let (addr, balance) = item?;
+        // Number of boundaries at or below the balance is the bucket index
+        let bucket = buckets.partition_point(|boundary| *boundary <= balance);
+        counts[bucket] += 1;
+        scanned += 1;
+        last = Some(addr);
+    }
+
+    // A short page means the scan reached the end of the map
+    let next_start_after = if scanned == limit { last.map(|addr| addr.to_string()) } else { None };
+
+    to_json_binary(&BalanceDistributionResponse { counts, next_start_after })
+}
+
+/// Lists activity ledger entries
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access
+/// * `start_after` - Entry id to resume listing after
+/// * `limit` - Maximum number of entries to return
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `LedgerResponse`
+fn query_ledger(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let entries = LEDGER
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<_>>()?;
+    to_json_binary(&LedgerResponse { entries })
+}
+
+/// Lists recorded drift samples
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access
+/// * `start_after` - Sample id to resume listing after
+/// * `limit` - Maximum number of samples to return
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `DriftHistoryResponse`
+fn query_drift_history(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let samples = DRIFT_LOG
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, sample)| sample))
+        .collect::<StdResult<_>>()?;
+    to_json_binary(&DriftHistoryResponse { samples })
+}
+
+/// Computes a spender's effective allowance at the current block without mutating state
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `env` - Environment information, used to evaluate expiry and window refills
+/// * `owner` - Address string of the account that granted the authorization
+/// * `spender` - Address string of the authorized spender
+///
+/// # Returns
+/// * `StdResult<Option<Uint128>>` - The effective allowance, `None` meaning unlimited
+fn query_effective_allowance(deps: Deps, env: Env, owner: String, spender: String) -> StdResult<Option<Uint128>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    effective_allowance(deps, &env.block, &owner_addr, &spender_addr)
+}
+
+/// Reports whether a SpendFrom would succeed at the current block, and why not
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access, address validation and the policy query
+/// * `env` - Environment information, used to evaluate expiry and window refills
+/// * `owner` - Address string of the account that would be debited
+/// * `spender` - Address string of the account that would send the SpendFrom
+/// * `amount` - Amount of the primary denom that would be spent
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `CanSpendResponse`
+fn query_can_spend(deps: Deps, env: Env, owner: String, spender: String, amount: Uint128) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let reason = spend_precheck(deps, &env, &owner_addr, &spender_addr, amount).err().map(|err| err.to_string());
+    to_json_binary(&CanSpendResponse { allowed: reason.is_none(), reason })
+}
+
+/// Runs SpendFrom's checks in the primary denom without a secret, returning the first failure
+fn spend_precheck(deps: Deps, env: &Env, owner: &Addr, spender: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::Paused {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err("Amount must be positive")));
+    }
+    let denom = DENOM.load(deps.storage)?;
+    let authorization = assert_can_spend(deps, env, owner, spender, &denom, amount, None)?;
+    if spender != owner {
+        assert_within_receive_cap(deps.storage, spender, &denom, amount)?;
+    }
+    let total = checked_total(amount, spend_fee(deps.storage, authorization.as_ref(), amount)?)?;
+    let balance = BALANCES.may_load(deps.storage, (&denom, owner))?.unwrap_or_default();
+    if balance < total {
+        return Err(ContractError::InsufficientBalance { available: balance, requested: total });
+    }
+    Ok(())
+}
+
+/// Reports whether a BatchSpendFrom would succeed at the current block, and why not
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access, address validation and the policy query
+/// * `env` - Environment information, used to evaluate expiry and window refills
+/// * `owner` - Address string of the account that would be debited
+/// * `spender` - Address string of the account that would send the BatchSpendFrom
+/// * `payouts` - List of (recipient address, amount) pairs in the primary denom
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `SimulateBatchSpendResponse`; `total`
+///   saturates at the maximum if the payouts overflow
+fn query_simulate_batch_spend(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    spender: String,
+    payouts: Vec<(String, Uint128)>,
+) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let total = payouts.iter().fold(Uint128::zero(), |sum, (_, amount)| sum.saturating_add(*amount));
+    let reason = batch_spend_precheck(deps, &env, &owner_addr, &spender_addr, payouts).err().map(|err| err.to_string());
+    to_json_binary(&SimulateBatchSpendResponse { would_succeed: reason.is_none(), total, reason })
+}
+
+/// Runs BatchSpendFrom's checks in the primary denom, returning the first failure
+fn batch_spend_precheck(deps: Deps, env: &Env, owner: &Addr, spender: &Addr, payouts: Vec<(String, Uint128)>) -> Result<(), ContractError> {
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::Paused {});
+    }
+    let denom = DENOM.load(deps.storage)?;
+    let (_, total, authorization) = check_batch_spend(deps, env, owner, spender, &denom, payouts)?;
+    let total = checked_total(total, spend_fee(deps.storage, authorization.as_ref(), total)?)?;
+    let balance = BALANCES.may_load(deps.storage, (&denom, owner))?.unwrap_or_default();
+    if balance < total {
+        return Err(ContractError::InsufficientBalance { available: balance, requested: total });
+    }
+    Ok(())
+}
+
+/// Computes what a spender can spend from an owner at `block`; `None` is unlimited
+fn effective_allowance(deps: Deps, block: &BlockInfo, owner: &Addr, spender: &Addr) -> StdResult<Option<Uint128>> {
+    let effective = match AUTHORIZED_SPENDERS.may_load(deps.storage, (owner, spender))? {
+        Some(authorization) if !authorization.is_expired(block) && !authorization.is_pending(block) => {
+            let window = window_remaining(deps.storage, block, owner, spender, &authorization)?;
+            match (authorization.allowance, window) {
+                (Some(allowance), Some(window)) => Some(allowance.min(window)),
+                (allowance, window) => allowance.or(window),
+            }
+        }
+        _ => Some(Uint128::zero()),
+    };
+    Ok(effective)
+}
+
+/// Values in micro-USD what a spender can spend from an owner right now
+///
+/// The spendable amount is the owner's primary denom balance capped by the
+/// spender's effective allowance; spend fees are not deducted. The conversion
+/// rounds down.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and the oracle querier
+/// * `env` - Environment information, used to evaluate the authorization and price age
+/// * `owner` - Address string of the owner
+/// * `spender` - Address string of the spender
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized Uint128 micro-USD value
+fn query_spendable_usd(deps: Deps, env: Env, owner: String, spender: String) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let denom = DENOM.load(deps.storage)?;
+
+    let balance = BALANCES.may_load(deps.storage, (&denom, &owner_addr))?.unwrap_or_default();
+    let spendable = match effective_allowance(deps, &env.block, &owner_addr, &spender_addr)? {
+        Some(allowance) => balance.min(allowance),
+        None => balance,
+    };
+    let oracle = load_price_oracle(deps.storage)?;
+    let price = oracle_price(deps, &oracle, &env.block, &denom)?
+        .ok_or_else(|| StdError::generic_err("Oracle price is zero or stale"))?;
+    let usd_amount = spendable.checked_mul_floor(price).map_err(|err| StdError::generic_err(err.to_string()))?;
+    to_json_binary(&usd_amount)
+}
+
+/// Loads the configured price oracle, failing if none is set
+pub(crate) fn load_price_oracle(storage: &dyn Storage) -> StdResult<Addr> {
+    PRICE_ORACLE
+        .may_load(storage)?
+        .ok_or_else(|| StdError::generic_err("No price oracle configured"))
+}
+
+/// Fetches `oracle`'s price for `denom` in micro-USD per base unit
+///
+/// Returns `None` if the price is zero or older than `MAX_PRICE_AGE_SECONDS`, and
+/// an error if the oracle query fails.
+pub(crate) fn oracle_price(deps: Deps, oracle: &Addr, block: &BlockInfo, denom: &str) -> StdResult<Option<Decimal>> {
+    let PriceResponse { price, updated_at } = deps
+        .querier
+        .query_wasm_smart(oracle, &OracleQueryMsg::Price { denom: denom.to_string() })?;
+    if price.is_zero() || updated_at.plus_seconds(MAX_PRICE_AGE_SECONDS) < block.time {
+        return Ok(None);
+    }
+    Ok(Some(price))
+}
+
+/// Retrieves the nonce expected on an owner's next signed spend
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string to look up
+///
+/// # Returns
+/// * `StdResult<u64>` - The expected nonce
+fn query_nonce(deps: Deps, owner: &str) -> StdResult<u64> {
+    let owner_addr = deps.api.addr_validate(owner)?;
+    Ok(NONCES.may_load(deps.storage, &owner_addr)?.unwrap_or(0))
+}
+
+/// Retrieves the block height of an address's last execute message
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string to look up
+///
+/// # Returns
+/// * `StdResult<Option<u64>>` - The block height, or `None` if the address never executed
+fn query_last_activity(deps: Deps, owner: String) -> StdResult<Option<u64>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    LAST_ACTIVITY.may_load(deps.storage, &owner_addr)
+}
+
+/// Retrieves when an owner's requested withdrawal becomes claimable
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string to look up
+///
+/// # Returns
+/// * `StdResult<Option<Timestamp>>` - The unlock time, or `None` if none is requested
+fn query_withdraw_ready_at(deps: Deps, owner: String) -> StdResult<Option<Timestamp>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let withdrawal = PENDING_WITHDRAWALS.may_load(deps.storage, &owner_addr)?;
+    Ok(withdrawal.map(|withdrawal| withdrawal.unlock_time))
+}
+
+/// Reports an authorization's start and expiry and whether it is active now
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `env` - Environment information, used to evaluate the window at the current block
+/// * `owner` - Address string of the owner
+/// * `spender` - Address string of the spender
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `ActiveWindowResponse`
+fn query_active_window(deps: Deps, env: Env, owner: String, spender: String) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let response = match AUTHORIZED_SPENDERS.may_load(deps.storage, (&owner_addr, &spender_addr))? {
+        Some(authorization) => ActiveWindowResponse {
+            starts_at: authorization.starts_at,
+            expires_at: authorization.expires_at_height,
+            currently_active: !authorization.is_pending(&env.block) && !authorization.is_expired(&env.block),
+        },
+        None => ActiveWindowResponse { starts_at: None, expires_at: None, currently_active: false },
+    };
+    to_json_binary(&response)
+}
+
+/// Cross-checks a slice of the authorization map against the reverse index
+///
+/// Both maps are keyed by a pair of addresses, so they are merged into one ordered
+/// sequence of keys: an authorization `(owner, spender)` contributes the key
+/// `(owner, spender)` and a reverse entry `(spender, owner)` the key
+/// `(spender, owner)`. The first `limit` keys after `start_after` are checked
+/// against the other map, keeping the cost of each call bounded.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `start_after` - Key to resume scanning after
+/// * `limit` - Maximum number of keys to check
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `IndexIntegrityResponse`
+fn query_index_integrity(deps: Deps, start_after: Option<(String, String)>, limit: Option<u32>) -> StdResult<Binary> {
+    let start = start_after
+        .map(|(first, second)| -> StdResult<_> { Ok((deps.api.addr_validate(&first)?, deps.api.addr_validate(&second)?)) })
+        .transpose()?;
+    let bound = start.as_ref().map(|(first, second)| Bound::exclusive((first, second)));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // Take one extra key from each side to learn whether anything is left
+    let mut keys = AUTHORIZED_SPENDERS
+        .keys(deps.storage, bound.clone(), None, Order::Ascending)
+        .take(limit + 1)
+        .map(|key| key.map(|key| (key, true)))
+        .collect::<StdResult<Vec<_>>>()?;
+    keys.extend(
+        SPENDER_INDEX
+            .keys(deps.storage, bound, None, Order::Ascending)
+            .take(limit + 1)
+            .map(|key| key.map(|key| (key, false)))
+            .collect::<StdResult<Vec<_>>>()?,
+    );
+    keys.sort();
+
+    // Keep a key that appears on both sides together, so the cursor never splits it
+    let mut scanned = keys.len().min(limit);
+    while scanned > 0 && scanned < keys.len() && keys[scanned].0 == keys[scanned - 1].0 {
+        scanned += 1;
+    }
+    let next_start_after = (scanned < keys.len())
+        .then(|| keys[scanned - 1].0.clone())
+        .map(|(first, second)| (first.to_string(), second.to_string()));
+
+    let mut mismatches = vec![];
+    for ((first, second), primary) in keys.into_iter().take(scanned) {
+        if primary {
+            if !SPENDER_INDEX.has(deps.storage, (&second, &first)) {
+                mismatches.push(IndexMismatch::MissingReverse { owner: first.to_string(), spender: second.to_string() });
+            }
+        } else if !AUTHORIZED_SPENDERS.has(deps.storage, (&second, &first)) {
+            mismatches.push(IndexMismatch::MissingPrimary { owner: second.to_string(), spender: first.to_string() });
+        }
+    }
+    to_json_binary(&IndexIntegrityResponse { mismatches, next_start_after })
+}
+
+/// Retrieves one spender's remaining allowance
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the owner
+/// * `spender` - Address string of the spender
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `AllowanceInfo`
+fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let info = match AUTHORIZED_SPENDERS.may_load(deps.storage, (&owner_addr, &spender_addr))? {
+        Some(authorization) => allowance_info(spender_addr, authorization),
+        None => AllowanceInfo { spender: spender_addr.to_string(), amount: Uint128::zero(), expires_at_height: None },
+    };
+    to_json_binary(&info)
+}
+
+/// Lists the spenders an owner has authorized along with their remaining allowance
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the owner whose grants are listed
+/// * `start_after` - Spender address to resume listing after
+/// * `limit` - Maximum number of entries to return
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `Vec<AllowanceInfo>`
+fn query_allowances(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let start_addr = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let allowances = AUTHORIZED_SPENDERS
+        .prefix(&owner_addr)
+        .range(deps.storage, start_addr.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (spender, authorization) = item?;
+            Ok(allowance_info(spender, authorization))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&allowances)
+}
+
+/// Lists the owners that have authorized a spender
+///
+/// Reads at most `limit` keys from the spender's prefix of the reverse index, so the
+/// cost depends on the page size rather than the total number of authorizations.
+/// Expired authorizations stay listed until CleanupExpired removes them.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `spender` - Address string of the spender
+/// * `start_after` - Owner address to resume listing after
+/// * `limit` - Maximum number of owners to return
+///
+/// # Returns
+/// * `StdResult<Vec<String>>` - Owner addresses in ascending order
+fn query_owners_for_spender(
+    deps: Deps,
+    spender: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<String>> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let start_addr = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    SPENDER_INDEX
+        .prefix(&spender_addr)
+        .keys(deps.storage, start_addr.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|owner| owner.to_string()))
+        .collect()
+}
+
+/// Lists the managers an owner has appointed
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `owner` - Address string of the owner
+/// * `start_after` - Manager address to resume listing after
+/// * `limit` - Maximum number of managers to return
+///
+/// # Returns
+/// * `StdResult<Vec<String>>` - Manager addresses in ascending order
+fn query_managers(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<String>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let start_addr = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    MANAGERS
+        .prefix(&owner_addr)
+        .keys(deps.storage, start_addr.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|manager| manager.to_string()))
+        .collect()
+}
+
+/// Lists accounts holding the primary denom that have not converted to the pending denom
+///
+/// Walks the primary denom's balances, so a page over mostly converted accounts may
+/// read more entries than it returns.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and address validation
+/// * `start_after` - Address to resume listing after
+/// * `limit` - Maximum number of accounts to return
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `Vec<BalanceEntry>`
+fn query_unconverted_accounts(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    if PENDING_DENOM.may_load(deps.storage)?.is_none() {
+        return to_json_binary(&Vec::<BalanceEntry>::new());
+    }
+    let denom = DENOM.load(deps.storage)?;
+    let start_addr = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut accounts = vec![];
+    for item in BALANCES
+        .prefix(&denom)
+        .range(deps.storage, start_addr.as_ref().map(Bound::exclusive), None, Order::Ascending)
+    {
+        let (account, balance) = item?;
+        if !balance.is_zero() && !CONVERTED.has(deps.storage, &account) {
+            accounts.push(BalanceEntry { owner: account.to_string(), balance });
+            if accounts.len() == limit {
+                break;
+            }
+        }
+    }
+    to_json_binary(&accounts)
+}
+
+/// Describes an authorization as an `AllowanceInfo`, reporting unlimited as `Uint128::MAX`
+fn allowance_info(spender: Addr, authorization: Authorization) -> AllowanceInfo {
+    AllowanceInfo {
+        spender: spender.to_string(),
+        amount: authorization.allowance.unwrap_or(Uint128::MAX),
+        expires_at_height: authorization.expires_at_height,
+    }
+}
+
+/// Returns the room left in an authorization's current rate-limit window
+///
+/// A window that has elapsed is treated as refilled. Returns `None` when the
+/// authorization has no per-window limit.
+pub(crate) fn window_remaining(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+    spender: &Addr,
+    authorization: &Authorization,
+) -> StdResult<Option<Uint128>> {
+    let (Some(limit), Some(window_seconds)) = (authorization.limit_per_window, authorization.window_seconds) else {
+        return Ok(None);
+    };
+    let now = block.time.seconds();
+    let spent = match SPEND_WINDOWS.may_load(storage, (owner, spender))? {
+        Some((start, spent)) if now < start.saturating_add(window_seconds) => spent,
+        _ => Uint128::zero(),
+    };
+    Ok(Some(limit.saturating_sub(spent)))
+}
+
+/// Returns the soonest time, from the current block on, outside every blackout window
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access
+/// * `env` - Environment information, providing the current block time
+///
+/// # Returns
+/// * `StdResult<Binary>` - Binary-encoded Timestamp or error
+fn query_next_spend_window(deps: Deps, env: Env) -> StdResult<Binary> {
+    let blackouts = SPENDING_BLACKOUTS.may_load(deps.storage)?.unwrap_or_default();
+    let mut block = env.block;
+    // Each step moves strictly forward to a window's end, so this ends after at most one step per window
+    while let Some(window) = blackouts.iter().find(|window| window.contains(&block)) {
+        block.time = window.end;
+    }
+    to_json_binary(&block.time)
+}
+
+/// Returns the room left under the global spending cap in the current epoch
+///
+/// An epoch that has elapsed is treated as reset. Returns `None` when no global cap
+/// is configured.
+pub(crate) fn global_cap_remaining(storage: &dyn Storage, block: &BlockInfo) -> StdResult<Option<Uint128>> {
+    let Some(GlobalSpendCap { cap, epoch_seconds }) = GLOBAL_SPEND_CAP.may_load(storage)? else {
+        return Ok(None);
+    };
+    let now = block.time.seconds();
+    let spent = match GLOBAL_SPEND_EPOCH.may_load(storage)? {
+        Some((start, spent)) if now < start.saturating_add(epoch_seconds) => spent,
+        _ => Uint128::zero(),
+    };
+    Ok(Some(cap.saturating_sub(spent)))
+}
+
+/// Compares the internal ledger against the contract's bank balance in the primary denom
+///
+/// A live version of the RecordDrift snapshot. Gas grows linearly with the number of
+/// balances, as for `ledger_total`.
+///
+/// # Arguments
+/// * `deps` - Dependencies for storage access and the bank querier
+/// * `env` - Environment information, providing the contract address
+///
+/// # Returns
+/// * `StdResult<Binary>` - JSON-serialized `InvariantResponse`
+fn query_invariant_check(deps: Deps, env: Env) -> StdResult<Binary> {
+    let denom = DENOM.load(deps.storage)?;
+    let ledger_total = ledger_total(deps.storage, &denom)?;
+    let escrow_balance = deps.querier.query_balance(&env.contract.address, &denom)?.amount;
+    to_json_binary(&InvariantResponse {
+        ledger_total,
+        escrow_balance,
+        consistent: ledger_total == escrow_balance,
+    })
+}
+
+/// Sums every internal balance in `denom`, plus the keeper reward pool for the primary denom
+///
+/// This is the amount of `denom` the contract owes out, and should match its
+/// actual bank balance. Iterates every account holding the denom, so gas grows
+/// linearly with the number of balances. Fails rather than wrapping if the sum
+/// overflows.
+pub(crate) fn ledger_total(storage: &dyn Storage, denom: &str) -> StdResult<Uint128> {
+    let pool = if DENOM.load(storage)? == denom { REWARD_POOL.load(storage)? } else { Uint128::zero() };
+    BALANCES
+        .prefix(denom)
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(pool, |total, item| {
+            let (_, balance) = item?;
+            Ok(total.checked_add(balance)?)
+        })
 }
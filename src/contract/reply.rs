@@ -0,0 +1,62 @@
+/// Reply logic for the Credits Delegation contract
+///
+/// This module handles the results of submessages dispatched by execute handlers.
+/// Withdrawals send their bank transfer as a submessage, so a failed transfer can
+/// be answered by restoring the debited balance instead of losing track of it.
+use cosmwasm_std::{DepsMut, Env, Reply, Response, StdError, SubMsgResult};
+use crate::contract::exec::{amount_attr, credit, record_ledger};
+use crate::error::ContractError;
+use crate::state::PENDING_WITHDRAWAL;
+
+/// Reply id of the bank transfer dispatched by Withdraw
+pub const WITHDRAW_REPLY_ID: u64 = 1;
+
+/// Main entry point for all submessage replies
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `env` - Environment information, recorded in the activity ledger
+/// * `msg` - The reply carrying the submessage id and result
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response or error
+pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    match msg.id {
+        WITHDRAW_REPLY_ID => reply_withdraw(deps, env, msg.result),
+        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id {}", id)))),
+    }
+}
+
+/// Settles a pending withdrawal, re-crediting it if the bank transfer failed
+///
+/// The failed transfer's own state changes are already reverted by the chain, but
+/// the debit made by Withdraw is not, so the amount is credited back here.
+fn reply_withdraw(deps: DepsMut, env: Env, result: SubMsgResult) -> Result<Response, ContractError> {
+    let pending = PENDING_WITHDRAWAL.load(deps.storage)?;
+    PENDING_WITHDRAWAL.remove(deps.storage);
+
+    match result {
+        SubMsgResult::Ok(_) => Ok(Response::new().add_attribute("action", "withdraw_confirmed")),
+        SubMsgResult::Err(err) => {
+            let balance = credit(deps.storage, &pending.account, &pending.denom, pending.amount)?;
+            record_ledger(
+                deps.storage,
+                &env,
+                "withdraw_reverted",
+                &pending.account,
+                None,
+                Some((&pending.denom, pending.amount)),
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "withdraw_reverted")
+                .add_attribute("owner", pending.account)
+                .add_attribute("denom", pending.denom)
+                .add_attributes([amount_attr("amount", pending.amount), amount_attr("new_balance", balance)])
+                .add_attribute("error", err))
+        }
+    }
+}
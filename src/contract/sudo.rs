@@ -0,0 +1,36 @@
+/// Sudo logic for the Credits Delegation contract
+///
+/// This module handles privileged operations triggered by the chain itself,
+/// typically through governance. No sender is involved, so no admin check applies.
+use cosmwasm_std::{DepsMut, Env, Response};
+use crate::error::ContractError;
+use crate::msg::sudo::SudoMsg;
+use crate::state::PAUSED;
+
+/// Main entry point for all sudo messages
+///
+/// # Arguments
+/// * `deps` - Mutable dependencies for storage access
+/// * `_env` - Environment information (block height/time, contract address)
+/// * `msg` - The sudo message with the operation to perform
+///
+/// # Returns
+/// * `Result<Response, ContractError>` - Success response or error
+pub fn sudo(
+    deps: DepsMut,
+    _env: Env,
+    msg: SudoMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ForcePause {} => set_paused(deps, true),
+        SudoMsg::ForceUnpause {} => set_paused(deps, false),
+    }
+}
+
+/// Stores the pause flag and reports the new state
+fn set_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+    PAUSED.save(deps.storage, &paused)?;
+    Ok(Response::new()
+        .add_attribute("action", "sudo_set_paused")
+        .add_attribute("paused", paused.to_string()))
+}
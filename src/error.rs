@@ -1,5 +1,5 @@
 use thiserror::Error;
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 
 /// Custom error types for the Credits Delegation contract
 ///
@@ -13,7 +13,169 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
     
-    /// Placeholder for features that are defined but not yet implemented
+    /// Returned when the admin would be set to the contract's own address
+    #[error("Invalid admin: the contract cannot be its own admin")]
+    InvalidAdmin {},
+
+    /// Returned when a spender's authorization has passed its expiry height
+    #[error("Authorization expired")]
+    AuthorizationExpired {},
+
+    /// Returned when a spender uses an authorization before its start time
+    #[error("Authorization is not active yet")]
+    NotYetActive {},
+
+    /// Returned when a spender spends again before the authorization's cooldown has passed
+    #[error("Spend cooldown active: retry after {retry_after}")]
+    Cooldown { retry_after: Timestamp },
+
+    /// Returned when a spend moves more than the authorization's per-transaction cap
+    #[error("Amount {requested} exceeds the per-transaction cap of {cap}")]
+    PerTxCapExceeded { cap: Uint128, requested: Uint128 },
+
+    /// Returned when authorization metadata exceeds the maximum stored size
+    #[error("Metadata is {size} bytes, above the maximum of {max}")]
+    MetadataTooLarge { size: usize, max: usize },
+
+    /// Returned when an allowance refill interval exceeds the maximum
+    #[error("Refill interval of {interval_seconds} seconds is above the maximum of {max}")]
+    InvalidRefillInterval { interval_seconds: u64, max: u64 },
+
+    /// Returned when a delegated spend falls inside an admin-configured blackout window
+    #[error("Delegated spending is blocked during the current blackout window")]
+    SpendingBlackout {},
+
+    /// Returned when a spend amount is finer than its denom's configured precision
+    #[error("Spend amount exceeds the denom's configured precision")]
+    PrecisionExceeded {},
+
+    /// Returned when a delegated spend would exceed the contract-wide cap for the epoch
+    #[error("Spend exceeds the global cap: {remaining} remaining in the current epoch")]
+    GlobalCapExceeded { remaining: Uint128 },
+
+    /// Returned when a spend would exceed the authorization's per-window limit
+    #[error("Spend exceeds the rate limit: {remaining} remaining in the current window")]
+    RateLimitExceeded { remaining: Uint128 },
+
+    /// Returned when a deposit is sent without any funds
+    #[error("No funds sent")]
+    NoFunds {},
+
+    /// Returned when funds are attached to a message that cannot credit them
+    #[error("This message does not accept funds")]
+    FundsNotAccepted {},
+
+    /// Returned when a deposit carries more than one coin
+    #[error("Send exactly one coin denomination")]
+    MultipleDenoms {},
+
+    /// Returned when a deposit's coin is not the contract's configured denom
+    #[error("Invalid denom: expected {expected}, got {got}")]
+    InvalidDenom { expected: String, got: String },
+
+    /// Returned when a spend would exceed the authorization's remaining total allowance
+    #[error("Spend exceeds the allowance: {remaining} remaining")]
+    AllowanceExceeded { remaining: Uint128 },
+
+    /// Returned when a spender spends a denom the owner has not allowed it
+    #[error("Spender may not spend {denom}")]
+    DenomNotAllowed { denom: String },
+
+    /// Returned when a spender on the admin blocklist tries to spend or be authorized
+    #[error("Spender is blocked")]
+    SpenderBlocked {},
+
+    /// Returned when a spend omits the secret required by the authorization,
+    /// or supplies one whose sha256 does not match the stored hash
+    #[error("Missing or incorrect spending secret")]
+    BadSecret {},
+
+    /// Returned when a frozen owner tries to grant a new authorization
+    #[error("Account is frozen")]
+    Frozen {},
+
+    /// Returned when a frozen account's balance would be deposited to, spent or withdrawn
+    #[error("Account {account} is frozen")]
+    AccountFrozen { account: String },
+
+    /// Returned when the price oracle reports a zero or stale price
+    #[error("Price oracle returned a zero or stale price")]
+    BadPrice {},
+
+    /// Returned when the configured policy contract denies a delegated spend
+    #[error("Spend denied by policy: {reason}")]
+    PolicyDenied { reason: String },
+
+    /// Returned when SpendUsd is called while USD spends are paused
+    #[error("USD spends are paused")]
+    UsdSpendsPaused {},
+
+    /// Returned when an owner who already has the maximum number of spenders authorizes another
+    #[error("Too many spenders; an owner may authorize at most {max}")]
+    TooManySpenders { max: u32 },
+
+    /// Returned when a signed spend carries a nonce other than the owner's current one
+    #[error("Invalid nonce: expected {expected}, got {got}")]
+    InvalidNonce { expected: u64, got: u64 },
+
+    /// Returned when a signed spend's owner has not registered a signing key
+    #[error("Owner has no signing key registered")]
+    NoSigningKey {},
+
+    /// Returned when a signed spend's signature does not verify against the owner's key
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
+    /// Returned when an immediate outflow would take an account's outflows in the
+    /// current window above the timelock threshold
+    #[error("Immediate outflows above {threshold} per window must be requested with RequestWithdraw")]
+    WithdrawTimelocked { threshold: Uint128 },
+
+    /// Returned when ClaimWithdraw is called before the requested withdrawal unlocks
+    #[error("Withdrawal is locked until {unlock_time}")]
+    WithdrawLocked { unlock_time: Timestamp },
+
+    /// Returned when ClaimWithdraw is called without a requested withdrawal
+    #[error("No withdrawal has been requested")]
+    NoPendingWithdrawal {},
+
+    /// Returned when a spend would push the receiving account's balance over the cap
+    #[error("Spend would push the recipient's balance over the receive cap")]
+    ReceiveCapExceeded {},
+
+    /// Returned when configuration parameters contradict each other
+    #[error("Conflicting configuration: {detail}")]
+    ConflictingConfig { detail: String },
+
+    /// Returned when an account's balance cannot cover a debit
+    #[error("Insufficient balance: have {available}, need {requested}")]
+    InsufficientBalance { available: Uint128, requested: Uint128 },
+
+    /// Returned when a deposit would credit the admin while admin deposits are disabled
+    #[error("The admin cannot deposit")]
+    AdminCannotDeposit {},
+
+    /// Returned when Recover is called before the owner has been inactive long enough
+    #[error("Recovery is not available until height {available_at_height}")]
+    RecoveryNotReady { available_at_height: u64 },
+
+    /// Returned when a deposit fee above 10000 basis points is configured
+    #[error("Invalid fee of {fee_bps} basis points; the maximum is 10000")]
+    InvalidFeeBps { fee_bps: u16 },
+
+    /// Returned when a deposit is attempted while the admin has halted deposits
+    #[error("Deposits are disabled")]
+    DepositsDisabled {},
+
+    /// Returned when a deposit is smaller than the configured minimum
+    #[error("Deposit of {amount} is below the minimum of {min}")]
+    DepositBelowMinimum { min: Uint128, amount: Uint128 },
+
+    /// Returned when a user operation is attempted while the contract is paused
+    #[error("Contract is paused")]
+    Paused {},
+
+    /// Returned by messages that are defined but not yet implemented (currently Snapshot)
     #[error("Not implemented")]
     NotImplemented {},
     
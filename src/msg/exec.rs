@@ -1,4 +1,6 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
+use crate::state::{BlackoutWindow, GlobalSpendCap, WithdrawTimelock};
 
 /// Execute messages for the Credits Delegation contract
 ///
@@ -9,20 +11,81 @@ pub enum ExecuteMsg {
     /// Deposits native tokens into the sender's balance
     /// 
     /// The deposited amount is determined by the funds sent with the transaction.
-    /// Must include exactly one native token matching the contract's configured denom.
+    /// Must include exactly one native token in one of the contract's accepted denoms;
+    /// it is credited to the sender's balance in that denom.
     Deposit {},
-    
+
+    /// Deposits native tokens into another address's balance
+    ///
+    /// Works like Deposit, but the funds sent with the transaction are credited to
+    /// `beneficiary` instead of the sender. Useful for paymaster-style funding flows.
+    DepositFor { beneficiary: String },
+
     /// Authorizes an address to spend tokens on behalf of the sender
     /// 
     /// After authorization, the spender can use SpendFrom to use tokens from the owner's balance.
-    /// Only the token owner can authorize spenders for their account.
-    AuthorizeSpender { spender: String },
+    /// Only the token owner, or a manager the owner appointed, can authorize spenders
+    /// for their account. A manager names the account in `owner`; omitting it
+    /// authorizes for the sender's own account. Optional `terms` restrict the
+    /// authorization (e.g. an expiry height); omitting them grants an unrestricted
+    /// authorization.
+    AuthorizeSpender { spender: String, terms: Option<AuthorizationTerms>, owner: Option<String> },
+
+    /// Deposits the sent funds and authorizes a spender in a single transaction
+    ///
+    /// Funds are validated exactly as for Deposit and credited to the sender, who then
+    /// grants `spender` an unrestricted authorization. Either both happen or neither does.
+    AuthorizeWithDeposit { spender: String },
     
+    /// Funds a new sub-account and delegates spending on it in one transaction
+    ///
+    /// Moves `amount` of the primary denom from the sender's balance to
+    /// `sub_account`, then authorizes `spender` (the sender if omitted) to spend up
+    /// to `limit` from it. `limit` may not exceed `amount`, so the grant only ever
+    /// covers the seeded funds. `sub_account` must be fresh: no balance, no execute
+    /// history and no authorizations of its own, so this can never grant access to
+    /// an account someone else already uses. Either both steps happen or neither does.
+    SeedSubAccount { sub_account: String, amount: Uint128, spender: Option<String>, limit: Uint128 },
+
+    /// Adds `amount` to a spender's remaining allowance
+    ///
+    /// Creates an authorization limited to `amount` if the spender has none. An
+    /// unlimited authorization stays unlimited. Unlike re-sending AuthorizeSpender,
+    /// this never overwrites what the spender has left, avoiding set-style races.
+    IncreaseAllowance { spender: String, amount: Uint128 },
+
+    /// Subtracts `amount` from a spender's remaining allowance
+    ///
+    /// Saturates at zero, and the authorization is removed once nothing is left.
+    /// Fails if the spender has no authorization or an unlimited one.
+    DecreaseAllowance { spender: String, amount: Uint128 },
+
+    /// Converts an existing unlimited authorization into a bounded one
+    ///
+    /// Sets the remaining allowance to `limit` and, if given, the expiry height to
+    /// `expires`, keeping every other term of the authorization. Fails if the spender
+    /// has no authorization or one that is already limited (use DecreaseAllowance).
+    LimitSpender { spender: String, limit: Uint128, expires: Option<u64> },
+
+    /// Restricts which denoms an authorized spender may spend
+    ///
+    /// Replaces any previous restriction. Every denom must be accepted by the
+    /// contract; an empty list lifts the restriction so all denoms are allowed again.
+    /// Fails if the spender has no authorization. Enforced by every delegated spend.
+    SetSpenderDenoms { spender: String, denoms: Vec<String> },
+
     /// Removes spending authorization from a previously authorized address
     /// 
     /// After revocation, the spender can no longer spend tokens from the owner's balance.
-    /// Only the token owner can revoke authorizations for their account.
-    RevokeSpender { spender: String },
+    /// Only the token owner, or a manager the owner appointed, can revoke
+    /// authorizations for their account; `owner` works as in AuthorizeSpender.
+    RevokeSpender { spender: String, owner: Option<String> },
+
+    /// Removes every spending authorization granted by the sender
+    ///
+    /// Intended for owners who suspect a compromise and need to cut off all
+    /// spenders at once.
+    RevokeAllSpenders {},
     
     /// Spends tokens from an owner's account to the sender's account
     /// 
@@ -30,5 +93,306 @@ pub enum ExecuteMsg {
     /// 1. The owner themselves (self-spending)
     /// 2. An address previously authorized by the owner via AuthorizeSpender
     /// Fails if the owner has insufficient balance or if sender is unauthorized.
-    SpendFrom { owner: String, amount: u128 },
+    /// `secret` is required when the authorization carries a `secret_hash`.
+    /// `denom` selects which balance to spend and defaults to the primary denom.
+    /// A configured spend fee is debited from the owner on top of `amount`.
+    SpendFrom { owner: String, amount: Uint128, secret: Option<Binary>, denom: Option<String> },
+
+    /// Pays tokens from an owner's account out to an external recipient
+    ///
+    /// Same checks as SpendFrom, in the primary denom, but instead of crediting the
+    /// sender inside the contract, `amount` is sent to `recipient` with a bank
+    /// transfer. Authorizations protected by a `secret_hash` cannot be used.
+    /// A configured spend fee is debited from the owner on top of `amount`.
+    SpendFromTo { owner: String, recipient: String, amount: Uint128 },
+
+    /// Spends tokens from an owner's account with the owner's signature
+    ///
+    /// Intended for relayers submitting off-chain-signed spends. Behaves like
+    /// SpendFrom, but `signature` must be the owner's 64-byte secp256k1 signature,
+    /// made with the key registered through SetSigningKey, over the sha256 hash of
+    /// `{contract}:{owner}:{relayer}:{denom}:{amount}:{nonce}`. The nonce signed is
+    /// the owner's current one (see the Nonce query), which is then incremented, so
+    /// the same spend cannot be submitted twice. `nonce` may be given to fail early
+    /// with `InvalidNonce` when it is stale; `denom` defaults to the primary denom.
+    SpendFromSigned { owner: String, amount: Uint128, nonce: Option<u64>, denom: Option<String>, signature: Binary },
+
+    /// Registers the secp256k1 public key that signs the sender's SpendFromSigned
+    /// payloads, or removes it when `pubkey` is `None`
+    SetSigningKey { pubkey: Option<Binary> },
+
+    /// Spends a USD-denominated amount from an owner's account to `recipient`
+    ///
+    /// `usd_amount` is in micro-USD (10^-6 USD). It is converted into the primary denom
+    /// at the configured price oracle's current price, rounding down, and then spent
+    /// with the same checks as BatchSpendFrom. Fails with BadPrice if the price is
+    /// zero or more than 5 minutes old, and with UsdSpendsPaused while USD spends are
+    /// paused. If the oracle query itself fails and `oracle_auto_pause` is enabled,
+    /// nothing is spent and USD spends are paused; the transaction succeeds so the
+    /// pause sticks, and reports `usd_spends_paused = true`.
+    SpendUsd { owner: String, usd_amount: Uint128, recipient: String },
+
+    /// Spends tokens from an owner's account to several recipients at once
+    ///
+    /// Authorization is checked once for the sender, and the owner's balance must
+    /// cover the sum of all payouts. The batch is all-or-nothing: an invalid recipient
+    /// address, a zero payout or an insufficient balance reverts every payout.
+    /// Authorizations protected by a `secret_hash` cannot be used for batches.
+    /// Every payout is in `denom`, which defaults to the primary denom.
+    BatchSpendFrom { owner: String, payouts: Vec<(String, Uint128)>, denom: Option<String> },
+
+    /// Returns tokens from the sender's balance to an owner's balance
+    ///
+    /// The reverse of SpendFrom, e.g. after a spender over-charged. Debits `amount`
+    /// in `denom` (defaulting to the primary denom) from the sender and credits it
+    /// to `owner`. It does not restore the spender's allowance.
+    Refund { owner: String, amount: Uint128, denom: Option<String> },
+
+    /// Withdraws tokens from the sender's balance to their wallet
+    ///
+    /// Debits `amount` in `denom` (defaulting to the primary denom) and sends it to
+    /// the sender with a bank transfer. Fails if the balance is insufficient.
+    Withdraw { amount: Uint128, denom: Option<String> },
+
+    /// Requests a withdrawal above the timelock threshold
+    ///
+    /// The withdrawal becomes claimable with ClaimWithdraw once the configured
+    /// delay has passed. The balance is only debited when it is claimed. A new
+    /// request replaces the sender's previous one and restarts the delay.
+    RequestWithdraw { amount: Uint128, denom: Option<String> },
+
+    /// Pays out the sender's requested withdrawal once its delay has passed
+    ClaimWithdraw {},
+
+    /// Converts the sender's primary denom balance into the pending new denom
+    ///
+    /// Uses the ratio set by SetPendingDenom, rounding down. Each account can
+    /// convert once; fails if no new denom is pending.
+    ConvertBalance {},
+
+    /// Adds the sent native tokens to the keeper reward pool
+    ///
+    /// Must include exactly one native token in the contract's primary denom.
+    FundRewardPool {},
+
+    /// Removes up to `limit` expired authorizations and rewards the caller
+    ///
+    /// Callable by anyone. Expiries are processed in height order from an index, so
+    /// the work per call is bounded by `limit` rather than the number of
+    /// authorizations. The caller is credited the configured keeper reward for each
+    /// entry removed, and the number of entries read is bounded by what the reward
+    /// pool can pay for.
+    CleanupExpired { limit: Option<u32> },
+
+    /// Restores the allowance of up to `limit` due recurring authorizations and
+    /// rewards the caller
+    ///
+    /// Callable by anyone. Each refilled authorization gets its allowance reset to
+    /// the refill amount and is rescheduled one interval later. The caller is credited
+    /// the configured keeper reward per refill, bounded by what the reward pool can pay.
+    ProcessRefills { limit: Option<u32> },
+
+    /// Transfers the admin role to a new address
+    ///
+    /// Only the current admin can call this. The new address is validated and may
+    /// not be the contract itself.
+    UpdateAdmin { new_admin: String },
+
+    /// Bans a spender contract-wide and revokes its authorizations from all owners
+    ///
+    /// Only the admin can call this. The spender is added to the blocklist immediately,
+    /// so it can neither spend nor be re-authorized. At most `limit` authorizations are
+    /// removed per call; repeat the call until the `complete` attribute is `true`.
+    GlobalRevokeSpender { spender: String, limit: Option<u32> },
+
+    /// Revokes one owner's authorization of a spender, for incident response
+    ///
+    /// Only the admin can call this. Works like RevokeSpender issued by the owner,
+    /// and the response marks the revocation as admin-initiated for auditing.
+    AdminRevoke { owner: String, spender: String },
+
+    /// Blocks a spender contract-wide without revoking its authorizations
+    ///
+    /// Only the admin can call this. A blocked spender can neither spend nor be
+    /// authorized, even by owners that already authorized it; its authorizations
+    /// apply again once it is unblocked.
+    BlockSpender { spender: String },
+
+    /// Lifts a block set by BlockSpender or GlobalRevokeSpender
+    ///
+    /// Only the admin can call this. Authorizations revoked by GlobalRevokeSpender
+    /// are not restored.
+    UnblockSpender { spender: String },
+
+    /// Freezes an account
+    ///
+    /// Only the admin can call this. A frozen account cannot receive deposits, spend,
+    /// be spent from, withdraw, be recovered or grant new authorizations.
+    FreezeAccount { account: String },
+
+    /// Lifts a freeze set by FreezeAccount
+    ///
+    /// Only the admin can call this.
+    UnfreezeAccount { account: String },
+
+    /// Records a snapshot of the internal ledger total against the contract's
+    /// actual bank balance
+    ///
+    /// Only the admin can call this. Samples are read back with the DriftHistory
+    /// query to spot slow accounting divergence. Gas grows with the number of accounts.
+    RecordDrift {},
+
+    /// Emergency escape hatch: sends the contract's entire bank balance of every
+    /// accepted denom to `recipient`
+    ///
+    /// Only the admin can call this. Internal balances are NOT adjusted, so after a
+    /// sweep the ledger no longer matches what the contract holds. Use it carefully,
+    /// only to recover funds that would otherwise be stranded.
+    AdminSweep { recipient: String },
+
+    /// Appoints a manager who may authorize and revoke spenders for the sender
+    ///
+    /// The manager cannot spend from the sender's balance, nor authorize itself.
+    AddManager { manager: String },
+
+    /// Removes a manager previously appointed by the sender
+    RemoveManager { manager: String },
+
+    /// Designates a recovery address for the sender's balance
+    ///
+    /// Replaces any previously set recovery address. The recovery address may claim
+    /// the balance via Recover once the owner has been inactive for the configured delay.
+    SetRecovery { address: String },
+
+    /// Moves an inactive owner's balances in every accepted denom to the caller
+    ///
+    /// Only the owner's designated recovery address can call this, and only once the
+    /// owner has sent no execute message for the configured number of blocks.
+    Recover { owner: String },
+
+    /// Halts or resumes deposits without affecting any other operation
+    ///
+    /// Only the admin can call this. While disabled, Deposit, DepositFor and
+    /// AuthorizeWithDeposit are rejected; spends, withdrawals and authorization
+    /// changes keep working.
+    SetDepositsEnabled { enabled: bool },
+
+    /// Pauses or resumes SpendUsd without affecting any other operation
+    ///
+    /// Only the admin can call this. Used to resume USD spends after an oracle
+    /// outage paused them, or to pause them pre-emptively.
+    SetUsdSpendsPaused { paused: bool },
+
+    /// Replaces the schedule of time windows during which delegated spending is blocked
+    ///
+    /// Only the admin can call this. Spenders are rejected inside any window (e.g.
+    /// weekends or maintenance); owners can still spend and withdraw their own
+    /// balance. An empty list lifts every blackout.
+    SetSpendingBlackouts { windows: Vec<BlackoutWindow> },
+
+    /// Announces a new native denom that accounts may convert their balance into
+    ///
+    /// Only the admin can call this. `denom` becomes an accepted denom, and each
+    /// account may then opt in with ConvertBalance, receiving `ratio` units of
+    /// `denom` per unit of the primary denom. Setting it again replaces the pending
+    /// denom and ratio for accounts that have not converted yet.
+    SetPendingDenom { denom: String, ratio: Decimal },
+
+    /// Updates several contract parameters in one transaction
+    ///
+    /// Only the admin can call this. Only the fields set in `params` are changed,
+    /// and those named in `params.clear` are unset; the update is applied atomically.
+    UpdateParams { params: ParamUpdate },
+
+    /// Planned: records a point-in-time snapshot of every balance
+    ///
+    /// Not implemented yet; always fails with NotImplemented.
+    Snapshot {},
+}
+
+/// Contract parameters to change via UpdateParams; `None` leaves a parameter as is
+#[cw_serde]
+#[derive(Default)]
+pub struct ParamUpdate {
+    /// Reward paid per expired authorization removed or allowance refilled by a keeper
+    pub keeper_reward: Option<Uint128>,
+    /// Whether deposits may credit the admin's own balance
+    pub admin_can_deposit: Option<bool>,
+    /// Blocks of owner inactivity required before a recovery address may claim
+    pub recovery_delay_blocks: Option<u64>,
+    /// Smallest amount a single deposit may credit
+    pub min_deposit: Option<Uint128>,
+    /// Deposit fee in basis points, at most 10000
+    pub fee_bps: Option<u16>,
+    /// Delegated spend fee in basis points, at most 10000
+    pub spend_fee_bps: Option<u16>,
+    /// Price oracle contract used by SpendUsd
+    pub price_oracle: Option<String>,
+    /// Whether a failing oracle query pauses USD spends
+    pub oracle_auto_pause: Option<bool>,
+    /// Policy contract consulted on delegated spends
+    pub policy: Option<String>,
+    /// Contract-wide cap on delegated spending per epoch
+    pub global_spend_cap: Option<GlobalSpendCap>,
+    /// Delay imposed on withdrawals above a threshold
+    pub withdraw_timelock: Option<WithdrawTimelock>,
+    /// Cap on the balance an account may reach by receiving spends from other owners
+    pub max_received_per_account: Option<Uint128>,
+    /// Parameters to reset to unset; naming one that is also set here is rejected
+    pub clear: Option<Vec<ClearableParam>>,
+}
+
+/// An optional contract parameter that UpdateParams can reset to unset
+#[cw_serde]
+pub enum ClearableParam {
+    /// Stops consulting a policy contract on delegated spends
+    Policy,
+    /// Removes the price oracle; rejected while oracle auto-pause stays enabled
+    PriceOracle,
+    /// Removes the contract-wide cap on delegated spending
+    GlobalSpendCap,
+    /// Makes every withdrawal immediate again
+    WithdrawTimelock,
+    /// Removes the cap on balances reached through received spends
+    MaxReceivedPerAccount,
+}
+
+/// Optional restrictions attached to a spender authorization
+///
+/// Amount limits count raw amounts in whichever denom is spent; they are not
+/// tracked separately per denom.
+#[cw_serde]
+#[derive(Default)]
+pub struct AuthorizationTerms {
+    /// Block height at which the authorization expires; `None` never expires
+    pub expires_at_height: Option<u64>,
+    /// Maximum amount a single SpendFrom (or BatchSpendFrom in total) may move;
+    /// `None` imposes no per-transaction limit
+    pub max_per_tx: Option<Uint128>,
+    /// Opaque metadata for off-chain coordinators, at most 1024 bytes.
+    /// The contract stores it as-is and never interprets it.
+    pub metadata: Option<Binary>,
+    /// Maximum amount that may be spent within each `window_seconds` period.
+    /// Must be set together with `window_seconds`.
+    pub limit_per_window: Option<Uint128>,
+    /// Length of the rate-limit window in seconds
+    pub window_seconds: Option<u64>,
+    /// sha256 hash (32 bytes) of a secret the spender must supply with each
+    /// SpendFrom; intended for high-value delegations
+    pub secret_hash: Option<Binary>,
+    /// Total amount the spender may spend over the authorization's lifetime;
+    /// `None` is unlimited
+    pub allowance: Option<Uint128>,
+    /// Block time from which spends are allowed; together with `expires_at_height`
+    /// this bounds an active window. `None` is active immediately.
+    pub starts_at: Option<Timestamp>,
+    /// Allowance restored every `refill_interval_seconds` by keepers calling
+    /// ProcessRefills. Must be set together with `refill_interval_seconds`; the
+    /// initial allowance defaults to it.
+    pub refill_amount: Option<Uint128>,
+    /// Seconds between allowance refills, at most one year
+    pub refill_interval_seconds: Option<u64>,
+    /// Seconds that must pass after a spend before the spender may spend again;
+    /// `None` imposes no cooldown
+    pub cooldown_seconds: Option<u64>,
 }
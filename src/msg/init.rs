@@ -1,10 +1,13 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+use crate::state::{DenomPrecision, GlobalSpendCap, WithdrawTimelock};
 
 /// Message for instantiating the Credits Delegation contract
 ///
 /// This message is sent once when the contract is first deployed to initialize
 /// its state. It configures who the admin is and what token denomination is accepted.
 #[cw_serde]
+#[derive(Default)]
 pub struct InstantiateMsg {
     /// Address that will be set as the contract administrator
     /// Must be a valid bech32 address string that will be validated during instantiation
@@ -13,4 +16,91 @@ pub struct InstantiateMsg {
     /// Native token denomination that the contract will accept for deposits
     /// Example: "uatom" for Cosmos Hub atoms
     pub denom: String,
+
+    /// Display decimals of `denom`, for frontends rendering human-readable amounts
+    /// (6 means 1000000 base units display as 1). At most 18. A `denom_precisions`
+    /// entry for `denom` must use the same decimals.
+    pub decimals: u8,
+
+    /// Further native token denominations accepted for deposits alongside `denom`,
+    /// each tracked as a separate balance. Defaults to none.
+    pub additional_denoms: Option<Vec<String>>,
+
+    /// Alternative denom strings for the primary `denom`, such as the `ibc/HASH`
+    /// and path forms of the same IBC token. Deposits in an alias are credited to
+    /// the primary denom balance. Defaults to none.
+    pub denom_aliases: Option<Vec<String>>,
+
+    /// Reward paid from the reward pool for each expired authorization a keeper
+    /// removes via CleanupExpired and each allowance refilled via ProcessRefills.
+    /// Defaults to 0 (keeper work is unrewarded).
+    pub keeper_reward: Option<Uint128>,
+
+    /// Whether the admin may hold a deposited balance. When false, deposits
+    /// crediting the admin are rejected so fee income is never mixed with
+    /// personal funds. Defaults to true.
+    pub admin_can_deposit: Option<bool>,
+
+    /// Most spenders a single owner may have authorized at once, bounding state growth
+    /// and the gas used by RevokeAllSpenders. Defaults to 50.
+    pub max_spenders_per_owner: Option<u32>,
+
+    /// Number of blocks an owner must be inactive before their recovery address
+    /// may claim their balance via Recover. Defaults to 100800 (about a week of
+    /// 6 second blocks).
+    pub recovery_delay_blocks: Option<u64>,
+
+    /// Whether deposits carrying more than one coin are rejected. When false, only
+    /// the coin in the primary `denom` is credited and any other coins are ignored
+    /// (they stay in the contract's bank balance untracked). Defaults to true.
+    pub strict_deposit: Option<bool>,
+
+    /// Whether SpendFrom keeps addresses and amounts out of transaction logs. When
+    /// true its response carries only the `action` and an opaque `ref` (a sha256 of
+    /// the spend's details) instead of the usual event and attributes. Defaults to false.
+    pub minimal_events: Option<bool>,
+
+    /// Smallest amount a single deposit may credit, to keep dust accounts from
+    /// bloating state on chains with cheap gas. Defaults to 0 (no minimum).
+    pub min_deposit: Option<Uint128>,
+
+    /// Most an account may hold in a denom after receiving tokens from another account
+    /// through SpendFrom, BatchSpendFrom, Refund or SeedSubAccount, to stop a single
+    /// agent accumulating credits. Deposits are not capped. Defaults to none (uncapped).
+    pub max_received_per_account: Option<Uint128>,
+
+    /// Fee charged on every deposit in basis points (250 = 2.5%), credited to the
+    /// admin's balance in the deposited denom. At most 10000. Defaults to 0.
+    pub fee_bps: Option<u16>,
+
+    /// Fee charged on every delegated spend in basis points, debited from the
+    /// owner on top of the spent amount and credited to the admin. At most 10000.
+    /// Defaults to 0.
+    pub spend_fee_bps: Option<u16>,
+
+    /// Price oracle contract answering `OracleQueryMsg::Price`, used by SpendUsd to
+    /// convert USD amounts into the primary denom. Defaults to none (SpendUsd disabled).
+    pub price_oracle: Option<String>,
+
+    /// Precision spend amounts may carry per accepted denom; SpendFrom rejects
+    /// amounts finer than it. Denoms not listed accept any amount. Defaults to none.
+    pub denom_precisions: Option<Vec<DenomPrecision>>,
+
+    /// Contract-wide cap on the total moved by delegated spends per epoch, for risk
+    /// control. Defaults to none (uncapped).
+    pub global_spend_cap: Option<GlobalSpendCap>,
+
+    /// Withdrawals above `threshold` must be requested and can only be claimed
+    /// `delay_seconds` later, to limit the damage of a compromised key. Defaults to
+    /// none (every withdrawal is immediate).
+    pub withdraw_timelock: Option<WithdrawTimelock>,
+
+    /// Policy contract answering `PolicyQueryMsg::CheckSpend`, consulted on every
+    /// delegated spend after the contract's own checks pass. It can deny a spend
+    /// but never allow one the contract would reject. Defaults to none.
+    pub policy: Option<String>,
+
+    /// Whether a SpendUsd whose oracle query fails pauses further USD spends until
+    /// the admin resumes them, so an oracle outage fails safe. Defaults to false.
+    pub oracle_auto_pause: Option<bool>,
 }
@@ -0,0 +1,16 @@
+use cosmwasm_schema::cw_serde;
+
+/// Migrate messages for the Credits Delegation contract
+///
+/// Sent with a code migration by the contract's chain-level admin. The same code
+/// can be migrated to repeatedly, which is how bounded backfills are continued.
+#[cw_serde]
+pub enum MigrateMsg {
+    /// Backfills the spender-to-owner reverse index and the expiry queue from the
+    /// authorization map
+    ///
+    /// Scans at most `limit` authorizations (default 30, max 100), resuming where
+    /// the previous call stopped, and writes any missing reverse or expiry entries.
+    /// Repeat the migration until the `complete` attribute is `true`.
+    RebuildReverseIndex { limit: Option<u32> },
+}
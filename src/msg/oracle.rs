@@ -0,0 +1,22 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Decimal, Timestamp};
+
+/// Query messages the contract sends to its configured price oracle
+///
+/// Any oracle contract answering this interface can back SpendUsd.
+#[cw_serde]
+pub enum OracleQueryMsg {
+    /// Retrieves the current USD price of `denom`
+    ///
+    /// Returns a `PriceResponse`.
+    Price { denom: String },
+}
+
+/// Response expected from the oracle's Price query
+#[cw_serde]
+pub struct PriceResponse {
+    /// Micro-USD (10^-6 USD) per base unit of the denom
+    pub price: Decimal,
+    /// Block time at which the price was last updated
+    pub updated_at: Timestamp,
+}
@@ -0,0 +1,28 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Uint128;
+
+/// Query messages the contract sends to its configured spend policy contract
+///
+/// Any contract answering this interface can act as a shared policy for
+/// delegated spends.
+#[cw_serde]
+pub enum PolicyQueryMsg {
+    /// Asks whether a delegated spend may go ahead
+    ///
+    /// Returns a `PolicyResponse`.
+    CheckSpend {
+        owner: String,
+        spender: String,
+        denom: String,
+        amount: Uint128,
+    },
+}
+
+/// Response expected from the policy's CheckSpend query
+#[cw_serde]
+pub struct PolicyResponse {
+    /// Whether the spend is allowed
+    pub allowed: bool,
+    /// Explanation reported to the spender when the spend is denied
+    pub reason: Option<String>,
+}
@@ -1,4 +1,6 @@
-use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Timestamp, Uint128};
+use crate::state::{BlackoutWindow, DenomConversion, DepositRecord, DenomPrecision, DriftSample, GlobalSpendCap, WithdrawTimelock, LedgerEntry, RecurringRefill};
 
 /// Query messages for the Credits Delegation contract
 ///
@@ -6,17 +8,659 @@ use cosmwasm_schema::cw_serde;
 /// without modifying its state. They provide read-only access to current
 /// balances and authorization statuses.
 #[cw_serde]
+#[derive(QueryResponses)]
 pub enum QueryMsg {
     /// Retrieves the token balance for a given address
     /// 
-    /// Returns a u128 value representing the current balance.
-    /// If the address has no recorded balance, returns 0.
-    Balance { owner: String },
+    /// Returns a Uint128 value representing the current balance in `denom`, which
+    /// defaults to the primary denom. If the address has no recorded balance, returns 0.
+    #[returns(Uint128)]
+    Balance { owner: String, denom: Option<String> },
+
+    /// Retrieves the primary denom balance stored under `owner` without validating it
+    ///
+    /// Returns a Uint128 value, 0 if nothing is stored under the string. Meant for
+    /// testing on chains with nonstandard address formats; use Balance otherwise.
+    #[returns(Uint128)]
+    BalanceRaw { owner: String },
+
+    /// Breaks an owner's primary denom balance down into what the owner can move now
+    /// and what is locked
+    ///
+    /// Returns an `OwnerAvailableResponse`. A frozen owner can move nothing.
+    #[returns(OwnerAvailableResponse)]
+    OwnerAvailable { owner: String },
+
+    /// Retrieves the token balance for a given address along with its denom
+    ///
+    /// Like Balance, but returns a `BalanceResponse` naming the owner and the denom
+    /// the amount is in, so the value cannot be misread as another token.
+    #[returns(BalanceResponse)]
+    BalanceV2 { owner: String, denom: Option<String> },
+
+    /// Retrieves the balances of several addresses at once
+    ///
+    /// Returns `Vec<BalanceEntry>` in the same order as `owners`, with 0 for addresses
+    /// that hold nothing. Every address is validated and at most 50 are accepted per
+    /// call. `denom` defaults to the primary denom.
+    #[returns(Vec<BalanceEntry>)]
+    BalancesBatch { owners: Vec<String>, denom: Option<String> },
     
     /// Checks if a spender is authorized to spend on behalf of an owner
     /// 
     /// Returns a boolean value:
     /// - true if the spender is authorized by the owner
     /// - false if no authorization exists
+    #[returns(bool)]
     IsAuthorized { owner: String, spender: String },
+
+    /// Checks if a spender is authorized to spend on behalf of an owner
+    ///
+    /// Like IsAuthorized, but returns an `IsAuthorizedResponse` naming the pair.
+    #[returns(IsAuthorizedResponse)]
+    IsAuthorizedV2 { owner: String, spender: String },
+
+    /// Retrieves every term of an authorization
+    ///
+    /// Returns `Option<AuthorizationDetails>`, null if the owner has not authorized
+    /// the spender. Expired authorizations that have not been cleaned up yet are
+    /// still returned, with `active` set to false.
+    #[returns(Option<AuthorizationDetails>)]
+    Authorization { owner: String, spender: String },
+
+    /// Retrieves every term of an authorization
+    ///
+    /// Like Authorization, but returns an `AuthorizationResponse` whose
+    /// `authorization` field is null when none exists.
+    #[returns(AuthorizationResponse)]
+    AuthorizationV2 { owner: String, spender: String },
+
+    /// Checks the authorization status of several owner/spender pairs at once
+    ///
+    /// Returns `Vec<AuthStatus>` in the same order as `pairs`, each reporting what
+    /// IsAuthorized and AgentMetadata would for that pair. Every address is validated
+    /// and at most 30 pairs are accepted per call.
+    #[returns(Vec<AuthStatus>)]
+    BatchAuthStatus { pairs: Vec<(String, String)> },
+
+    /// Retrieves the contract configuration
+    ///
+    /// Returns a `ConfigResponse` with the admin, accepted denom and contract parameters.
+    #[returns(ConfigResponse)]
+    Config {},
+
+    /// Retrieves the total a spender has spent across all owners
+    ///
+    /// Returns a Uint128 value summing every delegated spend made by the spender.
+    /// Spends from the spender's own balance are not counted.
+    #[returns(Uint128)]
+    AgentTotalSpent { spender: String },
+
+    /// Retrieves the total spend fees an owner has paid
+    ///
+    /// Returns a Uint128 value summing the fees charged on delegated spends from the
+    /// owner's balance, across all denoms. Returns 0 if none were charged.
+    #[returns(Uint128)]
+    FeesPaidBy { owner: String },
+
+    /// Retrieves the opaque metadata attached to an authorization
+    ///
+    /// Returns the metadata as Binary, or null if the authorization does not exist
+    /// or carries no metadata.
+    #[returns(Option<Binary>)]
+    AgentMetadata { owner: String, spender: String },
+
+    /// Retrieves the opaque metadata attached to an authorization
+    ///
+    /// Like AgentMetadata, but returns an `AgentMetadataResponse`.
+    #[returns(AgentMetadataResponse)]
+    AgentMetadataV2 { owner: String, spender: String },
+
+    /// Counts accounts by balance bucket
+    ///
+    /// `buckets` are strictly ascending boundaries; an account with balance `b` falls in
+    /// bucket `i` where `buckets[i-1] <= b < buckets[i]`, so e.g. `[100, 1000]` yields
+    /// counts for 0-99, 100-999 and 1000+. At most `limit` accounts (default 100, max 500)
+    /// are scanned per call; page through with `start_after` and sum the counts to cover
+    /// every account. Gas grows linearly with the number of accounts scanned.
+    /// Balances in `denom` are counted; it defaults to the primary denom.
+    #[returns(BalanceDistributionResponse)]
+    BalanceDistribution {
+        denom: Option<String>,
+        buckets: Vec<Uint128>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Counts balance entries greater than zero
+    ///
+    /// Returns a u64 value read from a counter maintained on every balance change,
+    /// so the cost is constant. An account holding several denoms counts once per denom.
+    #[returns(u64)]
+    ActiveAccountCount {},
+
+    /// Returns the soonest time delegated spending is permitted
+    ///
+    /// Returns a Timestamp: the current block time outside any blackout window,
+    /// otherwise the end of the blackout (following back-to-back or overlapping windows).
+    #[returns(Timestamp)]
+    NextSpendWindow {},
+
+    /// Returns the receipt of a deposit by its `deposit_id`
+    ///
+    /// Returns an `Option<DepositRecord>`, none for an unknown id.
+    #[returns(Option<DepositRecord>)]
+    Deposit { id: u64 },
+
+    /// Returns the receipt of a deposit by its `deposit_id`
+    ///
+    /// Like Deposit, but returns a `DepositResponse`.
+    #[returns(DepositResponse)]
+    DepositV2 { id: u64 },
+
+    /// Counts authorizations across all owners
+    ///
+    /// Returns a u64 value read from a counter maintained on every authorize and
+    /// revoke, so the cost is constant. Expired authorizations count until removed.
+    #[returns(u64)]
+    TotalAuthorizations {},
+
+    /// Returns lifetime totals deposited and spent in the primary denom
+    ///
+    /// Returns a StatsResponse read from counters maintained by deposits and
+    /// delegated SpendFrom calls.
+    #[returns(StatsResponse)]
+    Stats {},
+
+    /// Lists the contract-wide activity ledger in ascending id order
+    ///
+    /// Every deposit, authorization, revocation or expiry, spend, withdrawal, recovery,
+    /// reward pool funding and keeper reward appends an entry. Returns a
+    /// `LedgerResponse`; pass the last seen id as `start_after` to page through,
+    /// `limit` defaults to 10 and is capped at 30.
+    #[returns(LedgerResponse)]
+    Ledger { start_after: Option<u64>, limit: Option<u32> },
+
+    /// Checks that the internal ledger matches what the contract actually holds
+    ///
+    /// Returns an `InvariantResponse` comparing the sum of all balances (plus the
+    /// reward pool) in the primary denom against the contract's bank balance. Gas
+    /// grows linearly with the number of accounts.
+    #[returns(InvariantResponse)]
+    InvariantCheck {},
+
+    /// Checks that the authorization map and its spender-to-owner reverse index agree
+    ///
+    /// Both maps are walked together as one sequence of address pairs ordered by
+    /// key, at most `limit` pairs (default 10, max 30) per call. Returns an
+    /// `IndexIntegrityResponse` listing every pair present on only one side; pass
+    /// its `next_start_after` back to continue until it is null.
+    #[returns(IndexIntegrityResponse)]
+    IndexIntegrity { start_after: Option<(String, String)>, limit: Option<u32> },
+
+    /// Lists recorded drift samples in ascending id order
+    ///
+    /// Returns a `DriftHistoryResponse`. Pass the last seen id as `start_after` to page
+    /// through the history; `limit` defaults to 10 and is capped at 30.
+    #[returns(DriftHistoryResponse)]
+    DriftHistory { start_after: Option<u64>, limit: Option<u32> },
+
+    /// Returns how much a spender can spend from an owner right now
+    ///
+    /// Returns `Option<Uint128>`: the smaller of the remaining total allowance and the
+    /// room left in the current rate-limit window, with an elapsed window treated as
+    /// refilled. `None` means unlimited; a missing, expired or not yet started
+    /// authorization yields 0.
+    #[returns(Option<Uint128>)]
+    EffectiveAllowance { owner: String, spender: String },
+
+    /// Returns how much a spender can spend from an owner right now
+    ///
+    /// Like EffectiveAllowance, but returns an `EffectiveAllowanceResponse`.
+    #[returns(EffectiveAllowanceResponse)]
+    EffectiveAllowanceV2 { owner: String, spender: String },
+
+    /// Values what a spender can spend from an owner right now in USD
+    ///
+    /// Returns a Uint128 micro-USD value: the owner's primary denom balance, capped by
+    /// the spender's effective allowance (see EffectiveAllowance), converted at the
+    /// price oracle's current price and rounded down. Spend fees are not deducted.
+    /// Fails if no oracle is configured or its price is zero or stale.
+    #[returns(Uint128)]
+    SpendableUsd { owner: String, spender: String },
+
+    /// Checks whether a SpendFrom of `amount` in the primary denom would succeed now
+    ///
+    /// Returns a `CanSpendResponse`. Runs the same checks as SpendFrom (pause,
+    /// precision, freezes, authorization, expiry, caps including the spender's
+    /// receive cap, allowance, rate limit, cooldown, policy and the owner's balance
+    /// including any spend fee) without changing state. A spend needing a secret is
+    /// reported as not allowed, since no secret is supplied.
+    #[returns(CanSpendResponse)]
+    CanSpend { owner: String, spender: String, amount: Uint128 },
+
+    /// Checks whether a BatchSpendFrom of `payouts` in the primary denom would succeed now
+    ///
+    /// Returns a `SimulateBatchSpendResponse`. Runs the same checks as
+    /// BatchSpendFrom sent by `spender` (recipient addresses, payout precision,
+    /// authorization against the total, receive caps and the owner's balance
+    /// including any spend fee) without changing state.
+    #[returns(SimulateBatchSpendResponse)]
+    SimulateBatchSpend { owner: String, spender: String, payouts: Vec<(String, Uint128)> },
+
+    /// Retrieves the nonce the owner's signature on their next SpendFromSigned must cover
+    ///
+    /// Returns a u64 value, 0 if the owner has never had a signed spend.
+    #[returns(u64)]
+    Nonce { owner: String },
+
+    /// Retrieves the nonce the owner's signature on their next SpendFromSigned must cover
+    ///
+    /// Like Nonce, but returns a `NonceResponse` naming the owner.
+    #[returns(NonceResponse)]
+    NonceV2 { owner: String },
+
+    /// Retrieves the block height of an address's most recent execute message
+    ///
+    /// Returns `Option<u64>`, null if the address has never executed. Used to judge
+    /// whether an owner is inactive for recovery purposes.
+    #[returns(Option<u64>)]
+    LastActivity { owner: String },
+
+    /// Retrieves the block height of an address's most recent execute message
+    ///
+    /// Like LastActivity, but returns a `LastActivityResponse`.
+    #[returns(LastActivityResponse)]
+    LastActivityV2 { owner: String },
+
+    /// Retrieves the window in which an authorization can be used
+    ///
+    /// Returns an `ActiveWindowResponse` evaluated at the current block. A missing
+    /// authorization is reported as never active.
+    #[returns(ActiveWindowResponse)]
+    ActiveWindow { owner: String, spender: String },
+
+    /// Retrieves the remaining allowance of one spender
+    ///
+    /// Returns an `AllowanceInfo`; the amount is 0 if no authorization exists.
+    #[returns(AllowanceInfo)]
+    Allowance { owner: String, spender: String },
+
+    /// Lists every spender an owner has authorized, with their remaining allowance
+    ///
+    /// Returns `Vec<AllowanceInfo>` ordered by spender address. Pass the last seen
+    /// spender as `start_after` to page through; `limit` defaults to 10 and is capped at 30.
+    #[returns(Vec<AllowanceInfo>)]
+    Allowances { owner: String, start_after: Option<String>, limit: Option<u32> },
+
+    /// Lists the owners that have authorized a spender
+    ///
+    /// Returns `Vec<String>` of owner addresses in ascending order. Expired
+    /// authorizations are listed until CleanupExpired removes them. Pass the last seen
+    /// owner as `start_after` to page through; `limit` defaults to 10 and is capped at 30.
+    #[returns(Vec<String>)]
+    OwnersForSpender { spender: String, start_after: Option<String>, limit: Option<u32> },
+
+    /// Lists the owners that have authorized a spender
+    ///
+    /// Like OwnersForSpender, but returns an `OwnersForSpenderResponse`.
+    #[returns(OwnersForSpenderResponse)]
+    OwnersForSpenderV2 { spender: String, start_after: Option<String>, limit: Option<u32> },
+
+    /// Lists the managers an owner has appointed
+    ///
+    /// Returns `Vec<String>` of manager addresses in ascending order. Pass the last
+    /// seen manager as `start_after` to page through; `limit` defaults to 10 and is
+    /// capped at 30.
+    #[returns(Vec<String>)]
+    Managers { owner: String, start_after: Option<String>, limit: Option<u32> },
+
+    /// Lists the managers an owner has appointed
+    ///
+    /// Like Managers, but returns a `ManagersResponse`.
+    #[returns(ManagersResponse)]
+    ManagersV2 { owner: String, start_after: Option<String>, limit: Option<u32> },
+
+    /// Lists accounts that still hold the primary denom and have not converted it
+    /// into the pending new denom
+    ///
+    /// Returns `Vec<BalanceEntry>` in ascending address order, each with the
+    /// account's unconverted balance; empty when no conversion is pending. Pass the
+    /// last seen address as `start_after` to page through; `limit` defaults to 10
+    /// and is capped at 30.
+    #[returns(Vec<BalanceEntry>)]
+    UnconvertedAccounts { start_after: Option<String>, limit: Option<u32> },
+
+    /// Returns when the owner's requested withdrawal becomes claimable
+    ///
+    /// Returns an `Option<Timestamp>`, none when no withdrawal is requested.
+    #[returns(Option<Timestamp>)]
+    WithdrawReadyAt { owner: String },
+
+    /// Returns when the owner's requested withdrawal becomes claimable
+    ///
+    /// Like WithdrawReadyAt, but returns a `WithdrawReadyAtResponse`.
+    #[returns(WithdrawReadyAtResponse)]
+    WithdrawReadyAtV2 { owner: String },
+}
+
+/// Response for the Config query
+#[cw_serde]
+pub struct ConfigResponse {
+    /// Current contract admin
+    pub admin: String,
+    /// Primary native token denomination
+    pub denom: String,
+    /// Display decimals of the primary denom
+    pub decimals: u8,
+    /// Every native token denomination accepted for deposits, the primary denom first
+    pub accepted_denoms: Vec<String>,
+    /// Alternative spellings of the primary denom, credited to it on deposit
+    pub denom_aliases: Vec<String>,
+    /// Spend precision configured per denom
+    pub denom_precisions: Vec<DenomPrecision>,
+    /// Reward paid per expired authorization cleaned up or allowance refilled by a keeper
+    pub keeper_reward: Uint128,
+    /// Whether deposits may credit the admin's own balance
+    pub admin_can_deposit: bool,
+    /// Blocks of owner inactivity required before a recovery address may claim
+    pub recovery_delay_blocks: u64,
+    /// Most spenders a single owner may have authorized at once
+    pub max_spenders_per_owner: u32,
+    /// Whether deposits carrying more than one coin are rejected
+    pub strict_deposit: bool,
+    /// Smallest amount a single deposit may credit
+    pub min_deposit: Uint128,
+    /// Whether deposits are currently accepted
+    pub deposits_enabled: bool,
+    /// Fee charged on deposits in basis points, credited to the admin
+    pub fee_bps: u16,
+    /// Fee charged on delegated spends in basis points, paid by the owner to the admin
+    pub spend_fee_bps: u16,
+    /// Price oracle contract used by SpendUsd, if configured
+    pub price_oracle: Option<String>,
+    /// Whether a failing oracle query pauses USD spends
+    pub oracle_auto_pause: bool,
+    /// Policy contract consulted on delegated spends, if configured
+    pub policy: Option<String>,
+    /// New denom accounts may convert their balance into, if one is pending
+    pub pending_denom: Option<DenomConversion>,
+    /// Most an account may hold after receiving a spend, if capped
+    pub max_received_per_account: Option<Uint128>,
+    /// Time windows during which delegated spending is blocked
+    pub spending_blackouts: Vec<BlackoutWindow>,
+    /// Contract-wide cap on delegated spending per epoch, if configured
+    pub global_spend_cap: Option<GlobalSpendCap>,
+    /// Delay imposed on large withdrawals, if configured
+    pub withdraw_timelock: Option<WithdrawTimelock>,
+    /// Whether SpendUsd is currently paused
+    pub usd_spends_paused: bool,
+    /// Whether the contract is paused by governance
+    pub paused: bool,
+    /// Whether SpendFrom responses carry only a hashed reference
+    pub minimal_events: bool,
+}
+
+/// Response for the BalanceV2 query
+#[cw_serde]
+pub struct BalanceResponse {
+    /// Queried address
+    pub owner: String,
+    /// Denom the amount is in
+    pub denom: String,
+    /// Balance held in `denom`
+    pub amount: Uint128,
+}
+
+/// Response for the IsAuthorizedV2 query
+#[cw_serde]
+pub struct IsAuthorizedResponse {
+    /// Owner of the checked pair
+    pub owner: String,
+    /// Spender of the checked pair
+    pub spender: String,
+    /// Whether the spender holds an unexpired authorization from the owner
+    pub authorized: bool,
+}
+
+/// Response for the AuthorizationV2 query
+#[cw_serde]
+pub struct AuthorizationResponse {
+    /// Terms of the authorization, or `None` if the owner has not authorized the spender
+    pub authorization: Option<AuthorizationDetails>,
+}
+
+/// Response for the AgentMetadataV2 query
+#[cw_serde]
+pub struct AgentMetadataResponse {
+    /// Metadata attached to the authorization, or `None` if there is none
+    pub metadata: Option<Binary>,
+}
+
+/// Response for the DepositV2 query
+#[cw_serde]
+pub struct DepositResponse {
+    /// Receipt of the deposit, or `None` for an unknown id
+    pub deposit: Option<DepositRecord>,
+}
+
+/// Response for the EffectiveAllowanceV2 query
+#[cw_serde]
+pub struct EffectiveAllowanceResponse {
+    /// Amount the spender can spend right now; `None` is unlimited
+    pub allowance: Option<Uint128>,
+}
+
+/// Response for the NonceV2 query
+#[cw_serde]
+pub struct NonceResponse {
+    /// Queried owner
+    pub owner: String,
+    /// Nonce the owner's next signed spend must carry
+    pub nonce: u64,
+}
+
+/// Response for the LastActivityV2 query
+#[cw_serde]
+pub struct LastActivityResponse {
+    /// Block height of the latest execute message, or `None` if the address never executed
+    pub height: Option<u64>,
+}
+
+/// Response for the OwnersForSpenderV2 query
+#[cw_serde]
+pub struct OwnersForSpenderResponse {
+    /// Owner addresses in ascending order
+    pub owners: Vec<String>,
+}
+
+/// Response for the ManagersV2 query
+#[cw_serde]
+pub struct ManagersResponse {
+    /// Manager addresses in ascending order
+    pub managers: Vec<String>,
+}
+
+/// Response for the WithdrawReadyAtV2 query
+#[cw_serde]
+pub struct WithdrawReadyAtResponse {
+    /// Time the requested withdrawal becomes claimable, or `None` if none is requested
+    pub ready_at: Option<Timestamp>,
+}
+
+/// Response for the CanSpend query
+#[cw_serde]
+pub struct CanSpendResponse {
+    /// Whether the spend would succeed
+    pub allowed: bool,
+    /// Error the spend would fail with, when not allowed
+    pub reason: Option<String>,
+}
+
+/// Response for the SimulateBatchSpend query
+#[cw_serde]
+pub struct SimulateBatchSpendResponse {
+    /// Whether the batch would succeed
+    pub would_succeed: bool,
+    /// Sum of the payouts, excluding any spend fee
+    pub total: Uint128,
+    /// Error the batch would fail with, when it would not succeed
+    pub reason: Option<String>,
+}
+
+/// Response for the OwnerAvailable query
+///
+/// `spendable_by_owner` is `total` minus every locked component, or 0 while the
+/// owner is frozen.
+#[cw_serde]
+pub struct OwnerAvailableResponse {
+    /// Whole balance in the primary denom
+    pub total: Uint128,
+    /// Amount the owner can spend or withdraw right now
+    pub spendable_by_owner: Uint128,
+    /// Amount held back as a reserve
+    pub locked_reserve: Uint128,
+    /// Amount committed to spends or withdrawals that have not completed
+    pub locked_pending: Uint128,
+    /// Amount set aside as savings
+    pub savings: Uint128,
+}
+
+/// One entry of the BalancesBatch query
+#[cw_serde]
+pub struct BalanceEntry {
+    /// Queried address
+    pub owner: String,
+    /// Balance held in the queried denom
+    pub balance: Uint128,
+}
+
+/// One entry of the BatchAuthStatus query
+#[cw_serde]
+pub struct AuthStatus {
+    /// Owner of the checked pair
+    pub owner: String,
+    /// Spender of the checked pair
+    pub spender: String,
+    /// Whether the spender holds an unexpired authorization from the owner
+    pub authorized: bool,
+    /// Opaque metadata attached to the authorization, if any
+    pub metadata: Option<Binary>,
+}
+
+/// Response for the Authorization query
+#[cw_serde]
+pub struct AuthorizationDetails {
+    /// Owner who granted the authorization
+    pub owner: String,
+    /// Authorized spender
+    pub spender: String,
+    /// Whether the current block is inside the authorization's active window
+    pub active: bool,
+    /// Block height at which the authorization expires, if any
+    pub expires_at_height: Option<u64>,
+    /// Block time from which the authorization can be used, if delayed
+    pub starts_at: Option<Timestamp>,
+    /// Remaining lifetime allowance; `None` is unlimited
+    pub allowance: Option<Uint128>,
+    /// Maximum amount a single spend may move, if capped
+    pub max_per_tx: Option<Uint128>,
+    /// Maximum amount spendable per rate-limit window, if rate limited
+    pub limit_per_window: Option<Uint128>,
+    /// Length of the rate-limit window in seconds
+    pub window_seconds: Option<u64>,
+    /// sha256 hash of the secret required by SpendFrom, if any
+    pub secret_hash: Option<Binary>,
+    /// Opaque metadata attached by the owner
+    pub metadata: Option<Binary>,
+    /// Recurring refill schedule, if any
+    pub refill: Option<RecurringRefill>,
+    /// Seconds required between consecutive spends, if throttled
+    pub cooldown_seconds: Option<u64>,
+    /// Denoms the spender may spend; empty allows every accepted denom
+    pub denoms: Vec<String>,
+}
+
+/// One entry of the Allowances query
+#[cw_serde]
+pub struct AllowanceInfo {
+    /// Authorized spender
+    pub spender: String,
+    /// Remaining lifetime allowance; `Uint128::MAX` when the authorization is unlimited
+    pub amount: Uint128,
+    /// Block height at which the authorization expires, if any
+    pub expires_at_height: Option<u64>,
+}
+
+/// Response for the ActiveWindow query
+#[cw_serde]
+pub struct ActiveWindowResponse {
+    /// Block time from which the authorization can be used, if delayed
+    pub starts_at: Option<Timestamp>,
+    /// Block height at which the authorization expires, if any
+    pub expires_at: Option<u64>,
+    /// Whether the authorization exists and the current block is inside its window
+    pub currently_active: bool,
+}
+
+/// Response for the BalanceDistribution query
+#[cw_serde]
+pub struct BalanceDistributionResponse {
+    /// Number of accounts in each bucket; one more entry than there are boundaries
+    pub counts: Vec<u64>,
+    /// Last account scanned, to pass as `start_after` for the next page;
+    /// `None` once every account has been scanned
+    pub next_start_after: Option<String>,
+}
+
+/// Response for the Stats query
+#[cw_serde]
+pub struct StatsResponse {
+    /// Total ever deposited in the primary denom, before deposit fees
+    pub total_deposited: Uint128,
+    /// Total ever moved by delegated spends, batches and payouts in the primary denom,
+    /// excluding fees
+    pub total_spent: Uint128,
+}
+
+/// Response for the InvariantCheck query
+#[cw_serde]
+pub struct InvariantResponse {
+    /// Sum of every internal balance plus the reward pool, in the primary denom
+    pub ledger_total: Uint128,
+    /// Contract's actual bank balance in the primary denom
+    pub escrow_balance: Uint128,
+    /// Whether the two are equal; a surplus from direct transfers also reports false
+    pub consistent: bool,
+}
+
+/// Response for the IndexIntegrity query
+#[cw_serde]
+pub struct IndexIntegrityResponse {
+    /// Pairs found on only one side of the index within the scanned slice
+    pub mismatches: Vec<IndexMismatch>,
+    /// Last key scanned, to pass as `start_after` for the next page;
+    /// `None` once both maps have been fully scanned
+    pub next_start_after: Option<(String, String)>,
+}
+
+/// An authorization index entry without its counterpart
+#[cw_serde]
+pub enum IndexMismatch {
+    /// An authorization with no reverse index entry
+    MissingReverse { owner: String, spender: String },
+    /// A reverse index entry with no authorization behind it
+    MissingPrimary { owner: String, spender: String },
+}
+
+/// Response for the Ledger query
+#[cw_serde]
+pub struct LedgerResponse {
+    pub entries: Vec<LedgerEntry>,
+}
+
+/// Response for the DriftHistory query
+#[cw_serde]
+pub struct DriftHistoryResponse {
+    pub samples: Vec<DriftSample>,
 }
@@ -0,0 +1,17 @@
+use cosmwasm_schema::cw_serde;
+
+/// Sudo messages for the Credits Delegation contract
+///
+/// These messages can only be issued by the chain itself (e.g. through a
+/// governance proposal), never by an account, so they bypass the admin check.
+#[cw_serde]
+pub enum SudoMsg {
+    /// Pauses the contract
+    ///
+    /// While paused, deposits, authorization changes, spends and recovery are
+    /// rejected; admin maintenance messages keep working.
+    ForcePause {},
+
+    /// Lifts a pause set by ForcePause
+    ForceUnpause {},
+}
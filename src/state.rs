@@ -1,17 +1,422 @@
-use cosmwasm_std::Addr;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, BlockInfo, Decimal, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
 
 /// Contract admin address with special privileges (if needed for future extensions)
 pub const ADMIN: Item<Addr> = Item::new("admin");
 
-/// Native token denomination that this contract accepts for deposits
+/// Primary native token denomination
+/// Used when a message or query does not name a denom, and the denom keeper
+/// rewards and drift samples are tracked in.
 pub const DENOM: Item<String> = Item::new("denom");
 
-/// Maps user addresses to their token balances
-/// Key: user address, Value: token balance as u128
-pub const BALANCES: Map<&Addr, u128> = Map::new("balances");
+/// Display decimals of the primary denom, for frontends; never used in accounting
+pub const DECIMALS: Item<u8> = Item::new("decimals");
+
+/// Every native token denomination accepted for deposits, the primary denom first
+pub const ACCEPTED_DENOMS: Item<Vec<String>> = Item::new("accepted_denoms");
+
+/// Alternative spellings of the primary denom (e.g. an IBC path form), credited
+/// to the primary balance when deposited
+pub const DENOM_ALIASES: Item<Vec<String>> = Item::new("denom_aliases");
+
+/// Precision a denom's spend amounts may carry
+///
+/// A denom with `decimals` 6 and `precision` 2 counts base units in millionths of a
+/// display unit but only accepts spends in whole hundredths, i.e. multiples of 10^4.
+#[cw_serde]
+pub struct DenomPrecision {
+    /// Denom the precision applies to
+    pub denom: String,
+    /// Decimal places between the denom's base unit and its display unit
+    pub decimals: u32,
+    /// Decimal places of the display unit a spend amount may use; at most `decimals`
+    pub precision: u32,
+}
+
+/// Configured spend precision per denom; denoms not listed accept any amount
+pub const DENOM_PRECISIONS: Item<Vec<DenomPrecision>> = Item::new("denom_precisions");
+
+/// New native denom that primary denom balances can be converted into
+#[cw_serde]
+pub struct DenomConversion {
+    /// Denom balances are converted into
+    pub denom: String,
+    /// Units of `denom` credited per unit of the primary denom
+    pub ratio: Decimal,
+}
+
+/// Denom rotation announced by the admin via SetPendingDenom; absent when none is pending
+pub const PENDING_DENOM: Item<DenomConversion> = Item::new("pending_denom");
+
+/// Accounts that have converted their balance via ConvertBalance
+/// Key: account address, Value: unit
+pub const CONVERTED: Map<&Addr, ()> = Map::new("converted");
+
+/// Policy contract consulted on every delegated spend; it can only deny spends the
+/// contract would otherwise allow. Unset skips the check.
+pub const POLICY: Item<Addr> = Item::new("policy");
+
+/// Price oracle contract used to convert USD amounts for SpendUsd; unset disables it
+pub const PRICE_ORACLE: Item<Addr> = Item::new("price_oracle");
+
+/// Whether a failing oracle query pauses USD spends instead of just erroring
+pub const ORACLE_AUTO_PAUSE: Item<bool> = Item::new("oracle_auto_pause");
+
+/// Whether SpendUsd is paused, independently of the global pause
+pub const USD_SPENDS_PAUSED: Item<bool> = Item::new("usd_spends_paused");
+
+/// Whether deposits are accepted; the admin can halt them without pausing the contract
+pub const DEPOSITS_ENABLED: Item<bool> = Item::new("deposits_enabled");
+
+/// Fee charged on deposits in basis points, credited to the admin's balance
+pub const FEE_BPS: Item<u16> = Item::new("fee_bps");
+
+/// Fee charged on delegated spends in basis points, paid by the owner on top of
+/// the spent amount and credited to the admin's balance
+pub const SPEND_FEE_BPS: Item<u16> = Item::new("spend_fee_bps");
+
+/// Basis points in a whole; `FEE_BPS` and `SPEND_FEE_BPS` may not exceed it
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+/// Smallest amount a single deposit may credit
+pub const MIN_DEPOSIT: Item<Uint128> = Item::new("min_deposit");
+
+/// Whether deposits carrying more than one coin are rejected; when false only the
+/// coin in the primary denom is credited and the others are ignored
+pub const STRICT_DEPOSIT: Item<bool> = Item::new("strict_deposit");
+
+/// Whether SpendFrom responses omit addresses and amounts, carrying only a hashed reference
+pub const MINIMAL_EVENTS: Item<bool> = Item::new("minimal_events");
+
+/// Whether the contract is paused by a chain-issued sudo message
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+/// Whether deposits may credit the admin's own balance
+pub const ADMIN_CAN_DEPOSIT: Item<bool> = Item::new("admin_can_deposit");
+
+/// Blocks of owner inactivity after which a recovery address may claim the balance
+pub const RECOVERY_DELAY_BLOCKS: Item<u64> = Item::new("recovery_delay_blocks");
+
+/// Managers each owner appointed via AddManager; a manager may authorize and revoke
+/// spenders on the owner's behalf but cannot spend
+/// Key: (owner address, manager address), Value: unit
+pub const MANAGERS: Map<(&Addr, &Addr), ()> = Map::new("managers");
+
+/// Recovery address designated by each owner via SetRecovery
+/// Key: owner address, Value: recovery address
+pub const RECOVERY: Map<&Addr, Addr> = Map::new("recovery");
+
+/// Block height of each address's most recent execute
+/// Key: address, Value: block height
+/// Written for the sender of every execute message; used to detect inactive owners.
+pub const LAST_ACTIVITY: Map<&Addr, u64> = Map::new("last_activity");
+
+/// Maps user addresses to their token balances, one balance per denom
+/// Key: (denom, user address), Value: token balance
+/// Keyed by denom first so every balance of one denom forms a single prefix.
+pub const BALANCES: Map<(&str, &Addr), Uint128> = Map::new("balances");
+
+/// Most an account may hold in a denom after receiving a spend, refund or seed from
+/// another account; unset means uncapped
+pub const MAX_RECEIVED_PER_ACCOUNT: Item<Uint128> = Item::new("max_received_per_account");
+
+/// Lifetime total deposited in the primary denom, before deposit fees
+pub const TOTAL_DEPOSITED: Item<Uint128> = Item::new("total_deposited");
+
+/// Lifetime total moved by delegated spends (single, batch or paid out) in the primary
+/// denom, excluding fees
+pub const TOTAL_SPENT: Item<Uint128> = Item::new("total_spent");
+
+/// Number of AUTHORIZED_SPENDERS entries across all owners
+/// Maintained by every authorization write and removal; expired entries count until removed.
+pub const AUTH_COUNT: Item<u64> = Item::new("auth_count");
+
+/// Number of BALANCES entries holding a nonzero balance
+/// Maintained by every credit and debit as a balance crosses zero.
+pub const ACTIVE_ACCOUNTS: Item<u64> = Item::new("active_accounts");
+
+/// Terms of a spender authorization granted by an owner
+#[cw_serde]
+pub struct Authorization {
+    /// Block height at which the authorization stops being valid; `None` never expires
+    pub expires_at_height: Option<u64>,
+    /// Maximum amount a single spend may move; `None` imposes no per-transaction limit
+    pub max_per_tx: Option<Uint128>,
+    /// Opaque metadata for off-chain coordinators (e.g. agent version, capabilities)
+    pub metadata: Option<Binary>,
+    /// Maximum amount that may be spent within one rate-limit window
+    pub limit_per_window: Option<Uint128>,
+    /// Length of the rate-limit window in seconds
+    pub window_seconds: Option<u64>,
+    /// sha256 hash of a secret the spender must present with every SpendFrom
+    pub secret_hash: Option<Binary>,
+    /// Amount the spender may still spend over the authorization's lifetime;
+    /// decremented on every spend. `None` is unlimited.
+    pub allowance: Option<Uint128>,
+    /// Block time before which the authorization cannot be used; `None` is active immediately
+    pub starts_at: Option<Timestamp>,
+    /// Schedule on which keepers reset the allowance; `None` is not recurring
+    pub refill: Option<RecurringRefill>,
+    /// Seconds that must pass between consecutive spends; `None` imposes no cooldown
+    pub cooldown_seconds: Option<u64>,
+}
+
+/// Recurring allowance schedule, processed by keepers via ProcessRefills
+#[cw_serde]
+pub struct RecurringRefill {
+    /// Allowance restored at each refill
+    pub amount: Uint128,
+    /// Seconds between refills
+    pub interval_seconds: u64,
+    /// Block time from which the next refill is due
+    pub next_refill_at: Timestamp,
+}
+
+impl Authorization {
+    /// Returns true once the current block height has reached the expiry height
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        matches!(self.expires_at_height, Some(height) if block.height >= height)
+    }
+
+    /// Returns true while the current block time is before the start time
+    pub fn is_pending(&self, block: &BlockInfo) -> bool {
+        matches!(self.starts_at, Some(start) if block.time < start)
+    }
+}
 
 /// Authorization mapping between owners and spenders
-/// Key: (owner address, spender address), Value: authorization status (true/false)
+/// Key: (owner address, spender address), Value: the authorization terms
 /// Used to track which addresses are allowed to spend on behalf of owners
-pub const AUTHORIZED_SPENDERS: Map<(&Addr, &Addr), bool> = Map::new("authorized_spenders");
+pub const AUTHORIZED_SPENDERS: Map<(&Addr, &Addr), Authorization> = Map::new("authorized_spenders");
+
+/// Reverse index of AUTHORIZED_SPENDERS for looking up authorizations by spender
+/// Key: (spender address, owner address), Value: unit
+/// Maintained alongside every authorization write and removal.
+pub const SPENDER_INDEX: Map<(&Addr, &Addr), ()> = Map::new("spender_index");
+
+/// Most spenders a single owner may have authorized at once
+pub const MAX_SPENDERS_PER_OWNER: Item<u32> = Item::new("max_spenders_per_owner");
+
+/// Number of AUTHORIZED_SPENDERS entries per owner, checked against MAX_SPENDERS_PER_OWNER
+/// Key: owner address, Value: number of authorized spenders
+pub const SPENDER_COUNT: Map<&Addr, u32> = Map::new("spender_count");
+
+/// Last authorization (owner, spender) scanned by the RebuildReverseIndex migration;
+/// absent when no rebuild is in progress
+pub const REINDEX_CURSOR: Item<(Addr, Addr)> = Item::new("reindex_cursor");
+
+/// Spenders blocked by the admin, overriding any owner's authorization
+/// Key: spender address, Value: true while blocked
+pub const SPENDER_BLOCKLIST: Map<&Addr, bool> = Map::new("spender_blocklist");
+
+/// Accounts frozen by the admin
+/// Key: account address, Value: true while frozen; unfreezing removes the entry
+pub const FROZEN: Map<&Addr, bool> = Map::new("frozen");
+
+/// Rate-limit window usage for authorizations with a per-window limit
+/// Key: (owner address, spender address), Value: (window start in seconds, amount spent in window)
+/// A window starts with the first spend after the previous window elapsed.
+pub const SPEND_WINDOWS: Map<(&Addr, &Addr), (u64, Uint128)> = Map::new("spend_windows");
+
+/// Time of the last spend under authorizations with a cooldown
+/// Key: (owner address, spender address), Value: block time of the last spend in seconds
+pub const LAST_SPEND: Map<(&Addr, &Addr), u64> = Map::new("last_spend");
+
+/// Contract-wide limit on delegated spending per epoch
+#[cw_serde]
+pub struct GlobalSpendCap {
+    /// Most that all delegated spends together may move within one epoch
+    pub cap: Uint128,
+    /// Length of an epoch in seconds
+    pub epoch_seconds: u64,
+}
+
+/// Time range during which delegated spending is blocked, from `start` (inclusive)
+/// to `end` (exclusive)
+#[cw_serde]
+pub struct BlackoutWindow {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl BlackoutWindow {
+    /// Returns true if `block` falls inside the window
+    pub fn contains(&self, block: &BlockInfo) -> bool {
+        self.start <= block.time && block.time < self.end
+    }
+}
+
+/// Blackout windows set by the admin; delegated spends inside any of them are rejected
+pub const SPENDING_BLACKOUTS: Item<Vec<BlackoutWindow>> = Item::new("spending_blackouts");
+
+/// Global delegated spending cap; absent when spending is uncapped
+pub const GLOBAL_SPEND_CAP: Item<GlobalSpendCap> = Item::new("global_spend_cap");
+
+/// Usage of the current global spending epoch: (epoch start in seconds, amount spent)
+/// An epoch starts with the first delegated spend after the previous one elapsed.
+pub const GLOBAL_SPEND_EPOCH: Item<(u64, Uint128)> = Item::new("global_spend_epoch");
+
+/// Denoms a spender may spend from an owner, set via SetSpenderDenoms
+/// Key: (owner address, spender address), Value: allowed denoms
+/// A missing entry allows every accepted denom.
+pub const SPENDER_DENOMS: Map<(&Addr, &Addr), Vec<String>> = Map::new("spender_denoms");
+
+/// Queue of scheduled allowance refills, ordered by due time
+/// Key: (due time in seconds, owner address, spender address), Value: unit
+/// An entry whose authorization was revoked or rescheduled is dropped when reached.
+pub const REFILL_QUEUE: Map<(u64, &Addr, &Addr), ()> = Map::new("refill_queue");
+
+/// Queue of authorization expiries, ordered by expiry height
+/// Key: (expiry block height, owner address, spender address), Value: unit
+/// An entry whose authorization was revoked or given a new expiry is dropped when reached.
+pub const EXPIRY_QUEUE: Map<(u64, &Addr, &Addr), ()> = Map::new("expiry_queue");
+
+/// Reward credited to a keeper for each expired authorization cleaned up or allowance refilled
+pub const KEEPER_REWARD: Item<Uint128> = Item::new("keeper_reward");
+
+/// Tokens set aside to pay keeper rewards, funded via FundRewardPool
+pub const REWARD_POOL: Item<Uint128> = Item::new("reward_pool");
+
+/// Cumulative amount each spender has spent from each owner's balance
+/// Key: (spender address, owner address), Value: total spent
+/// Keyed by spender first so an agent's spends across all owners form one prefix.
+/// Self-spends by an owner are not recorded.
+pub const SPENT_BY: Map<(&Addr, &Addr), Uint128> = Map::new("spent_by");
+
+/// Next expected SpendFromSigned nonce of each owner
+/// Key: owner address, Value: nonce, starting at 0 and incremented on every signed spend
+pub const NONCES: Map<&Addr, u64> = Map::new("nonces");
+
+/// secp256k1 public keys owners sign SpendFromSigned payloads with
+/// Key: owner address, Value: compressed (33-byte) or uncompressed (65-byte) public key
+pub const SIGNING_KEYS: Map<&Addr, Binary> = Map::new("signing_keys");
+
+/// Cumulative spend fees charged to each owner
+/// Key: owner address whose funds were spent, Value: total fees paid across all denoms
+pub const FEES_PAID_BY: Map<&Addr, Uint128> = Map::new("fees_paid_by");
+
+/// Withdrawal whose bank transfer is in flight, awaiting its reply
+#[cw_serde]
+pub struct PendingWithdrawal {
+    /// Account that was debited
+    pub account: Addr,
+    /// Denom withdrawn
+    pub denom: String,
+    /// Amount debited
+    pub amount: Uint128,
+}
+
+/// Set by Withdraw and cleared by the reply to its bank transfer
+pub const PENDING_WITHDRAWAL: Item<PendingWithdrawal> = Item::new("pending_withdrawal");
+
+/// Delay imposed on large withdrawals, to limit the damage of a compromised key
+#[cw_serde]
+pub struct WithdrawTimelock {
+    /// Most an account may move out of its balance immediately per window, whether
+    /// withdrawn or paid to other accounts, in base units of the denom; withdrawals
+    /// beyond it must be requested with RequestWithdraw and claimed after the delay
+    pub threshold: Uint128,
+    /// Seconds between RequestWithdraw and the earliest ClaimWithdraw, and the length
+    /// of the window over which immediate outflows are summed; at most one year
+    pub delay_seconds: u64,
+}
+
+/// Withdrawal timelock; absent when every withdrawal is immediate
+pub const WITHDRAW_TIMELOCK: Item<WithdrawTimelock> = Item::new("withdraw_timelock");
+
+/// Tokens moved out of an account's balance without going through the timelock
+/// Key: (account address, denom), Value: (window start in seconds, amount sent in window)
+/// A window lasts the timelock's `delay_seconds` and starts with the first outflow
+/// after the previous window elapsed.
+pub const IMMEDIATE_OUTFLOWS: Map<(&Addr, &str), (u64, Uint128)> = Map::new("immediate_outflows");
+
+/// Requested withdrawal waiting for its timelock to pass
+#[cw_serde]
+pub struct TimelockedWithdrawal {
+    /// Denom to withdraw
+    pub denom: String,
+    /// Amount to withdraw; the balance is only debited when it is claimed
+    pub amount: Uint128,
+    /// Earliest time ClaimWithdraw may pay it out
+    pub unlock_time: Timestamp,
+}
+
+/// Each account's requested withdrawal, set by RequestWithdraw and cleared by ClaimWithdraw
+/// Key: account address, Value: the requested withdrawal
+pub const PENDING_WITHDRAWALS: Map<&Addr, TimelockedWithdrawal> = Map::new("pending_withdrawals");
+
+/// One operation in the contract-wide activity ledger
+#[cw_serde]
+pub struct LedgerEntry {
+    /// Sequential id of the entry, starting at 1
+    pub id: u64,
+    /// Block height at which the operation ran
+    pub height: u64,
+    /// Operation kind: deposit, seed, authorize, limit, revoke, expire, spend, refund,
+    /// withdraw, convert, recover, fund_reward_pool or keeper_reward
+    pub action: String,
+    /// Account whose balance or authorizations the operation changed
+    pub account: Addr,
+    /// Other party involved: the depositor, spender or recipient, if not `account`
+    pub counterparty: Option<Addr>,
+    /// Denom moved by the operation, if any
+    pub denom: Option<String>,
+    /// Amount moved by the operation, if any
+    pub amount: Option<Uint128>,
+}
+
+/// Receipt of one deposit, for reconciliation
+#[cw_serde]
+pub struct DepositRecord {
+    /// Sequential id of the deposit, starting at 1
+    pub id: u64,
+    /// Block height at which the deposit was made
+    pub height: u64,
+    /// Block time at which the deposit was made
+    pub time: Timestamp,
+    /// Account that sent the funds
+    pub depositor: Addr,
+    /// Account whose balance was credited
+    pub beneficiary: Addr,
+    /// Denom credited
+    pub denom: String,
+    /// Amount credited, net of the deposit fee
+    pub amount: Uint128,
+    /// Deposit fee credited to the admin
+    pub fee: Uint128,
+}
+
+/// Number of deposits made so far (also the id of the latest deposit)
+pub const DEPOSIT_COUNTER: Item<u64> = Item::new("deposit_counter");
+
+/// Receipt of every deposit
+/// Key: deposit id, Value: the receipt
+pub const DEPOSITS: Map<u64, DepositRecord> = Map::new("deposits");
+
+/// Number of ledger entries recorded so far (also the id of the latest entry)
+pub const LEDGER_COUNT: Item<u64> = Item::new("ledger_count");
+
+/// Contract-wide activity ledger, appended by every balance or authorization change
+/// Key: entry id, Value: the entry
+pub const LEDGER: Map<u64, LedgerEntry> = Map::new("ledger");
+
+/// Snapshot comparing the contract's internal ledger with its actual bank balance
+#[cw_serde]
+pub struct DriftSample {
+    /// Sequential id of the sample, starting at 1
+    pub id: u64,
+    /// Block height at which the sample was recorded
+    pub height: u64,
+    /// Sum of all internal primary-denom balances plus the keeper reward pool
+    pub ledger_total: Uint128,
+    /// Actual balance of the primary denom held by the contract
+    pub escrow_balance: Uint128,
+}
+
+/// Number of drift samples recorded so far (also the id of the latest sample)
+pub const DRIFT_COUNT: Item<u64> = Item::new("drift_count");
+
+/// History of drift samples recorded via RecordDrift
+/// Key: sample id, Value: the sample
+pub const DRIFT_LOG: Map<u64, DriftSample> = Map::new("drift_log");
@@ -1,30 +1,39 @@
-use cosmwasm_std::{testing::{mock_dependencies, mock_env, mock_info}, coins};
-use credits_delegation::{instantiate, execute, query};
+//! # Credits Delegation Contract Testing Guide
+//!
+//! This file contains comprehensive integration tests for the Credits Delegation contract.
+//! Each test simulates a different scenario or edge case to verify the contract's behavior.
+//!
+//! ## Testing Approach
+//! The tests use CosmWasm's mock environment, which simulates blockchain behavior without
+//! requiring an actual blockchain. This allows for rapid testing of contract logic.
+//!
+//! ## Key Testing Concepts
+//! - `mock_dependencies()`: Creates a simulated blockchain backend
+//! - `mock_env()`: Sets up a simulated blockchain environment
+//! - `mock_info()`: Creates transaction context (sender, funds)
+//!
+//! ## Learning Points
+//! These tests demonstrate:
+//! 1. Contract instantiation and configuration
+//! 2. Token deposits and balance tracking
+//! 3. Access control mechanisms
+//! 4. Delegated spending authorization
+//! 5. Error handling and validation
+
+use cosmwasm_std::{testing::{mock_dependencies, mock_env, mock_info}, coin, coins, BankMsg, Binary, CosmosMsg, Event, Uint128};
+use credits_delegation::{instantiate, execute, query, sudo, reply, migrate};
+use credits_delegation::contract::debit_and_send;
 use credits_delegation::msg::init::InstantiateMsg;
-use credits_delegation::msg::exec::ExecuteMsg;
-use credits_delegation::msg::query::QueryMsg;
-
-/// # Credits Delegation Contract Testing Guide
-///
-/// This file contains comprehensive integration tests for the Credits Delegation contract.
-/// Each test simulates a different scenario or edge case to verify the contract's behavior.
-///
-/// ## Testing Approach
-/// The tests use CosmWasm's mock environment, which simulates blockchain behavior without
-/// requiring an actual blockchain. This allows for rapid testing of contract logic.
-///
-/// ## Key Testing Concepts
-/// - `mock_dependencies()`: Creates a simulated blockchain backend
-/// - `mock_env()`: Sets up a simulated blockchain environment
-/// - `mock_info()`: Creates transaction context (sender, funds)
-///
-/// ## Learning Points
-/// These tests demonstrate:
-/// 1. Contract instantiation and configuration
-/// 2. Token deposits and balance tracking
-/// 3. Access control mechanisms
-/// 4. Delegated spending authorization
-/// 5. Error handling and validation
+use credits_delegation::msg::exec::{AuthorizationTerms, ClearableParam, ExecuteMsg, ParamUpdate};
+use credits_delegation::msg::oracle::PriceResponse;
+use credits_delegation::msg::policy::{PolicyQueryMsg, PolicyResponse};
+use credits_delegation::msg::sudo::SudoMsg;
+use credits_delegation::msg::migrate::MigrateMsg;
+use credits_delegation::msg::query::{ActiveWindowResponse, AgentMetadataResponse, AllowanceInfo, AuthStatus, AuthorizationDetails, AuthorizationResponse, BalanceDistributionResponse, BalanceEntry, BalanceResponse, CanSpendResponse, ConfigResponse, DepositResponse, DriftHistoryResponse,
+    EffectiveAllowanceResponse, IndexIntegrityResponse, IndexMismatch, InvariantResponse, IsAuthorizedResponse, LastActivityResponse, LedgerResponse, ManagersResponse, NonceResponse, OwnerAvailableResponse, OwnersForSpenderResponse, QueryMsg, SimulateBatchSpendResponse, StatsResponse, WithdrawReadyAtResponse};
+use credits_delegation::state::{BlackoutWindow, DenomPrecision, DepositRecord, GlobalSpendCap, WithdrawTimelock, SPENDER_INDEX};
+use credits_delegation::error::ContractError;
+use sha2::{Digest, Sha256};
 
 /// ## Happy Path Test
 /// Tests the complete flow of deposit, authorization, and spending
@@ -39,7 +48,7 @@ fn test_deposit_and_spend_flow() {
     let denom = "ucosm";
     
     // Instantiate contract with admin and token denomination
-    let instantiate_msg = InstantiateMsg { admin: admin.to_string(), denom: denom.to_string() };
+    let instantiate_msg = InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() };
     let info = mock_info(admin, &[]);  // No funds sent with instantiation
     instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
 
@@ -51,22 +60,22 @@ fn test_deposit_and_spend_flow() {
     // User authorizes a spender to access their funds
     let spender = "spender1";
     let auth_info = mock_info(user, &[]);  // No funds needed for authorization
-    execute(deps.as_mut(), mock_env(), auth_info, ExecuteMsg::AuthorizeSpender { spender: spender.to_string() }).unwrap();
+    execute(deps.as_mut(), mock_env(), auth_info, ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }).unwrap();
 
     // Spender transfers 500 tokens from user's balance to their own
     let spend_info = mock_info(spender, &[]);
-    execute(deps.as_mut(), mock_env(), spend_info, ExecuteMsg::SpendFrom { owner: user.to_string(), amount: 500 }).unwrap();
+    execute(deps.as_mut(), mock_env(), spend_info, ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(500), secret: None, denom: None }).unwrap();
 
     // Verify user's remaining balance (1000 - 500 = 500)
-    let balance: u128 = cosmwasm_std::from_json(&query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: user.to_string() }).unwrap()).unwrap();
-    assert_eq!(balance, 500);
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: user.to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(500));
     
     // Verify spender received the tokens (0 + 500 = 500)
-    let spender_balance: u128 = cosmwasm_std::from_json(&query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: spender.to_string() }).unwrap()).unwrap();
-    assert_eq!(spender_balance, 500);
+    let spender_balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: spender.to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(spender_balance, Uint128::new(500));
     
     // Verify that the authorization is still valid after the spend
-    let is_auth: bool = cosmwasm_std::from_json(&query(deps.as_ref(), mock_env(), QueryMsg::IsAuthorized { owner: user.to_string(), spender: spender.to_string() }).unwrap()).unwrap();
+    let is_auth: bool = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::IsAuthorized { owner: user.to_string(), spender: spender.to_string() }).unwrap()).unwrap();
     assert!(is_auth);
 }
 
@@ -84,7 +93,7 @@ fn test_deposit_validation() {
         deps.as_mut(),
         mock_env(),
         mock_info(admin, &[]),
-        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string() }
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
     ).unwrap();
 
     // Case 1: Deposit with correct denomination
@@ -98,12 +107,12 @@ fn test_deposit_validation() {
     assert!(deposit_result.is_ok());
     
     // Verify balance was recorded
-    let balance: u128 = cosmwasm_std::from_json(&query(
+    let balance: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: user.to_string() }
+        QueryMsg::Balance { owner: user.to_string(), denom: None }
     ).unwrap()).unwrap();
-    assert_eq!(balance, 500);
+    assert_eq!(balance, Uint128::new(500));
 
     // Case 2: Deposit with wrong denomination
     let wrong_denom_result = execute(
@@ -113,7 +122,10 @@ fn test_deposit_validation() {
         ExecuteMsg::Deposit {}
     );
     // This should error as wrong token denom provided
-    assert!(wrong_denom_result.is_err());
+    assert!(matches!(
+        wrong_denom_result.unwrap_err(),
+        ContractError::InvalidDenom { expected, got } if expected == denom && got == "usdt"
+    ));
     
     // Case 3: Deposit with multiple coin types
     let multiple_coins_result = execute(
@@ -123,15 +135,19 @@ fn test_deposit_validation() {
         ExecuteMsg::Deposit {}
     );
     // This should error as multiple token denoms provided
-    assert!(multiple_coins_result.is_err());
+    assert!(matches!(multiple_coins_result.unwrap_err(), ContractError::MultipleDenoms {}));
+
+    // Case 4: Deposit without any funds
+    let no_funds_result = execute(deps.as_mut(), mock_env(), mock_info(user, &[]), ExecuteMsg::Deposit {});
+    assert!(matches!(no_funds_result.unwrap_err(), ContractError::NoFunds {}));
     
     // Verify balance hasn't changed after failed attempts
-    let balance_after: u128 = cosmwasm_std::from_json(&query(
+    let balance_after: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: user.to_string() }
+        QueryMsg::Balance { owner: user.to_string(), denom: None }
     ).unwrap()).unwrap();
-    assert_eq!(balance_after, 500);
+    assert_eq!(balance_after, Uint128::new(500));
 }
 
 /// ## Authorization Test
@@ -148,7 +164,7 @@ fn test_authorization_and_revocation() {
         deps.as_mut(),
         mock_env(),
         mock_info(admin, &[]),
-        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string() }
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
     ).unwrap();
     
     // Setup user with balance
@@ -162,56 +178,61 @@ fn test_authorization_and_revocation() {
     ).unwrap();
     
     // Test 1: Initial state - spender should NOT be authorized
-    let initial_auth: bool = cosmwasm_std::from_json(&query(
+    let initial_auth: bool = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
         QueryMsg::IsAuthorized { owner: user.to_string(), spender: spender.to_string() }
     ).unwrap()).unwrap();
-    assert_eq!(initial_auth, false);
+    assert!(!initial_auth);
     
     // Test 2: Authorize spender
     execute(
         deps.as_mut(),
         mock_env(),
         mock_info(user, &[]),
-        ExecuteMsg::AuthorizeSpender { spender: spender.to_string() }
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
     ).unwrap();
     
     // Verify authorization was granted
-    let auth_granted: bool = cosmwasm_std::from_json(&query(
+    let auth_granted: bool = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
         QueryMsg::IsAuthorized { owner: user.to_string(), spender: spender.to_string() }
     ).unwrap()).unwrap();
-    assert_eq!(auth_granted, true);
+    assert!(auth_granted);
     
     // Test 3: Revoke authorization
     execute(
         deps.as_mut(),
         mock_env(),
         mock_info(user, &[]),
-        ExecuteMsg::RevokeSpender { spender: spender.to_string() }
+        ExecuteMsg::RevokeSpender { spender: spender.to_string(), owner: None }
     ).unwrap();
     
     // Verify authorization was revoked
-    let auth_revoked: bool = cosmwasm_std::from_json(&query(
+    let auth_revoked: bool = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
         QueryMsg::IsAuthorized { owner: user.to_string(), spender: spender.to_string() }
     ).unwrap()).unwrap();
-    assert_eq!(auth_revoked, false);
+    assert!(!auth_revoked);
     
     // Test 4: Only owner can authorize/revoke
+    // AuthorizeSpender always grants on the sender's own balance, so another user
+    // authorizing the same spender never restores access to user's funds
     let other_user = "other_user";
-    let unauthorized_auth = execute(
+    execute(
         deps.as_mut(),
         mock_env(),
         mock_info(other_user, &[]),
-        ExecuteMsg::AuthorizeSpender { spender: spender.to_string() }
-    );
-    
-    // This should return an error since other_user is trying to authorize on behalf of user
-    assert!(unauthorized_auth.is_err());
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+    ).unwrap();
+    let still_revoked: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsAuthorized { owner: user.to_string(), spender: spender.to_string() }
+    ).unwrap()).unwrap();
+    assert!(!still_revoked);
 }
 
 /// ## Spending Authorization Test
@@ -228,7 +249,7 @@ fn test_spending_authorization() {
         deps.as_mut(),
         mock_env(),
         mock_info(admin, &[]),
-        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string() }
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
     ).unwrap();
     
     // Setup users with balances
@@ -249,48 +270,48 @@ fn test_spending_authorization() {
         deps.as_mut(),
         mock_env(),
         mock_info(owner, &[]),
-        ExecuteMsg::AuthorizeSpender { spender: authorized_spender.to_string() }
+        ExecuteMsg::AuthorizeSpender { spender: authorized_spender.to_string(), terms: None, owner: None }
     ).unwrap();
     // Test 1: Authorized spender can spend
     let auth_spend_result = execute(
         deps.as_mut(),
         mock_env(),
         mock_info(authorized_spender, &[]),
-        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: 300 }
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(300), secret: None, denom: None }
     );
     assert!(auth_spend_result.is_ok());
     
     // Verify balances after authorized spend
-    let owner_balance: u128 = cosmwasm_std::from_json(&query(
+    let owner_balance: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: owner.to_string() }
+        QueryMsg::Balance { owner: owner.to_string(), denom: None }
     ).unwrap()).unwrap();
-    assert_eq!(owner_balance, 700); // 1000 - 300
+    assert_eq!(owner_balance, Uint128::new(700)); // 1000 - 300
     
-    let auth_spender_balance: u128 = cosmwasm_std::from_json(&query(
+    let auth_spender_balance: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: authorized_spender.to_string() }
+        QueryMsg::Balance { owner: authorized_spender.to_string(), denom: None }
     ).unwrap()).unwrap();
-    assert_eq!(auth_spender_balance, 300);
+    assert_eq!(auth_spender_balance, Uint128::new(300));
     
     // Test 2: Unauthorized spender cannot spend
     let unauth_spend_result = execute(
         deps.as_mut(),
         mock_env(),
         mock_info(unauthorized_spender, &[]),
-        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: 100 }
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
     );
     assert!(unauth_spend_result.is_err());
     
     // Verify balances remain unchanged after unauthorized attempt
-    let owner_balance_after: u128 = cosmwasm_std::from_json(&query(
+    let owner_balance_after: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: owner.to_string() }
+        QueryMsg::Balance { owner: owner.to_string(), denom: None }
     ).unwrap()).unwrap();
-    assert_eq!(owner_balance_after, 700); // Still 700
+    assert_eq!(owner_balance_after, Uint128::new(700)); // Still 700
 }
 
 /// ## Overdraft Protection Test
@@ -306,7 +327,7 @@ fn test_overdraft_protection() {
         deps.as_mut(),
         mock_env(),
         mock_info(admin, &[]),
-        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string() }
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
     ).unwrap();
     
     // Setup user with limited balance
@@ -326,7 +347,7 @@ fn test_overdraft_protection() {
         deps.as_mut(),
         mock_env(),
         mock_info(user, &[]),
-        ExecuteMsg::AuthorizeSpender { spender: spender.to_string() }
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
     ).unwrap();
     
     // Test 1: Spend exactly what's available
@@ -334,7 +355,7 @@ fn test_overdraft_protection() {
         deps.as_mut(),
         mock_env(),
         mock_info(spender, &[]),
-        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: 500 }
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(500), secret: None, denom: None }
     );
     assert!(exact_spend.is_ok());
     
@@ -343,7 +364,7 @@ fn test_overdraft_protection() {
         deps.as_mut(),
         mock_env(),
         mock_info(spender, &[]),
-        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: 1 }
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(1), secret: None, denom: None }
     );
     assert!(overdraft_result.is_err());
     
@@ -360,17 +381,17 @@ fn test_overdraft_protection() {
         deps.as_mut(),
         mock_env(),
         mock_info(spender, &[]),
-        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: 2000 }
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(2000), secret: None, denom: None }
     );
     assert!(excessive_spend.is_err());
     
     // Balance should still be intact
-    let balance: u128 = cosmwasm_std::from_json(&query(
+    let balance: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: user.to_string() }
+        QueryMsg::Balance { owner: user.to_string(), denom: None }
     ).unwrap()).unwrap();
-    assert_eq!(balance, 1000);
+    assert_eq!(balance, Uint128::new(1000));
 }
 
 /// ## Self-Spending Test
@@ -386,7 +407,7 @@ fn test_self_spending() {
         deps.as_mut(),
         mock_env(),
         mock_info(admin, &[]),
-        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string() }
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
     ).unwrap();
     
     // Setup user with balance
@@ -403,16 +424,5581 @@ fn test_self_spending() {
         deps.as_mut(),
         mock_env(),
         mock_info(user, &[]),
-        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: 300 }
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(300), secret: None, denom: None }
     );
     
     assert!(self_spend.is_ok());
     
     // Check that balance was adjusted (spent tokens become a wash)
-    let balance: u128 = cosmwasm_std::from_json(&query(
+    let balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: user.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(1000)); // Balance remains at 1000 because self-spending is effectively a no-op
+}
+
+/// ## Deposit On Behalf Test
+/// Tests that a sponsor can fund another address's balance with DepositFor,
+/// leaving the sponsor's own balance untouched.
+#[test]
+fn test_deposit_for_beneficiary() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Sponsor deposits 500 tokens on behalf of user1
+    let sponsor = "sponsor";
+    let user = "user1";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(sponsor, &coins(500, denom)),
+        ExecuteMsg::DepositFor { beneficiary: user.to_string() }
+    ).unwrap();
+
+    // The beneficiary is credited
+    let user_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: user.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(user_balance, Uint128::new(500));
+
+    // The sponsor's balance is unchanged
+    let sponsor_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: sponsor.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(sponsor_balance, Uint128::new(0));
+}
+
+/// ## Batch Spend Test
+/// Tests that an authorized spender can distribute an owner's funds
+/// to several recipients in a single message, and that zero payouts are rejected.
+#[test]
+fn test_batch_spend_from() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account and authorizes a payroll spender
+    let owner = "owner";
+    let spender = "payroll";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+    ).unwrap();
+
+    // A zero payout is rejected along with the rest of the batch
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::BatchSpendFrom {
+            owner: owner.to_string(),
+            payouts: vec![("alice".to_string(), Uint128::new(100)), ("bob".to_string(), Uint128::zero())],
+            denom: None,
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+
+    // Distribute to three recipients in one message
+    let payouts = vec![
+        ("alice".to_string(), Uint128::new(100)),
+        ("bob".to_string(), Uint128::new(200)),
+        ("carol".to_string(), Uint128::new(300)),
+    ];
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::BatchSpendFrom { owner: owner.to_string(), payouts: payouts.clone(), denom: None }
+    ).unwrap();
+
+    // Every recipient is credited with their payout
+    for (recipient, amount) in payouts {
+        let balance: Uint128 = cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance { owner: recipient, denom: None }
+        ).unwrap()).unwrap();
+        assert_eq!(balance, amount);
+    }
+
+    // The owner is debited by the total (1000 - 600 = 400)
+    let owner_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: owner.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(owner_balance, Uint128::new(400));
+}
+
+/// ## Batch Spend Atomicity Test
+/// Tests that a batch whose total exceeds the owner's balance fails as a whole
+/// and leaves every balance unchanged.
+#[test]
+fn test_batch_spend_from_exceeding_balance() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account and authorizes a payroll spender
+    let owner = "owner";
+    let spender = "payroll";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(500, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+    ).unwrap();
+
+    // Total of 600 exceeds the owner's balance of 500
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::BatchSpendFrom {
+            owner: owner.to_string(),
+            payouts: vec![
+                ("alice".to_string(), Uint128::new(100)),
+                ("bob".to_string(), Uint128::new(200)),
+                ("carol".to_string(), Uint128::new(300)),
+            ],
+            denom: None,
+        }
+    );
+    assert!(result.is_err());
+
+    // Nothing changed
+    let owner_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: owner.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(owner_balance, Uint128::new(500));
+    for recipient in ["alice", "bob", "carol"] {
+        let balance: Uint128 = cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance { owner: recipient.to_string(), denom: None }
+        ).unwrap()).unwrap();
+        assert_eq!(balance, Uint128::new(0));
+    }
+}
+
+/// ## Expired Authorization Cleanup Test
+/// Tests that a keeper can remove expired authorizations, is credited a reward
+/// per entry from the reward pool, and that cleanup is bounded by the pool.
+#[test]
+fn test_cleanup_expired_with_keeper_reward() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with a keeper reward of 10 per cleaned entry
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), keeper_reward: Some(Uint128::new(10)), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account
+    let owner = "owner";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+
+    // Three authorizations expire shortly, one never expires
+    let expires_at_height = mock_env().block.height + 5;
+    for spender in ["agent1", "agent2", "agent3"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::AuthorizeSpender {
+                spender: spender.to_string(),
+                terms: Some(AuthorizationTerms { expires_at_height: Some(expires_at_height), ..Default::default() }),
+                owner: None,
+            }
+        ).unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender { spender: "long_lived".to_string(), terms: None, owner: None }
+    ).unwrap();
+
+    // The admin funds the reward pool with enough for only two cleanups
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &coins(25, denom)),
+        ExecuteMsg::FundRewardPool {}
+    ).unwrap();
+
+    // Advance past the expiry height
+    let mut env = mock_env();
+    env.block.height = expires_at_height + 1;
+
+    // Expired spenders can no longer spend
+    let expired_spend = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("agent1", &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(1), secret: None, denom: None }
+    );
+    assert!(expired_spend.is_err());
+
+    // Keeper cleanup is bounded by the pool: 25 / 10 = 2 entries
+    let keeper = "keeper";
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(keeper, &[]),
+        ExecuteMsg::CleanupExpired { limit: None }
+    ).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "cleaned" && attr.value == "2"));
+    assert_eq!(res.events.iter().filter(|event| event.ty == "authorization_audit").count(), 2);
+
+    let keeper_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Balance { owner: keeper.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(keeper_balance, Uint128::new(20));
+
+    // Topping up the pool lets the last expired entry be cleaned
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &coins(5, denom)),
+        ExecuteMsg::FundRewardPool {}
+    ).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(keeper, &[]),
+        ExecuteMsg::CleanupExpired { limit: Some(5) }
+    ).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "cleaned" && attr.value == "1"));
+
+    let keeper_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Balance { owner: keeper.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(keeper_balance, Uint128::new(30));
+
+    // The non-expiring authorization is untouched
+    let still_authorized: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env,
+        QueryMsg::IsAuthorized { owner: owner.to_string(), spender: "long_lived".to_string() }
+    ).unwrap()).unwrap();
+    assert!(still_authorized);
+}
+
+/// ## Expiry Queue Test
+/// Tests that cleanup walks authorizations in expiry order, stops at the first one
+/// that has not expired, and drops queue entries left behind by a revoked
+/// authorization or a moved expiry without removing or rewarding anything.
+#[test]
+fn test_cleanup_expired_queue() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with a keeper reward and a funded pool
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), keeper_reward: Some(Uint128::new(10)), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &coins(100, denom)), ExecuteMsg::FundRewardPool {}).unwrap();
+    let height = mock_env().block.height;
+    let authorize = |spender: &str, expires_at_height: u64| ExecuteMsg::AuthorizeSpender {
+        spender: spender.to_string(),
+        terms: Some(AuthorizationTerms { expires_at_height: Some(expires_at_height), ..Default::default() }),
+        owner: None,
+    };
+    let is_authorized = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, spender: &str| -> bool {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::IsAuthorized { owner: "owner1".to_string(), spender: spender.to_string() }).unwrap()).unwrap()
+    };
+
+    // agent1 expires first, agent2 is revoked and agent3's expiry is pushed back
+    for spender in ["agent1", "agent2", "agent3"] {
+        execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize(spender, height + 5)).unwrap();
+    }
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RevokeSpender { spender: "agent2".to_string(), owner: None }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize("agent3", height + 50)).unwrap();
+
+    // Only agent1 is removed and rewarded; the stale entries are dropped on the way
+    let mut env = mock_env();
+    env.block.height = height + 10;
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "cleaned" && attr.value == "1"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "reward" && attr.value == "10"));
+    assert!(!is_authorized(&deps, "agent1"));
+    assert!(is_authorized(&deps, "agent3"));
+
+    // agent3 is only removed once its new expiry has been reached
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "cleaned" && attr.value == "0"));
+    env.block.height = height + 50;
+    let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "cleaned" && attr.value == "1"));
+    assert!(!is_authorized(&deps, "agent3"));
+}
+
+/// ## Agent Spend Aggregate Test
+/// Tests that the total an agent has spent is summed across every owner
+/// that authorized it.
+#[test]
+fn test_agent_total_spent_across_owners() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Two owners fund their accounts and authorize the same agent
+    let agent = "agent";
+    for owner in ["owner1", "owner2"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &coins(1000, denom)),
+            ExecuteMsg::Deposit {}
+        ).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::AuthorizeSpender { spender: agent.to_string(), terms: None, owner: None }
+        ).unwrap();
+    }
+
+    // The agent spends from both owners
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(agent, &[]),
+        ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(150), secret: None, denom: None }
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(agent, &[]),
+        ExecuteMsg::SpendFrom { owner: "owner2".to_string(), amount: Uint128::new(250), secret: None, denom: None }
+    ).unwrap();
+
+    // Spending its own credited balance does not count as a delegated spend
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(agent, &[]),
+        ExecuteMsg::SpendFrom { owner: agent.to_string(), amount: Uint128::new(50), secret: None, denom: None }
+    ).unwrap();
+
+    // The aggregate equals the sum of the delegated spends
+    let total: Uint128 = cosmwasm_std::from_json(query(
         deps.as_ref(),
         mock_env(),
-        QueryMsg::Balance { owner: user.to_string() }
+        QueryMsg::AgentTotalSpent { spender: agent.to_string() }
     ).unwrap()).unwrap();
-    assert_eq!(balance, 1000); // Balance remains at 1000 because self-spending is effectively a no-op
+    assert_eq!(total, Uint128::new(400));
+}
+
+/// ## Structured Event Test
+/// Tests that deposits and spends emit dedicated events, so off-chain listeners
+/// can subscribe to `wasm-deposit` and `wasm-delegated_spend` directly.
+#[test]
+fn test_spend_and_deposit_events() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Deposit emits a `deposit` event
+    let owner = "owner";
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    let expected = Event::new("deposit")
+        .add_attribute("from", owner)
+        .add_attribute("account", owner)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", "1000");
+    assert_eq!(res.events, vec![expected]);
+
+    // Authorized spend emits a `delegated_spend` event
+    let spender = "agent";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+    ).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(300), secret: None, denom: None }
+    ).unwrap();
+    let expected = Event::new("delegated_spend")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", spender)
+        .add_attribute("recipient", spender)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", "300")
+        .add_attribute("remaining_balance", "700");
+    assert_eq!(res.events, vec![expected]);
+}
+
+/// ## Per-Transaction Cap Test
+/// Tests that a spender with a per-transaction cap can spend up to the cap
+/// but is rejected above it, even when the owner's balance is sufficient.
+#[test]
+fn test_per_tx_spend_cap() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account and authorizes a spender capped at 100 per spend
+    let owner = "owner";
+    let spender = "agent";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms { max_per_tx: Some(Uint128::new(100)), ..Default::default() }),
+            owner: None,
+        }
+    ).unwrap();
+
+    // Spending exactly the cap succeeds
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap();
+
+    // One above the cap is rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(101), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::PerTxCapExceeded { cap, requested } if cap == Uint128::new(100) && requested == Uint128::new(101)));
+
+    // A batch is capped by its total, not by each payout
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::BatchSpendFrom {
+            owner: owner.to_string(),
+            payouts: vec![("alice".to_string(), Uint128::new(60)), ("bob".to_string(), Uint128::new(60))],
+            denom: None,
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::PerTxCapExceeded { cap, requested } if cap == Uint128::new(100) && requested == Uint128::new(120)));
+
+    // Only the successful spend left the owner's balance
+    let owner_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: owner.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(owner_balance, Uint128::new(900));
+}
+
+/// ## Uncapped Authorization Test
+/// Tests that an authorization without a per-transaction cap imposes no limit
+/// beyond the owner's balance.
+#[test]
+fn test_uncapped_spend() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account and authorizes a spender with no cap
+    let owner = "owner";
+    let spender = "agent";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms { max_per_tx: None, ..Default::default() }),
+            owner: None,
+        }
+    ).unwrap();
+
+    // The whole balance can be spent in one transaction
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(1000), secret: None, denom: None }
+    ).unwrap();
+
+    let spender_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Balance { owner: spender.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(spender_balance, Uint128::new(1000));
+}
+
+/// ## Same-Block Ordering Test
+/// Tests that a deposit and a spend landing in the same block apply in execution
+/// order: a spend after the deposit sees the new funds, while a spend ordered
+/// before the deposit is checked against the pre-deposit balance.
+#[test]
+fn test_same_block_deposit_and_spend_ordering() {
+    let mut deps = mock_dependencies();
+
+    // Every transaction below shares one block
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Two owners each start with 100 and authorize the same agent
+    let spender = "agent";
+    for owner in ["owner1", "owner2"] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner, &coins(100, denom)),
+            ExecuteMsg::Deposit {}
+        ).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(owner, &[]),
+            ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+        ).unwrap();
+    }
+
+    // Order 1: deposit 200, then spend 250 -> the spend sees 300 and succeeds
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner1", &coins(200, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(250), secret: None, denom: None }
+    ).unwrap();
+
+    let balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Balance { owner: "owner1".to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(50));
+
+    // Order 2: spend 250, then deposit 200 -> the spend sees 100 and fails
+    let early_spend = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: "owner2".to_string(), amount: Uint128::new(250), secret: None, denom: None }
+    );
+    assert!(early_spend.is_err());
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner2", &coins(200, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+
+    let balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Balance { owner: "owner2".to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(300));
+
+    // The agent only received the one successful spend
+    let spender_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Balance { owner: spender.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(spender_balance, Uint128::new(250));
+}
+
+/// ## Agent Metadata Test
+/// Tests that opaque metadata attached at authorization time round-trips
+/// through the metadata query unchanged, and that oversized blobs are rejected.
+#[test]
+fn test_agent_metadata_round_trip() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    let owner = "owner";
+    let spender = "agent";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+
+    // Oversized metadata is rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms { metadata: Some(Binary::from(vec![0u8; 1025])), ..Default::default() }),
+            owner: None,
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::MetadataTooLarge { size: 1025, max: 1024 }));
+
+    // Authorize with a binary metadata blob
+    let metadata = Binary::from(br#"{"version":"1.2.0","capabilities":["pay","tip"]}"#.to_vec());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms { metadata: Some(metadata.clone()), ..Default::default() }),
+            owner: None,
+        }
+    ).unwrap();
+
+    // The query returns the exact bytes
+    let stored: Option<Binary> = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AgentMetadata { owner: owner.to_string(), spender: spender.to_string() }
+    ).unwrap()).unwrap();
+    assert_eq!(stored, Some(metadata));
+
+    // Unknown authorizations have no metadata
+    let missing: Option<Binary> = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AgentMetadata { owner: owner.to_string(), spender: "stranger".to_string() }
+    ).unwrap()).unwrap();
+    assert_eq!(missing, None);
+}
+
+/// ## Rate Limit Test
+/// Tests that a spender limited to 1000 per day can spend up to the limit,
+/// is rejected beyond it, can spend again once the window has elapsed, and that a
+/// maximal window keeps enforcing the limit.
+#[test]
+fn test_rate_limited_spending() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account and limits the agent to 1000 per day
+    let owner = "owner";
+    let spender = "agent";
+    let day = 24 * 60 * 60;
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(5000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms {
+                limit_per_window: Some(Uint128::new(1000)),
+                window_seconds: Some(day),
+                ..Default::default()
+            }),
+            owner: None,
+        }
+    ).unwrap();
+
+    // Spend up to the limit in two steps within the window
+    let mut env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(600), secret: None, denom: None }
+    ).unwrap();
+    env.block.time = env.block.time.plus_seconds(60 * 60);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(400), secret: None, denom: None }
+    ).unwrap();
+
+    // Further spending within the window is rejected
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(1), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::RateLimitExceeded { remaining } if remaining == Uint128::new(0)));
+
+    // Once the window has elapsed the full limit is available again
+    env.block.time = mock_env().block.time.plus_seconds(day);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(1000), secret: None, denom: None }
+    ).unwrap();
+
+    let owner_balance: Uint128 = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Balance { owner: owner.to_string(), denom: None }
+    ).unwrap()).unwrap();
+    assert_eq!(owner_balance, Uint128::new(3000));
+
+    // A window far in the future still enforces the limit rather than overflowing
+    let patient = "patient_agent";
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: patient.to_string(),
+            terms: Some(AuthorizationTerms {
+                limit_per_window: Some(Uint128::new(100)),
+                window_seconds: Some(u64::MAX),
+                ..Default::default()
+            }),
+            owner: None,
+        }
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(patient, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(60), secret: None, denom: None }
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(patient, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(40), secret: None, denom: None }
+    ).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(patient, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(1), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::RateLimitExceeded { remaining } if remaining == Uint128::new(0)));
+}
+
+/// ## Revoke All Spenders Test
+/// Tests that an owner can revoke every authorized spender in one message.
+#[test]
+fn test_revoke_all_spenders() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds their account and authorizes three spenders
+    let owner = "owner";
+    let spenders = ["agent1", "agent2", "agent3"];
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    for spender in spenders {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+        ).unwrap();
+    }
+
+    // Another owner's authorization must survive
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_owner", &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_owner", &[]),
+        ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }
+    ).unwrap();
+
+    // Revoke everything at once
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::RevokeAllSpenders {}
+    ).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "revoked" && attr.value == "3"));
+
+    // None of the three spenders is authorized any more
+    for spender in spenders {
+        let is_auth: bool = cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsAuthorized { owner: owner.to_string(), spender: spender.to_string() }
+        ).unwrap()).unwrap();
+        assert!(!is_auth);
+    }
+
+    let other_auth: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsAuthorized { owner: "other_owner".to_string(), spender: "agent1".to_string() }
+    ).unwrap()).unwrap();
+    assert!(other_auth);
+}
+
+/// ## Admin Validation Test
+/// Tests that the contract's own address cannot be configured as its admin,
+/// since admin actions would then be impossible to sign.
+#[test]
+fn test_admin_cannot_be_contract_address() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    let err = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("deployer", &[]),
+        InstantiateMsg {
+            admin: env.contract.address.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidAdmin {}));
+}
+
+/// ## Balance Distribution Test
+/// Tests that accounts are counted into the correct balance buckets,
+/// both in a single call and when paging through the accounts.
+#[test]
+fn test_balance_distribution() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Balances spread across the 0-99, 100-999 and 1000+ buckets
+    for (user, amount) in [("user1", 50), ("user2", 99), ("user3", 100), ("user4", 500), ("user5", 1000), ("user6", 7500)] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(user, &coins(amount, denom)),
+            ExecuteMsg::Deposit {}
+        ).unwrap();
+    }
+
+    // A single call covers every account
+    let res: BalanceDistributionResponse = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BalanceDistribution { denom: None, buckets: vec![Uint128::new(100), Uint128::new(1000)], start_after: None, limit: None }
+    ).unwrap()).unwrap();
+    assert_eq!(res.counts, vec![2, 2, 2]);
+    assert_eq!(res.next_start_after, None);
+
+    // Paging four accounts at a time yields the same totals
+    let mut totals = vec![0u64; 3];
+    let mut start_after = None;
+    loop {
+        let page: BalanceDistributionResponse = cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BalanceDistribution { denom: None, buckets: vec![Uint128::new(100), Uint128::new(1000)], start_after, limit: Some(4) }
+        ).unwrap()).unwrap();
+        for (total, count) in totals.iter_mut().zip(page.counts) {
+            *total += count;
+        }
+        start_after = page.next_start_after;
+        if start_after.is_none() {
+            break;
+        }
+    }
+    assert_eq!(totals, vec![2, 2, 2]);
+
+    // Boundaries must be ascending
+    let unordered = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BalanceDistribution { denom: None, buckets: vec![Uint128::new(1000), Uint128::new(100)], start_after: None, limit: None }
+    );
+    assert!(unordered.is_err());
+}
+
+/// ## Admin Rotation Test
+/// Tests that the admin can hand the role to a new address, after which only
+/// the new admin can rotate it again, and that invalid addresses are rejected.
+#[test]
+fn test_update_admin() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // A non-admin cannot rotate the admin
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("intruder", &[]),
+        ExecuteMsg::UpdateAdmin { new_admin: "intruder".to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // An invalid address is rejected
+    let invalid = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateAdmin { new_admin: "".to_string() }
+    );
+    assert!(invalid.is_err());
+
+    // The contract's own address is rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateAdmin { new_admin: mock_env().contract.address.to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidAdmin {}));
+
+    // The admin rotates to a new key
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateAdmin { new_admin: "new_admin".to_string() }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Config {}
+    ).unwrap()).unwrap();
+    assert_eq!(config.admin, "new_admin");
+
+    // The previous admin has lost the role
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateAdmin { new_admin: admin.to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+}
+
+/// ## Global Spender Ban Test
+/// Tests that the admin can revoke a malicious spender from every owner at once,
+/// in bounded batches, and that the banned spender can neither spend nor be
+/// re-authorized afterwards.
+#[test]
+fn test_global_revoke_spender() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Two owners authorize the same agent
+    let agent = "malicious_agent";
+    for owner in ["owner1", "owner2"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &coins(1000, denom)),
+            ExecuteMsg::Deposit {}
+        ).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::AuthorizeSpender { spender: agent.to_string(), terms: None, owner: None }
+        ).unwrap();
+    }
+
+    // Only the admin may issue a global revoke
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner1", &[]),
+        ExecuteMsg::GlobalRevokeSpender { spender: agent.to_string(), limit: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // Revoke one authorization per call to exercise the continuation
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::GlobalRevokeSpender { spender: agent.to_string(), limit: Some(1) }
+    ).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "complete" && attr.value == "false"));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::GlobalRevokeSpender { spender: agent.to_string(), limit: Some(1) }
+    ).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "complete" && attr.value == "true"));
+
+    // Neither owner's authorization survives
+    for owner in ["owner1", "owner2"] {
+        let is_auth: bool = cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsAuthorized { owner: owner.to_string(), spender: agent.to_string() }
+        ).unwrap()).unwrap();
+        assert!(!is_auth);
+    }
+
+    // The banned spender cannot be authorized again
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner1", &[]),
+        ExecuteMsg::AuthorizeSpender { spender: agent.to_string(), terms: None, owner: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::SpenderBlocked {}));
+}
+
+/// ## Drift History Test
+/// Tests that the admin can record snapshots of the internal ledger against
+/// the contract's real bank balance and read them back in order.
+#[test]
+fn test_record_and_read_drift_history() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // A user deposits 1000, which the bank also holds for the contract
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &coins(1000, denom)),
+        ExecuteMsg::Deposit {}
+    ).unwrap();
+    deps.querier.update_balance(env.contract.address.clone(), coins(1000, denom));
+
+    // First sample: ledger and escrow agree
+    execute(deps.as_mut(), env.clone(), mock_info(admin, &[]), ExecuteMsg::RecordDrift {}).unwrap();
+
+    // Escrow drifts below the ledger
+    deps.querier.update_balance(env.contract.address.clone(), coins(900, denom));
+    let mut later = env.clone();
+    later.block.height += 100;
+    execute(deps.as_mut(), later.clone(), mock_info(admin, &[]), ExecuteMsg::RecordDrift {}).unwrap();
+
+    // Only the admin may record samples
+    let err = execute(deps.as_mut(), later, mock_info("user1", &[]), ExecuteMsg::RecordDrift {}).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // Both samples are returned in order
+    let history: DriftHistoryResponse = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::DriftHistory { start_after: None, limit: None }
+    ).unwrap()).unwrap();
+    assert_eq!(history.samples.len(), 2);
+    assert_eq!(history.samples[0].id, 1);
+    assert_eq!(history.samples[0].height, env.block.height);
+    assert_eq!(history.samples[0].ledger_total, Uint128::new(1000));
+    assert_eq!(history.samples[0].escrow_balance, Uint128::new(1000));
+    assert_eq!(history.samples[1].id, 2);
+    assert_eq!(history.samples[1].height, env.block.height + 100);
+    assert_eq!(history.samples[1].ledger_total, Uint128::new(1000));
+    assert_eq!(history.samples[1].escrow_balance, Uint128::new(900));
+
+    // Paging past the first sample returns only the second
+    let page: DriftHistoryResponse = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        env,
+        QueryMsg::DriftHistory { start_after: Some(1), limit: None }
+    ).unwrap()).unwrap();
+    assert_eq!(page.samples.len(), 1);
+    assert_eq!(page.samples[0].id, 2);
+}
+
+/// ## Admin Sweep Test
+/// Tests that the admin can sweep the contract's entire bank balance to a
+/// recipient, and that nobody else can.
+#[test]
+fn test_admin_sweep() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // The contract holds 750 tokens in the bank
+    deps.querier.update_balance(env.contract.address.clone(), coins(750, denom));
+
+    // A non-admin cannot sweep
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user1", &[]),
+        ExecuteMsg::AdminSweep { recipient: "user1".to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // The admin sweeps everything to the recovery address
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(admin, &[]),
+        ExecuteMsg::AdminSweep { recipient: "recovery".to_string() }
+    ).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send { to_address: "recovery".to_string(), amount: coins(750, denom) })
+    );
+}
+
+/// ## Spending Secret Test
+/// Tests that an authorization protected by a secret hash only allows spends
+/// that present the matching preimage.
+#[test]
+fn test_spend_requires_secret() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner deposits and authorizes a spender behind a secret
+    let owner = "owner";
+    let spender = "spender";
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let secret = Binary::from(b"correct horse battery staple".to_vec());
+    let secret_hash = Binary::from(&Sha256::digest(secret.as_slice())[..]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms { secret_hash: Some(secret_hash), ..Default::default() }),
+            owner: None,
+        }
+    ).unwrap();
+
+    // A wrong secret and a missing secret are both rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: Some(Binary::from(b"wrong".to_vec())), denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::BadSecret {}));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::BadSecret {}));
+
+    // The correct secret lets the spend through
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: Some(secret), denom: None }
+    ).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(900));
+}
+
+/// ## Zero Amount Test
+/// Tests that zero-amount deposits and spends are rejected and leave every
+/// balance unchanged.
+#[test]
+fn test_zero_amounts_rejected() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    let owner = "owner";
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // A zero-coin deposit is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(0, denom)), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(err.to_string().contains("Amount must be positive"));
+
+    // A zero spend is rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(0), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(err.to_string().contains("Amount must be positive"));
+
+    // The balance is untouched
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(1000));
+}
+
+/// ## Insufficient Balance Error Test
+/// Tests that overspending returns the typed InsufficientBalance error carrying
+/// the available and requested amounts.
+#[test]
+fn test_insufficient_balance_error() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    let owner = "owner";
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(250, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(400), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientBalance { available, requested } if available == Uint128::new(250) && requested == Uint128::new(400)));
+}
+
+/// ## Admin Deposit Flag Test
+/// Tests that with `admin_can_deposit` off the admin cannot be credited by a
+/// deposit, while other users deposit normally.
+#[test]
+fn test_admin_cannot_deposit_when_disabled() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with admin deposits disabled
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            admin_can_deposit: Some(false),
+            ..Default::default()
+        }
+    ).unwrap();
+
+    // The admin's own deposit is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info(admin, &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::AdminCannotDeposit {}));
+
+    // Depositing on the admin's behalf is rejected too
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &coins(100, denom)),
+        ExecuteMsg::DepositFor { beneficiary: admin.to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::AdminCannotDeposit {}));
+
+    // Other users deposit normally
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(100));
+
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert!(!config.admin_can_deposit);
+}
+
+/// ## Effective Allowance Test
+/// Tests that the effective allowance reflects a refilled rate-limit window as
+/// soon as the window elapses, before any further spend updates state.
+#[test]
+fn test_effective_allowance_after_refill() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner grants 100 per hour out of a 250 lifetime allowance
+    let owner = "owner";
+    let spender = "spender";
+    execute(deps.as_mut(), env.clone(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms {
+                limit_per_window: Some(Uint128::new(100)),
+                window_seconds: Some(3600),
+                allowance: Some(Uint128::new(250)),
+                ..Default::default()
+            }),
+            owner: None,
+        }
+    ).unwrap();
+    let effective_allowance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env| -> Option<Uint128> {
+        cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            env,
+            QueryMsg::EffectiveAllowance { owner: owner.to_string(), spender: spender.to_string() }
+        ).unwrap()).unwrap()
+    };
+    assert_eq!(effective_allowance(&deps, env.clone()), Some(Uint128::new(100)));
+
+    // Spending 80 leaves 20 in the window
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(80), secret: None, denom: None }
+    ).unwrap();
+    assert_eq!(effective_allowance(&deps, env.clone()), Some(Uint128::new(20)));
+
+    // Once the window elapses the full window limit is available again
+    let mut later = env.clone();
+    later.block.time = later.block.time.plus_seconds(3600);
+    assert_eq!(effective_allowance(&deps, later.clone()), Some(Uint128::new(100)));
+
+    // Spending the refilled window leaves only the lifetime allowance, 250 - 180 = 70
+    execute(
+        deps.as_mut(),
+        later.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap();
+    let mut next_window = later.clone();
+    next_window.block.time = next_window.block.time.plus_seconds(3600);
+    assert_eq!(effective_allowance(&deps, next_window.clone()), Some(Uint128::new(70)));
+
+    // The lifetime allowance is enforced even with a fresh window
+    let err = execute(
+        deps.as_mut(),
+        next_window,
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(80), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::AllowanceExceeded { remaining } if remaining == Uint128::new(70)));
+}
+
+/// ## Owner Recovery Test
+/// Tests that a designated recovery address can claim an owner's balance only
+/// after the owner has been inactive for the configured delay.
+#[test]
+fn test_recovery_after_inactivity() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract with a 100 block recovery delay
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            recovery_delay_blocks: Some(100),
+            ..Default::default()
+        }
+    ).unwrap();
+
+    // Owner deposits and designates a recovery address
+    let owner = "owner";
+    let backup = "backup";
+    execute(deps.as_mut(), env.clone(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SetRecovery { address: backup.to_string() }
+    ).unwrap();
+
+    // Only the recovery address may recover
+    let mut later = env.clone();
+    later.block.height += 100;
+    let err = execute(
+        deps.as_mut(),
+        later.clone(),
+        mock_info("stranger", &[]),
+        ExecuteMsg::Recover { owner: owner.to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // Before the delay has passed, recovery is refused
+    let mut early = env.clone();
+    early.block.height += 99;
+    let err = execute(
+        deps.as_mut(),
+        early,
+        mock_info(backup, &[]),
+        ExecuteMsg::Recover { owner: owner.to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::RecoveryNotReady { available_at_height } if available_at_height == env.block.height + 100));
+
+    // After 100 inactive blocks the recovery address claims the whole balance
+    execute(
+        deps.as_mut(),
+        later.clone(),
+        mock_info(backup, &[]),
+        ExecuteMsg::Recover { owner: owner.to_string() }
+    ).unwrap();
+    let owner_balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), later.clone(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap();
+    let backup_balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), later, QueryMsg::Balance { owner: backup.to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(owner_balance, Uint128::new(0));
+    assert_eq!(backup_balance, Uint128::new(1000));
+}
+
+/// ## Authorize With Deposit Test
+/// Tests that funding and authorizing in a single message credits the owner
+/// and authorizes the spender.
+#[test]
+fn test_authorize_with_deposit() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner funds and authorizes in one transaction
+    let owner = "owner";
+    let spender = "spender";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &coins(1000, denom)),
+        ExecuteMsg::AuthorizeWithDeposit { spender: spender.to_string() }
+    ).unwrap();
+
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(1000));
+    let is_authorized: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsAuthorized { owner: owner.to_string(), spender: spender.to_string() }
+    ).unwrap()).unwrap();
+    assert!(is_authorized);
+
+    // A wrong denom fails the whole message, leaving no authorization behind
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_owner", &coins(1000, "uatom")),
+        ExecuteMsg::AuthorizeWithDeposit { spender: spender.to_string() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidDenom { .. }));
+}
+
+/// ## Last Activity Test
+/// Tests that an owner's last activity height is recorded on execute and
+/// moves forward with later actions.
+#[test]
+fn test_last_activity() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    let owner = "owner";
+    let last_activity = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Option<u64> {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::LastActivity { owner: owner.to_string() }).unwrap()).unwrap()
+    };
+    assert_eq!(last_activity(&deps), None);
+
+    // A deposit at a known height is recorded
+    execute(deps.as_mut(), env.clone(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(last_activity(&deps), Some(env.block.height));
+
+    // A later action moves the height forward
+    let mut later = env.clone();
+    later.block.height += 50;
+    execute(
+        deps.as_mut(),
+        later.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender { spender: "spender".to_string(), terms: None, owner: None }
+    ).unwrap();
+    assert_eq!(last_activity(&deps), Some(later.block.height));
+}
+
+/// ## Unfunded Authorization Test
+/// Tests that an owner with no balance can authorize a spender ahead of funding
+/// the account, and that the grant only covers the owner's own balance.
+#[test]
+fn test_authorize_with_zero_balance() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // An owner who never deposited authorizes a spender
+    let owner = "owner";
+    let spender = "spender";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }
+    ).unwrap();
+    let is_authorized: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsAuthorized { owner: owner.to_string(), spender: spender.to_string() }
+    ).unwrap()).unwrap();
+    assert!(is_authorized);
+
+    // The grant does not extend to any other owner's balance
+    execute(deps.as_mut(), mock_env(), mock_info("funded", &coins(500, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: "funded".to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // Once funded, the pre-authorized spender can spend
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(300, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap();
+}
+
+/// ## Allowance Listing Test
+/// Tests that the Allowances query lists every spender of an owner with its
+/// remaining allowance, and pages with `start_after`.
+#[test]
+fn test_allowances_listing() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner grants differing allowances to two spenders
+    let owner = "owner";
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let expiry = mock_env().block.height + 100;
+    for (spender, allowance, expires_at_height) in [("agent_a", Uint128::new(300), None), ("agent_b", Uint128::new(50), Some(expiry))] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::AuthorizeSpender {
+                spender: spender.to_string(),
+                terms: Some(AuthorizationTerms { allowance: Some(allowance), expires_at_height, ..Default::default() }),
+                owner: None,
+            }
+        ).unwrap();
+    }
+
+    // Spending reduces the listed allowance
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("agent_a", &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap();
+
+    let allowances: Vec<AllowanceInfo> = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Allowances { owner: owner.to_string(), start_after: None, limit: None }
+    ).unwrap()).unwrap();
+    assert_eq!(allowances, vec![
+        AllowanceInfo { spender: "agent_a".to_string(), amount: Uint128::new(200), expires_at_height: None },
+        AllowanceInfo { spender: "agent_b".to_string(), amount: Uint128::new(50), expires_at_height: Some(expiry) },
+    ]);
+
+    // Paging after the first spender returns only the second
+    let page: Vec<AllowanceInfo> = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Allowances { owner: owner.to_string(), start_after: Some("agent_a".to_string()), limit: Some(1) }
+    ).unwrap()).unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].spender, "agent_b");
+}
+
+/// ## Parameter Update Test
+/// Tests that the admin can change a fee and the receive cap in one message while
+/// parameters left unset keep their values, that optional parameters can be
+/// cleared, and that an overlong withdrawal timelock is rejected.
+#[test]
+fn test_update_params() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    let timelock = WithdrawTimelock { threshold: Uint128::new(1000), delay_seconds: 3600 };
+    let instantiate_msg = InstantiateMsg {
+        admin: admin.to_string(),
+        denom: denom.to_string(),
+        keeper_reward: Some(Uint128::new(5)),
+        admin_can_deposit: Some(false),
+        recovery_delay_blocks: Some(1000),
+        price_oracle: Some("oracle".to_string()),
+        oracle_auto_pause: Some(true),
+        global_spend_cap: Some(GlobalSpendCap { cap: Uint128::new(300), epoch_seconds: 3600 }),
+        withdraw_timelock: Some(timelock.clone()),
+        ..Default::default()
+    };
+
+    // A timelock delay that could overflow the unlock time is rejected at instantiation
+    let err = instantiate(
+        mock_dependencies().as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            withdraw_timelock: Some(WithdrawTimelock { threshold: Uint128::new(1000), delay_seconds: u64::MAX }),
+            ..instantiate_msg.clone()
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+    instantiate(deps.as_mut(), mock_env(), mock_info(admin, &[]), instantiate_msg).unwrap();
+
+    let params = ParamUpdate { fee_bps: Some(50), max_received_per_account: Some(Uint128::new(5000)), ..Default::default() };
+
+    // Only the admin may update parameters
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user1", &[]),
+        ExecuteMsg::UpdateParams { params: params.clone() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // The fee and the receive cap change together
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UpdateParams { params }).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.fee_bps, 50);
+    assert_eq!(config.max_received_per_account, Some(Uint128::new(5000)));
+
+    // The unset parameters are untouched
+    assert_eq!(config.keeper_reward, Uint128::new(5));
+    assert_eq!(config.recovery_delay_blocks, 1000);
+    assert!(!config.admin_can_deposit);
+    assert_eq!(config.withdraw_timelock, Some(timelock));
+
+    // An overlong timelock delay is rejected here too
+    let err = execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UpdateParams { params: ParamUpdate {
+        withdraw_timelock: Some(WithdrawTimelock { threshold: Uint128::new(1000), delay_seconds: u64::MAX }),
+        ..Default::default()
+    } }).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+
+    // The oracle cannot be cleared while auto-pause still relies on it
+    let clear = vec![
+        ClearableParam::PriceOracle,
+        ClearableParam::GlobalSpendCap,
+        ClearableParam::WithdrawTimelock,
+        ClearableParam::MaxReceivedPerAccount,
+    ];
+    let err = execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UpdateParams { params: ParamUpdate {
+        clear: Some(clear.clone()),
+        ..Default::default()
+    } }).unwrap_err();
+    assert!(matches!(err, ContractError::ConflictingConfig { .. }));
+
+    // Turning auto-pause off in the same update lets every optional parameter be cleared
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UpdateParams { params: ParamUpdate {
+        oracle_auto_pause: Some(false),
+        clear: Some(clear),
+        ..Default::default()
+    } }).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.price_oracle, None);
+    assert!(!config.oracle_auto_pause);
+    assert_eq!(config.global_spend_cap, None);
+    assert_eq!(config.withdraw_timelock, None);
+    assert_eq!(config.max_received_per_account, None);
+    assert_eq!(config.fee_bps, 50);
+}
+
+/// ## Multiple Denoms Test
+/// Tests that a user can deposit two accepted denoms and hold independent
+/// balances in each, and that spends only touch the requested denom.
+#[test]
+fn test_multiple_denoms() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract accepting ucosm (primary) and uatom
+    let admin = "admin";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            additional_denoms: Some(vec!["uatom".to_string()]),
+            ..Default::default()
+        }
+    ).unwrap();
+
+    // The same user deposits both denoms
+    let user = "user1";
+    execute(deps.as_mut(), mock_env(), mock_info(user, &coins(1000, "ucosm")), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(user, &coins(40, "uatom")), ExecuteMsg::Deposit {}).unwrap();
+
+    // A denom outside the accepted list is still rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info(user, &coins(5, "uosmo")), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidDenom { .. }));
+
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, denom: Option<&str>| -> Uint128 {
+        cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Balance { owner: user.to_string(), denom: denom.map(str::to_string) }
+        ).unwrap()).unwrap()
+    };
+    assert_eq!(balance(&deps, Some("ucosm")), Uint128::new(1000));
+    assert_eq!(balance(&deps, Some("uatom")), Uint128::new(40));
+    // Omitting the denom reads the primary balance
+    assert_eq!(balance(&deps, None), Uint128::new(1000));
+
+    // Spending uatom leaves the ucosm balance untouched
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(user, &[]),
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(15), secret: None, denom: Some("uatom".to_string()) }
+    ).unwrap();
+    assert_eq!(balance(&deps, Some("uatom")), Uint128::new(40));
+
+    execute(deps.as_mut(), mock_env(), mock_info(user, &[]), ExecuteMsg::AuthorizeSpender { spender: "agent".to_string(), terms: None, owner: None }).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("agent", &[]),
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(15), secret: None, denom: Some("uatom".to_string()) }
+    ).unwrap();
+    assert_eq!(balance(&deps, Some("uatom")), Uint128::new(25));
+    assert_eq!(balance(&deps, Some("ucosm")), Uint128::new(1000));
+
+    // Overspending one denom cannot draw on the other
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("agent", &[]),
+        ExecuteMsg::SpendFrom { owner: user.to_string(), amount: Uint128::new(100), secret: None, denom: Some("uatom".to_string()) }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientBalance { available, requested } if available == Uint128::new(25) && requested == Uint128::new(100)));
+}
+
+/// ## Allowance Adjustment Test
+/// Tests CW20-style allowance changes: increasing creates and grows a limited
+/// allowance, decreasing shrinks it, and reaching zero removes the authorization.
+#[test]
+fn test_increase_and_decrease_allowance() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    let owner = "owner";
+    let spender = "spender";
+    let allowance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> AllowanceInfo {
+        cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Allowance { owner: owner.to_string(), spender: spender.to_string() }
+        ).unwrap()).unwrap()
+    };
+
+    // Increasing from nothing creates a limited authorization, in two steps up to 100
+    for amount in [Uint128::new(60), Uint128::new(40)] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(owner, &[]),
+            ExecuteMsg::IncreaseAllowance { spender: spender.to_string(), amount }
+        ).unwrap();
+    }
+    assert_eq!(allowance(&deps).amount, Uint128::new(100));
+
+    // Decreasing by 30 leaves 70
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::DecreaseAllowance { spender: spender.to_string(), amount: Uint128::new(30) }
+    ).unwrap();
+    assert_eq!(allowance(&deps).amount, Uint128::new(70));
+
+    // Decreasing past zero saturates and removes the authorization
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::DecreaseAllowance { spender: spender.to_string(), amount: Uint128::new(500) }
+    ).unwrap();
+    assert_eq!(allowance(&deps).amount, Uint128::new(0));
+    let is_authorized: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsAuthorized { owner: owner.to_string(), spender: spender.to_string() }
+    ).unwrap()).unwrap();
+    assert!(!is_authorized);
+}
+
+/// ## Delayed Start Test
+/// Tests that an authorization with a future start time rejects spends until
+/// the start time is reached.
+#[test]
+fn test_authorization_start_time() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner grants a budget that starts in one hour
+    let owner = "owner";
+    let spender = "spender";
+    execute(deps.as_mut(), env.clone(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let starts_at = env.block.time.plus_seconds(3600);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms { starts_at: Some(starts_at), ..Default::default() }),
+            owner: None,
+        }
+    ).unwrap();
+
+    // Before the start the spend is rejected
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::NotYetActive {}));
+
+    // From the start time on it succeeds
+    let mut started = env;
+    started.block.time = starts_at;
+    execute(
+        deps.as_mut(),
+        started,
+        mock_info(spender, &[]),
+        ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(100), secret: None, denom: None }
+    ).unwrap();
+}
+
+/// ## Active Window Test
+/// Tests that the ActiveWindow query reports the start and expiry of an
+/// authorization and whether the current block falls inside them.
+#[test]
+fn test_active_window() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Owner grants a future-start, future-expiry authorization
+    let owner = "owner";
+    let spender = "spender";
+    let starts_at = env.block.time.plus_seconds(600);
+    let expires_at = env.block.height + 1000;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::AuthorizeSpender {
+            spender: spender.to_string(),
+            terms: Some(AuthorizationTerms {
+                starts_at: Some(starts_at),
+                expires_at_height: Some(expires_at),
+                ..Default::default()
+            }),
+            owner: None,
+        }
+    ).unwrap();
+
+    let window = |env| -> ActiveWindowResponse {
+        cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            env,
+            QueryMsg::ActiveWindow { owner: owner.to_string(), spender: spender.to_string() }
+        ).unwrap()).unwrap()
+    };
+
+    // Before the start it is not active
+    assert_eq!(
+        window(env.clone()),
+        ActiveWindowResponse { starts_at: Some(starts_at), expires_at: Some(expires_at), currently_active: false }
+    );
+
+    // Inside the window it is active
+    let mut inside = env.clone();
+    inside.block.time = starts_at;
+    inside.block.height += 100;
+    assert!(window(inside).currently_active);
+
+    // Past expiry it is no longer active
+    let mut expired = env;
+    expired.block.time = starts_at.plus_seconds(600);
+    expired.block.height = expires_at;
+    assert!(!window(expired).currently_active);
+}
+
+/// ## Sudo Pause Test
+/// Tests that chain-issued sudo messages flip the pause flag without any admin
+/// check, and that user operations are rejected while paused.
+#[test]
+fn test_sudo_pause_and_unpause() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    let paused = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> bool {
+        let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        config.paused
+    };
+    assert!(!paused(&deps));
+
+    // Governance pauses the contract
+    sudo(deps.as_mut(), mock_env(), SudoMsg::ForcePause {}).unwrap();
+    assert!(paused(&deps));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::Paused {}));
+
+    // Governance lifts the pause and deposits work again
+    sudo(deps.as_mut(), mock_env(), SudoMsg::ForceUnpause {}).unwrap();
+    assert!(!paused(&deps));
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+}
+
+/// ## Batch Authorization Status Test
+/// Tests that BatchAuthStatus reports each pair in request order, covering an
+/// authorization with metadata, a missing one and an expired one.
+#[test]
+fn test_batch_auth_status() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // owner1 authorizes agent1 with metadata; owner2 authorizes agent1 briefly
+    let metadata = Binary::from(b"job-42".to_vec());
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { metadata: Some(metadata.clone()), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner2", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { expires_at_height: Some(mock_env().block.height + 5), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+
+    // Query three pairs after owner2's authorization has expired
+    let mut env = mock_env();
+    env.block.height += 10;
+    let statuses: Vec<AuthStatus> = cosmwasm_std::from_json(query(deps.as_ref(), env, QueryMsg::BatchAuthStatus {
+        pairs: vec![
+            ("owner1".to_string(), "agent1".to_string()),
+            ("owner1".to_string(), "agent2".to_string()),
+            ("owner2".to_string(), "agent1".to_string()),
+        ],
+    }).unwrap()).unwrap();
+
+    assert_eq!(statuses.len(), 3);
+    assert_eq!(statuses[0], AuthStatus {
+        owner: "owner1".to_string(),
+        spender: "agent1".to_string(),
+        authorized: true,
+        metadata: Some(metadata),
+    });
+    assert!(!statuses[1].authorized);
+    assert_eq!(statuses[1].spender, "agent2");
+    assert!(!statuses[2].authorized);
+    assert_eq!(statuses[2].owner, "owner2");
+    assert_eq!(statuses[2].metadata, None);
+
+    // An invalid address fails the whole query
+    let err = query(deps.as_ref(), mock_env(), QueryMsg::BatchAuthStatus {
+        pairs: vec![("owner1".to_string(), "".to_string())],
+    });
+    assert!(err.is_err());
+}
+
+/// ## Withdraw Ordering Test
+/// Tests that the ledger is debited before the outgoing bank message exists, both
+/// through the `debit_and_send` helper and the full Withdraw handler.
+#[test]
+fn test_withdraw_debits_before_bank_message() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund user1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // The balance is already decremented in storage when the message is handed back
+    let user = cosmwasm_std::Addr::unchecked("user1");
+    let (send, remaining) = debit_and_send(deps.as_mut().storage, &user, &user, denom, Uint128::new(40)).unwrap();
+    assert_eq!(send, BankMsg::Send { to_address: "user1".to_string(), amount: coins(40, denom) });
+    assert_eq!(remaining, Uint128::new(60));
+    assert_eq!(balance(&deps), Uint128::new(60));
+
+    // An insufficient balance yields no message and leaves storage untouched
+    let err = debit_and_send(deps.as_mut().storage, &user, &user, denom, Uint128::new(61)).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientBalance { available, requested } if available == Uint128::new(60) && requested == Uint128::new(61)));
+    assert_eq!(balance(&deps), Uint128::new(60));
+
+    // The Withdraw handler attaches the transfer after the same debit
+    let res = execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(60), denom: None }).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "user1".to_string(), amount: coins(60, denom) }));
+    assert_eq!(balance(&deps), Uint128::new(0));
+}
+
+/// ## Minimum Deposit Test
+/// Tests that deposits below the configured minimum are rejected while deposits
+/// at the minimum are credited.
+#[test]
+fn test_min_deposit() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with a minimum deposit of 100
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), min_deposit: Some(Uint128::new(100)), ..Default::default() }
+    ).unwrap();
+
+    // A dust deposit is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::DepositBelowMinimum { min, amount } if min == Uint128::new(100) && amount == Uint128::new(50)));
+
+    // A deposit of exactly the minimum is accepted
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(100));
+}
+
+/// ## Deposits Disabled Test
+/// Tests that the admin can halt deposits while authorized spends and withdrawals
+/// keep working, and that deposits resume once re-enabled.
+#[test]
+fn test_set_deposits_enabled() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract, fund owner1 and authorize agent1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+
+    // Only the admin may halt deposits
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::SetDepositsEnabled { enabled: false }).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetDepositsEnabled { enabled: false }).unwrap();
+
+    // Deposits fail, but the agent can still spend and the owner can still withdraw
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::DepositsDisabled {}));
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(30),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(20), denom: None }).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(50));
+
+    // Re-enabling deposits lets them through again
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetDepositsEnabled { enabled: true }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap();
+}
+
+/// ## Deposit Fee Test
+/// Tests that a deposit fee is split off to the admin's balance and that fees
+/// above 100% are rejected at instantiation.
+#[test]
+fn test_deposit_fee() {
+    let mut deps = mock_dependencies();
+
+    // A fee above 10000 basis points is invalid
+    let admin = "admin";
+    let denom = "ucosm";
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), fee_bps: Some(10_001), ..Default::default() }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFeeBps { fee_bps: 10_001 }));
+
+    // Setup contract with a 2.5% fee
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), fee_bps: Some(250), ..Default::default() }
+    ).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // Depositing 1000 credits 975 to the depositor and 25 to the admin
+    let res = execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "fee" && attr.value == "25"));
+    assert_eq!(balance(&deps, "user1"), Uint128::new(975));
+    assert_eq!(balance(&deps, admin), Uint128::new(25));
+}
+
+/// ## Fees Paid By Test
+/// Tests that delegated spends charge the spend fee to the owner on top of the
+/// spent amount and that FeesPaidBy accumulates it.
+#[test]
+fn test_fees_paid_by() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with a 1% spend fee, fund owner1 and authorize agent1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), spend_fee_bps: Some(100), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // Two delegated spends of 200 and 300 cost fees of 2 and 3
+    for amount in [Uint128::new(200), Uint128::new(300)] {
+        execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+            owner: "owner1".to_string(),
+            amount,
+            secret: None,
+            denom: None,
+        }).unwrap();
+    }
+    let fees: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::FeesPaidBy { owner: "owner1".to_string() }).unwrap()).unwrap();
+    assert_eq!(fees, Uint128::new(5));
+    assert_eq!(balance(&deps, "owner1"), Uint128::new(495));
+    assert_eq!(balance(&deps, "agent1"), Uint128::new(500));
+    assert_eq!(balance(&deps, admin), Uint128::new(5));
+
+    // Owners spending their own balance pay no fee
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(100),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    let fees: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::FeesPaidBy { owner: "owner1".to_string() }).unwrap()).unwrap();
+    assert_eq!(fees, Uint128::new(5));
+}
+
+/// ## Owners For Spender Test
+/// Tests that OwnersForSpender lists every owner that authorized a spender, pages
+/// through them, and drops owners once they revoke or their expired authorization
+/// is cleaned up.
+#[test]
+fn test_owners_for_spender() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Two owners authorize agent1; a third authorizes someone else
+    for owner in ["owner1", "owner2"] {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    }
+    execute(deps.as_mut(), mock_env(), mock_info("owner3", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent2".to_string(), terms: None, owner: None }).unwrap();
+    let owners = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, start_after: Option<&str>, limit: Option<u32>| -> Vec<String> {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::OwnersForSpender {
+            spender: "agent1".to_string(),
+            start_after: start_after.map(str::to_string),
+            limit,
+        }).unwrap()).unwrap()
+    };
+
+    // Both owners are returned, and paging yields them one at a time
+    assert_eq!(owners(&deps, None, None), vec!["owner1".to_string(), "owner2".to_string()]);
+    assert_eq!(owners(&deps, None, Some(1)), vec!["owner1".to_string()]);
+    assert_eq!(owners(&deps, Some("owner1"), Some(1)), vec!["owner2".to_string()]);
+
+    // A revoked owner drops out of the list
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: None }).unwrap();
+    assert_eq!(owners(&deps, None, None), vec!["owner2".to_string()]);
+
+    // An expired authorization stays listed until it is cleaned up
+    let expires_at_height = mock_env().block.height + 5;
+    execute(deps.as_mut(), mock_env(), mock_info("owner3", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { expires_at_height: Some(expires_at_height), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+    let mut env = mock_env();
+    env.block.height = expires_at_height;
+    assert_eq!(owners(&deps, None, None), vec!["owner2".to_string(), "owner3".to_string()]);
+    execute(deps.as_mut(), env, mock_info("keeper", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+    assert_eq!(owners(&deps, None, None), vec!["owner2".to_string()]);
+}
+
+/// ## Limit Spender Test
+/// Tests that an unlimited authorization can be bounded in place, with an audit trail,
+/// and that the new allowance and expiry apply to the following spends.
+#[test]
+fn test_limit_spender() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract, fund owner1 and grant agent1 an unlimited authorization
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(2000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = |amount: u128| ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(amount), secret: None, denom: None };
+
+    // Bound the authorization to 500, expiring in 100 blocks
+    let expires = mock_env().block.height + 100;
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::LimitSpender {
+        spender: "agent1".to_string(),
+        limit: Uint128::new(500),
+        expires: Some(expires),
+    }).unwrap();
+
+    // The change carries the audit attributes and is recorded in the ledger
+    assert!(res.attributes.iter().any(|attr| attr.key == "previous_state" && attr.value == "true"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "new_state" && attr.value == "true"));
+    let ledger: LedgerResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Ledger { start_after: None, limit: None }).unwrap()).unwrap();
+    let last = ledger.entries.last().unwrap();
+    assert_eq!(last.action, "limit");
+    assert_eq!(last.counterparty.as_ref().map(|addr| addr.as_str()), Some("agent1"));
+
+    // A spend beyond the new allowance fails; one within it succeeds
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(600)).unwrap_err();
+    assert!(matches!(err, ContractError::AllowanceExceeded { remaining } if remaining == Uint128::new(500)));
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(200)).unwrap();
+
+    // Once the expiry height is reached the authorization no longer works
+    let mut env = mock_env();
+    env.block.height = expires;
+    let err = execute(deps.as_mut(), env, mock_info("agent1", &[]), spend(100)).unwrap_err();
+    assert!(matches!(err, ContractError::AuthorizationExpired {}));
+
+    // An already limited authorization cannot be converted again
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::LimitSpender {
+        spender: "agent1".to_string(),
+        limit: Uint128::new(100),
+        expires: None,
+    });
+    assert!(err.is_err());
+}
+
+/// ## Process Refills Test
+/// Tests that a keeper restores every due recurring allowance in one call, is
+/// rewarded per refill, and that refills are not due again until the next interval.
+/// Intervals longer than a year are rejected.
+#[test]
+fn test_process_refills() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with a keeper reward and a funded reward pool
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), keeper_reward: Some(Uint128::new(5)), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &coins(100, denom)), ExecuteMsg::FundRewardPool {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // An interval too long to schedule is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { refill_amount: Some(Uint128::new(100)), refill_interval_seconds: Some(u64::MAX), ..Default::default() }),
+        owner: None,
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRefillInterval { interval_seconds: u64::MAX, .. }));
+
+    // owner1 grants two agents a daily allowance of 100 and both spend some of it
+    for agent in ["agent1", "agent2"] {
+        execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+            spender: agent.to_string(),
+            terms: Some(AuthorizationTerms { refill_amount: Some(Uint128::new(100)), refill_interval_seconds: Some(86_400), ..Default::default() }),
+            owner: None,
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(agent, &[]), ExecuteMsg::SpendFrom {
+            owner: "owner1".to_string(),
+            amount: Uint128::new(80),
+            secret: None,
+            denom: None,
+        }).unwrap();
+    }
+    let allowance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, spender: &str| -> Uint128 {
+        let info: AllowanceInfo = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Allowance {
+            owner: "owner1".to_string(),
+            spender: spender.to_string(),
+        }).unwrap()).unwrap();
+        info.amount
+    };
+    assert_eq!(allowance(&deps, "agent1"), Uint128::new(20));
+
+    // Nothing is due before the interval has passed
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), ExecuteMsg::ProcessRefills { limit: None }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "refilled" && attr.value == "0"));
+
+    // A day later both refills are processed in one call
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86_400);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), ExecuteMsg::ProcessRefills { limit: None }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "refilled" && attr.value == "2"));
+    assert_eq!(allowance(&deps, "agent1"), Uint128::new(100));
+    assert_eq!(allowance(&deps, "agent2"), Uint128::new(100));
+    let keeper_balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "keeper".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(keeper_balance, Uint128::new(10));
+
+    // The refills were rescheduled, so an immediate second call does nothing
+    let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), ExecuteMsg::ProcessRefills { limit: None }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "refilled" && attr.value == "0"));
+}
+
+/// ## Signed Spend Test
+/// Tests that SpendFromSigned accepts a spend signed by the owner's registered key
+/// and bumps the nonce, while replays, forged signatures and signatures for other
+/// spends are rejected.
+#[test]
+fn test_spend_from_signed() {
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use k256::ecdsa::{Signature, SigningKey};
+
+    let mut deps = mock_dependencies();
+
+    // Setup contract, fund owner1 and authorize the relayer
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "relayer".to_string(), terms: None, owner: None }).unwrap();
+    let owner_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let nonce = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> u64 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Nonce { owner: "owner1".to_string() }).unwrap()).unwrap()
+    };
+    let sign = |key: &SigningKey, amount: u128, nonce: u64| -> Binary {
+        let payload = format!("{}:owner1:relayer:{}:{}:{}", mock_env().contract.address, denom, amount, nonce);
+        let signature: Signature = key.sign_prehash(&Sha256::digest(payload.as_bytes())).unwrap();
+        Binary::from(&signature.to_bytes()[..])
+    };
+    let signed_spend = |amount: u128, nonce: Option<u64>, signature: Binary| ExecuteMsg::SpendFromSigned {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(amount),
+        nonce,
+        denom: None,
+        signature,
+    };
+    assert_eq!(nonce(&deps), 0);
+
+    // Without a registered key nothing can be verified
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_spend(100, None, sign(&owner_key, 100, 0))).unwrap_err();
+    assert!(matches!(err, ContractError::NoSigningKey {}));
+    let pubkey = Binary::from(owner_key.verifying_key().to_encoded_point(true).as_bytes());
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::SetSigningKey { pubkey: Some(pubkey) }).unwrap();
+
+    // The owner's signature over the current nonce succeeds and bumps it
+    execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_spend(100, Some(0), sign(&owner_key, 100, 0))).unwrap();
+    assert_eq!(nonce(&deps), 1);
+
+    // Resubmitting the same signed spend is rejected, with or without the nonce
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_spend(100, Some(0), sign(&owner_key, 100, 0))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidNonce { expected: 1, got: 0 }));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_spend(100, None, sign(&owner_key, 100, 0))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidSignature {}));
+
+    // The relayer can neither raise the signed amount nor sign for the owner itself
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_spend(500, None, sign(&owner_key, 100, 1))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidSignature {}));
+    let relayer_key = SigningKey::from_slice(&[9u8; 32]).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), signed_spend(100, None, sign(&relayer_key, 100, 1))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidSignature {}));
+    assert_eq!(nonce(&deps), 1);
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(900));
+}
+
+/// ## Active Account Count Test
+/// Tests that the active account counter follows balances as they cross zero.
+#[test]
+fn test_active_account_count() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    let count = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> u64 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::ActiveAccountCount {}).unwrap()).unwrap()
+    };
+    assert_eq!(count(&deps), 0);
+
+    // Two deposits make two active accounts; topping one up changes nothing
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user2", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user2", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(count(&deps), 2);
+
+    // Draining user1 to zero leaves one active account
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(100), denom: None }).unwrap();
+    assert_eq!(count(&deps), 1);
+}
+
+/// ## Invariant Check Test
+/// Tests that the ledger stays consistent with the escrowed funds through deposits
+/// and delegated spends, and that a sweep bypassing the ledger is detected.
+#[test]
+fn test_invariant_check() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    let check = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> InvariantResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::InvariantCheck {}).unwrap()).unwrap()
+    };
+
+    // Deposit and spend; the bank balance mirrors the deposited funds
+    execute(deps.as_mut(), env.clone(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    deps.querier.update_balance(env.contract.address.clone(), coins(1000, denom));
+    execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(300),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    assert_eq!(check(&deps), InvariantResponse { ledger_total: Uint128::new(1000), escrow_balance: Uint128::new(1000), consistent: true });
+
+    // An admin sweep empties the bank balance without touching the ledger
+    execute(deps.as_mut(), env.clone(), mock_info(admin, &[]), ExecuteMsg::AdminSweep { recipient: "safe".to_string() }).unwrap();
+    deps.querier.update_balance(env.contract.address, vec![]);
+    assert_eq!(check(&deps), InvariantResponse { ledger_total: Uint128::new(1000), escrow_balance: Uint128::new(0), consistent: false });
+}
+
+/// ## Frozen Owner Authorization Test
+/// Tests that a frozen owner cannot authorize spenders and that unfreezing
+/// restores the ability.
+#[test]
+fn test_frozen_owner_cannot_authorize() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    let authorize = ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None };
+
+    // Only the admin may freeze accounts
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::FreezeAccount { account: "owner1".to_string() }).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // A frozen owner's authorization attempt fails
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::FreezeAccount { account: "owner1".to_string() }).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Frozen {}));
+
+    // Unfreezing restores it
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UnfreezeAccount { account: "owner1".to_string() }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize).unwrap();
+    let authorized: bool = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::IsAuthorized {
+        owner: "owner1".to_string(),
+        spender: "agent1".to_string(),
+    }).unwrap()).unwrap();
+    assert!(authorized);
+}
+
+/// ## Spend Block Height Test
+/// Tests that SpendFrom receives the block environment from the dispatcher by
+/// checking the block height it reports.
+#[test]
+fn test_spend_from_reports_block_height() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund user1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // Spend at a specific height and read it back from the response
+    let mut env = mock_env();
+    env.block.height = 424_242;
+    let res = execute(deps.as_mut(), env, mock_info("user1", &[]), ExecuteMsg::SpendFrom {
+        owner: "user1".to_string(),
+        amount: Uint128::new(10),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "block_height" && attr.value == "424242"));
+}
+
+/// ## Freeze Account Test
+/// Tests that freezing an account blocks its deposits, spends from its balance
+/// and withdrawals, and that unfreezing lets them succeed again.
+#[test]
+fn test_freeze_account_blocks_balance_movements() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract, fund user1 and authorize agent1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = ExecuteMsg::SpendFrom { owner: "user1".to_string(), amount: Uint128::new(10), secret: None, denom: None };
+
+    // Freeze user1: deposits, delegated spends and withdrawals all fail
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::FreezeAccount { account: "user1".to_string() }).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::AccountFrozen { account } if account == "user1"));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::AccountFrozen { .. }));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(10), denom: None }).unwrap_err();
+    assert!(matches!(err, ContractError::AccountFrozen { .. }));
+
+    // Unfreezing lets the deposit and spend through
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UnfreezeAccount { account: "user1".to_string() }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(140));
+}
+
+/// ## Activity Ledger Test
+/// Tests that deposits, authorizations, spends, withdrawals and revocations are
+/// appended to the ledger in execution order and can be paged through, while
+/// revoking an unauthorized pair records nothing.
+#[test]
+fn test_activity_ledger() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+
+    // Perform one operation of each kind
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(30),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(20), denom: None }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: None }).unwrap();
+
+    // Revoking a pair that is no longer authorized records nothing
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: None }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::AdminRevoke { owner: "owner1".to_string(), spender: "agent2".to_string() }).unwrap();
+
+    // The ledger lists them in order
+    let ledger = |start_after: Option<u64>, limit: Option<u32>| -> LedgerResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Ledger { start_after, limit }).unwrap()).unwrap()
+    };
+    let entries = ledger(None, None).entries;
+    let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(actions, vec!["deposit", "authorize", "spend", "withdraw", "revoke"]);
+    assert_eq!(entries[2].account, "owner1");
+    assert_eq!(entries[2].counterparty.as_ref().map(|addr| addr.as_str()), Some("agent1"));
+    assert_eq!(entries[2].amount, Some(Uint128::new(30)));
+    assert_eq!(entries[2].height, mock_env().block.height);
+
+    // Paging resumes after the last seen id
+    let page = ledger(Some(3), Some(1)).entries;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].id, 4);
+    assert_eq!(page[0].action, "withdraw");
+}
+
+/// ## Ledger Coverage Test
+/// Tests that reward pool funding, keeper rewards, expiry cleanup, admin bans,
+/// allowance removal at zero and account recovery are all recorded in the ledger.
+#[test]
+fn test_ledger_covers_keeper_and_recovery() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with a keeper reward and a short recovery delay
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            keeper_reward: Some(Uint128::new(10)),
+            recovery_delay_blocks: Some(10),
+            ..Default::default()
+        }
+    ).unwrap();
+    let ledger_after = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, start_after: u64| -> LedgerResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Ledger { start_after: Some(start_after), limit: None }).unwrap()).unwrap()
+    };
+
+    // Funding the pool is recorded against the funder
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &coins(100, denom)), ExecuteMsg::FundRewardPool {}).unwrap();
+    let entries = ledger_after(&deps, 0).entries;
+    assert_eq!(entries[0].action, "fund_reward_pool");
+    assert_eq!(entries[0].account, admin);
+    assert_eq!(entries[0].amount, Some(Uint128::new(100)));
+
+    // Cleanup records the expiry and the keeper's reward
+    let expires_at_height = mock_env().block.height + 5;
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { expires_at_height: Some(expires_at_height), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+    let mut env = mock_env();
+    env.block.height = expires_at_height;
+    execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), ExecuteMsg::CleanupExpired { limit: None }).unwrap();
+    let entries = ledger_after(&deps, 2).entries;
+    assert_eq!(entries[0].action, "expire");
+    assert_eq!(entries[0].account, "owner1");
+    assert_eq!(entries[0].counterparty.as_ref().map(|addr| addr.as_str()), Some("agent1"));
+    assert_eq!(entries[1].action, "keeper_reward");
+    assert_eq!(entries[1].account, "keeper");
+    assert_eq!(entries[1].amount, Some(Uint128::new(10)));
+
+    // Decreasing an allowance to zero and an admin ban both record a revocation
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::IncreaseAllowance { spender: "agent2".to_string(), amount: Uint128::new(5) }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::DecreaseAllowance { spender: "agent2".to_string(), amount: Uint128::new(5) }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner2", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent3".to_string(), terms: None, owner: None }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::GlobalRevokeSpender { spender: "agent3".to_string(), limit: None }).unwrap();
+    let entries = ledger_after(&deps, 4).entries;
+    let actions: Vec<&str> = entries.iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(actions, vec!["authorize", "revoke", "authorize", "revoke"]);
+    assert_eq!(entries[3].account, "owner2");
+
+    // Recovery records the moved balance against the owner
+    execute(deps.as_mut(), mock_env(), mock_info("owner3", &coins(50, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner3", &[]), ExecuteMsg::SetRecovery { address: "backup".to_string() }).unwrap();
+    env.block.height = mock_env().block.height + 10;
+    execute(deps.as_mut(), env, mock_info("backup", &[]), ExecuteMsg::Recover { owner: "owner3".to_string() }).unwrap();
+    let entries = ledger_after(&deps, 9).entries;
+    assert_eq!(entries[0].action, "recover");
+    assert_eq!(entries[0].account, "owner3");
+    assert_eq!(entries[0].counterparty.as_ref().map(|addr| addr.as_str()), Some("backup"));
+    assert_eq!(entries[0].amount, Some(Uint128::new(50)));
+}
+
+/// ## Balances Batch Test
+/// Tests that BalancesBatch returns balances in request order with a zero default,
+/// and rejects lists above the cap.
+#[test]
+fn test_balances_batch() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund two of three users
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg { admin: admin.to_string(), denom: denom.to_string(), ..Default::default() }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user3", &coins(300, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // The unfunded user appears in position with a zero balance
+    let owners = vec!["user1".to_string(), "user2".to_string(), "user3".to_string()];
+    let balances: Vec<BalanceEntry> = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::BalancesBatch { owners, denom: None }).unwrap()).unwrap();
+    assert_eq!(balances, vec![
+        BalanceEntry { owner: "user1".to_string(), balance: Uint128::new(100) },
+        BalanceEntry { owner: "user2".to_string(), balance: Uint128::new(0) },
+        BalanceEntry { owner: "user3".to_string(), balance: Uint128::new(300) },
+    ]);
+
+    // More than 50 addresses are rejected
+    let owners = (0..51).map(|i| format!("user{}", i)).collect();
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::BalancesBatch { owners, denom: None }).is_err());
+}
+
+/// ## Spend USD Test
+/// Tests that SpendUsd converts a micro-USD amount into the primary denom at the
+/// oracle's price, and rejects zero or stale prices.
+#[test]
+fn test_spend_usd() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with an oracle, fund owner1 and authorize agent1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            price_oracle: Some("oracle".to_string()),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let set_price = |deps: &mut cosmwasm_std::OwnedDeps<_, _, cosmwasm_std::testing::MockQuerier>, price: cosmwasm_std::Decimal, updated_at: cosmwasm_std::Timestamp| {
+        deps.querier.update_wasm(move |_| {
+            cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(
+                cosmwasm_std::to_json_binary(&PriceResponse { price, updated_at }).unwrap(),
+            ))
+        });
+    };
+    let spend_usd = |usd_amount: u128| ExecuteMsg::SpendUsd {
+        owner: "owner1".to_string(),
+        usd_amount: Uint128::new(usd_amount),
+        recipient: "merchant".to_string(),
+    };
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // At 2.5 micro-USD per ucosm, 1000 micro-USD buys 400 ucosm
+    let now = mock_env().block.time;
+    set_price(&mut deps, cosmwasm_std::Decimal::percent(250), now);
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend_usd(1000)).unwrap();
+    assert_eq!(balance(&deps, "merchant"), Uint128::new(400));
+    assert_eq!(balance(&deps, "owner1"), Uint128::new(600));
+
+    // A zero price is rejected
+    set_price(&mut deps, cosmwasm_std::Decimal::zero(), now);
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend_usd(1000)).unwrap_err();
+    assert!(matches!(err, ContractError::BadPrice {}));
+
+    // A price older than five minutes is rejected
+    set_price(&mut deps, cosmwasm_std::Decimal::percent(250), now.minus_seconds(301));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend_usd(1000)).unwrap_err();
+    assert!(matches!(err, ContractError::BadPrice {}));
+    assert_eq!(balance(&deps, "owner1"), Uint128::new(600));
+}
+
+/// ## Spendable USD Test
+/// Tests that SpendableUsd values the owner's balance, capped by the spender's
+/// allowance, at the oracle's price.
+#[test]
+fn test_spendable_usd() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with an oracle pricing ucosm at 2.5 micro-USD
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            price_oracle: Some("oracle".to_string()),
+            ..Default::default()
+        }
+    ).unwrap();
+    let updated_at = mock_env().block.time;
+    deps.querier.update_wasm(move |_| {
+        cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(
+            cosmwasm_std::to_json_binary(&PriceResponse { price: cosmwasm_std::Decimal::percent(250), updated_at }).unwrap(),
+        ))
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let spendable_usd = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, spender: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::SpendableUsd {
+            owner: "owner1".to_string(),
+            spender: spender.to_string(),
+        }).unwrap()).unwrap()
+    };
+
+    // An unlimited spender can spend the whole balance: Uint128::new(1000) ucosm = 2500 micro-USD
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    assert_eq!(spendable_usd(&deps, "agent1"), Uint128::new(2500));
+
+    // An allowance of 300 ucosm caps the value at 750 micro-USD
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::IncreaseAllowance { spender: "agent2".to_string(), amount: Uint128::new(300) }).unwrap();
+    assert_eq!(spendable_usd(&deps, "agent2"), Uint128::new(750));
+
+    // An unauthorized spender can spend nothing
+    assert_eq!(spendable_usd(&deps, "stranger"), Uint128::new(0));
+}
+
+/// ## Strict Deposit Test
+/// Tests that a two-coin deposit is rejected by default and, with strict deposits
+/// disabled, credits only the primary denom coin.
+#[test]
+fn test_strict_deposit() {
+    let admin = "admin";
+    let denom = "ucosm";
+    let user = "user";
+    let two_coins = [coin(300, denom), coin(100, "usdt")];
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, denom: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: user.to_string(), denom: Some(denom.to_string()) }).unwrap()).unwrap()
+    };
+
+    // Strict mode (the default) rejects the deposit outright
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info(user, &two_coins), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::MultipleDenoms {}));
+    assert_eq!(balance(&deps, denom), Uint128::new(0));
+
+    // Non-strict mode credits the ucosm coin and ignores the usdt one
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            strict_deposit: Some(false),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(user, &two_coins), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(balance(&deps, denom), Uint128::new(300));
+    assert_eq!(balance(&deps, "usdt"), Uint128::new(0));
+
+    // Without a coin in the primary denom there is nothing to credit
+    let err = execute(deps.as_mut(), mock_env(), mock_info(user, &[coin(5, "uatom"), coin(100, "usdt")]), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidDenom { expected, .. } if expected == denom));
+}
+
+/// ## Oracle Auto-Pause Test
+/// Tests that a failing oracle pauses USD spends when auto-pause is enabled, while
+/// native spends keep working until the admin resumes USD spends.
+#[test]
+fn test_oracle_auto_pause() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with an auto-pausing oracle that is down
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            price_oracle: Some("oracle".to_string()),
+            oracle_auto_pause: Some(true),
+            ..Default::default()
+        }
+    ).unwrap();
+    deps.querier.update_wasm(|_| {
+        cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Err("oracle unavailable".to_string()))
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(2000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend_usd = ExecuteMsg::SpendUsd {
+        owner: "owner1".to_string(),
+        usd_amount: Uint128::new(1000),
+        recipient: "merchant".to_string(),
+    };
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // A sender without an authorization cannot trip the pause
+    let err = execute(deps.as_mut(), mock_env(), mock_info("stranger", &[]), spend_usd.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert!(!config.usd_spends_paused);
+
+    // The failed oracle query spends nothing and pauses USD spends
+    let res = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend_usd.clone()).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "usd_spends_paused" && attr.value == "true"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "spent" && attr.value == "0"));
+    assert_eq!(balance(&deps, "owner1"), Uint128::new(2000));
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert!(config.usd_spends_paused);
+    assert!(!config.paused);
+
+    // Further USD spends are rejected, native spends still work
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend_usd.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::UsdSpendsPaused {}));
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(100),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    assert_eq!(balance(&deps, "agent1"), Uint128::new(100));
+
+    // Only the admin can resume USD spends; once the oracle recovers they go through
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SetUsdSpendsPaused { paused: false }).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetUsdSpendsPaused { paused: false }).unwrap();
+    let updated_at = mock_env().block.time;
+    deps.querier.update_wasm(move |_| {
+        cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(
+            cosmwasm_std::to_json_binary(&PriceResponse { price: cosmwasm_std::Decimal::one(), updated_at }).unwrap(),
+        ))
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend_usd).unwrap();
+    assert_eq!(balance(&deps, "merchant"), Uint128::new(1000));
+}
+
+/// ## New Balance Attribute Test
+/// Tests that deposits, spends and withdrawals report the resulting balances, and
+/// that the reported values match a subsequent Balance query.
+#[test]
+fn test_new_balance_attributes() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with a spend fee, so the owner's debit exceeds the spent amount
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            spend_fee_bps: Some(100),
+            ..Default::default()
+        }
+    ).unwrap();
+    let attr = |res: &cosmwasm_std::Response, key: &str| -> Uint128 {
+        res.attributes.iter().find(|attr| attr.key == key).unwrap().value.parse().unwrap()
+    };
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // Deposit reports the depositor's new balance
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(600, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(400, denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(attr(&res, "new_balance"), Uint128::new(1000));
+    assert_eq!(attr(&res, "new_balance"), balance(&deps, "owner1"));
+
+    // SpendFrom reports both sides, the owner's net of the 1% fee
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(300),
+        secret: None,
+        denom: None,
+    }).unwrap();
+    assert_eq!(attr(&res, "owner_new_balance"), Uint128::new(697));
+    assert_eq!(attr(&res, "owner_new_balance"), balance(&deps, "owner1"));
+    assert_eq!(attr(&res, "spender_new_balance"), balance(&deps, "agent1"));
+
+    // Withdraw reports what is left once, under the same key as deposits
+    let res = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(120), denom: None }).unwrap();
+    assert_eq!(attr(&res, "new_balance"), Uint128::new(180));
+    assert_eq!(attr(&res, "new_balance"), balance(&deps, "agent1"));
+    assert!(!res.attributes.iter().any(|attr| attr.key == "remaining_balance"));
+}
+
+/// ## Authorization Details Test
+/// Tests that the Authorization query returns every term of a grant, and null for
+/// a pair without one.
+#[test]
+fn test_authorization_details() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let details = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, spender: &str| -> Option<AuthorizationDetails> {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Authorization {
+            owner: "owner1".to_string(),
+            spender: spender.to_string(),
+        }).unwrap()).unwrap()
+    };
+
+    // Grant an allowance with an expiry and a per-transaction cap
+    let expires_at_height = mock_env().block.height + 100;
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms {
+            allowance: Some(Uint128::new(500)),
+            expires_at_height: Some(expires_at_height),
+            max_per_tx: Some(Uint128::new(50)),
+            ..Default::default()
+        }),
+        owner: None,
+    }).unwrap();
+
+    let grant = details(&deps, "agent1").unwrap();
+    assert_eq!(grant.owner, "owner1");
+    assert_eq!(grant.spender, "agent1");
+    assert!(grant.active);
+    assert_eq!(grant.allowance, Some(Uint128::new(500)));
+    assert_eq!(grant.expires_at_height, Some(expires_at_height));
+    assert_eq!(grant.max_per_tx, Some(Uint128::new(50)));
+    assert_eq!(grant.starts_at, None);
+    assert_eq!(grant.limit_per_window, None);
+    assert_eq!(grant.refill, None);
+
+    // No grant, no details
+    assert_eq!(details(&deps, "agent2"), None);
+}
+
+/// ## Spender Denoms Test
+/// Tests that an agent restricted to one of two denoms can spend only that denom,
+/// and that an empty list lifts the restriction.
+#[test]
+fn test_spender_denoms() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract accepting ucosm and uatom, and fund owner1 in both
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            additional_denoms: Some(vec!["uatom".to_string()]),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, "uatom")), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = |denom: &str| ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(100),
+        secret: None,
+        denom: Some(denom.to_string()),
+    };
+
+    // Restrict agent1 to uatom
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::SetSpenderDenoms {
+        spender: "agent1".to_string(),
+        denoms: vec!["uatom".to_string()],
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend("uatom")).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(denom)).unwrap_err();
+    assert!(matches!(err, ContractError::DenomNotAllowed { denom: rejected } if rejected == denom));
+
+    // Batches are restricted too
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::BatchSpendFrom {
+        owner: "owner1".to_string(),
+        payouts: vec![("shop".to_string(), Uint128::new(10))],
+        denom: None,
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::DenomNotAllowed { .. }));
+
+    // Denoms the contract does not accept are rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::SetSpenderDenoms {
+        spender: "agent1".to_string(),
+        denoms: vec!["usdt".to_string()],
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidDenom { .. }));
+
+    // An empty list allows every denom again
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::SetSpenderDenoms {
+        spender: "agent1".to_string(),
+        denoms: vec![],
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(denom)).unwrap();
+}
+
+/// ## Index Integrity Test
+/// Tests that IndexIntegrity reports authorizations missing from the reverse index
+/// and reverse entries without an authorization, page by page.
+#[test]
+fn test_index_integrity() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with three authorizations
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    for (owner, spender) in [("owner1", "agent1"), ("owner1", "agent2"), ("owner2", "agent1")] {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }).unwrap();
+    }
+    let check = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, start_after: Option<(String, String)>, limit: u32| -> IndexIntegrityResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::IndexIntegrity { start_after, limit: Some(limit) }).unwrap()).unwrap()
+    };
+
+    // A consistent index reports nothing
+    let response = check(&deps, None, 30);
+    assert!(response.mismatches.is_empty());
+    assert_eq!(response.next_start_after, None);
+
+    // Corrupt the reverse index: drop one entry and add a stray one
+    let addr = cosmwasm_std::Addr::unchecked;
+    SPENDER_INDEX.remove(deps.as_mut().storage, (&addr("agent2"), &addr("owner1")));
+    SPENDER_INDEX.save(deps.as_mut().storage, (&addr("agent3"), &addr("owner3")), &()).unwrap();
+
+    let response = check(&deps, None, 30);
+    assert_eq!(response.mismatches, vec![
+        IndexMismatch::MissingPrimary { owner: "owner3".to_string(), spender: "agent3".to_string() },
+        IndexMismatch::MissingReverse { owner: "owner1".to_string(), spender: "agent2".to_string() },
+    ]);
+
+    // Paging two keys at a time finds the same mismatches
+    let mut mismatches = vec![];
+    let mut start_after = None;
+    loop {
+        let page = check(&deps, start_after, 2);
+        mismatches.extend(page.mismatches);
+        start_after = page.next_start_after;
+        if start_after.is_none() {
+            break;
+        }
+    }
+    assert_eq!(mismatches.len(), 2);
+}
+
+/// ## Withdraw Reply Test
+/// Tests that a failed withdrawal transfer reported through the reply entry point
+/// restores the debited balance, while a successful one leaves it debited.
+#[test]
+fn test_withdraw_reply() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund user1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // The withdrawal is dispatched as a submessage that always replies
+    let res = execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(40), denom: None }).unwrap();
+    let reply_id = res.messages[0].id;
+    assert_eq!(res.messages[0].reply_on, cosmwasm_std::ReplyOn::Always);
+    assert_eq!(balance(&deps), Uint128::new(60));
+
+    // The transfer fails: the balance is restored
+    let res = reply(deps.as_mut(), mock_env(), cosmwasm_std::Reply {
+        id: reply_id,
+        result: cosmwasm_std::SubMsgResult::Err("insufficient funds".to_string()),
+    }).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "action" && attr.value == "withdraw_reverted"));
+    assert_eq!(balance(&deps), Uint128::new(100));
+
+    // The next withdrawal succeeds: the balance stays debited
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(30), denom: None }).unwrap();
+    reply(deps.as_mut(), mock_env(), cosmwasm_std::Reply {
+        id: reply_id,
+        result: cosmwasm_std::SubMsgResult::Ok(cosmwasm_std::SubMsgResponse { events: vec![], data: None }),
+    }).unwrap();
+    assert_eq!(balance(&deps), Uint128::new(70));
+
+    // Unknown reply ids are rejected
+    let err = reply(deps.as_mut(), mock_env(), cosmwasm_std::Reply {
+        id: reply_id + 100,
+        result: cosmwasm_std::SubMsgResult::Err("unexpected".to_string()),
+    });
+    assert!(err.is_err());
+}
+
+/// ## Rebuild Reverse Index Migration Test
+/// Tests that the RebuildReverseIndex migration backfills reverse entries for
+/// authorizations that predate the index, a bounded slice per migration.
+#[test]
+fn test_migrate_rebuild_reverse_index() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with three authorizations, then drop their reverse entries to
+    // mimic state written before the index existed
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let pairs = [("owner1", "agent1"), ("owner2", "agent1"), ("owner3", "agent2")];
+    for (owner, spender) in pairs {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }).unwrap();
+        SPENDER_INDEX.remove(deps.as_mut().storage, (&cosmwasm_std::Addr::unchecked(spender), &cosmwasm_std::Addr::unchecked(owner)));
+    }
+    let owners_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, spender: &str| -> Vec<String> {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::OwnersForSpender {
+            spender: spender.to_string(),
+            start_after: None,
+            limit: None,
+        }).unwrap()).unwrap()
+    };
+    let complete = |res: &cosmwasm_std::Response| -> String {
+        res.attributes.iter().find(|attr| attr.key == "complete").unwrap().value.clone()
+    };
+    assert!(owners_of(&deps, "agent1").is_empty());
+
+    // The first migration covers two authorizations and reports more to do
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::RebuildReverseIndex { limit: Some(2) }).unwrap();
+    assert_eq!(complete(&res), "false");
+    assert_eq!(owners_of(&deps, "agent1"), vec!["owner1", "owner2"]);
+    assert!(owners_of(&deps, "agent2").is_empty());
+
+    // The second one resumes where the first stopped and finishes
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::RebuildReverseIndex { limit: Some(2) }).unwrap();
+    assert_eq!(complete(&res), "true");
+    assert_eq!(owners_of(&deps, "agent2"), vec!["owner3"]);
+
+    // The index now matches the authorization map exactly
+    let integrity: IndexIntegrityResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::IndexIntegrity { start_after: None, limit: None }).unwrap()).unwrap();
+    assert!(integrity.mismatches.is_empty());
+}
+
+/// ## Balance V2 Test
+/// Tests that BalanceV2 names the owner and the configured denom alongside the amount.
+#[test]
+fn test_balance_v2() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund user1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(250, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // The response defaults to the configured denom
+    let response: BalanceResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::BalanceV2 {
+        owner: "user1".to_string(),
+        denom: None,
+    }).unwrap()).unwrap();
+    assert_eq!(response, BalanceResponse { owner: "user1".to_string(), denom: denom.to_string(), amount: Uint128::new(250) });
+
+    // The bare Balance query keeps working
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, response.amount);
+}
+
+/// ## Named Query Responses Test
+/// Tests that the V2 variants of queries returning bare values wrap the same values
+/// in named response structs, including the null and empty cases.
+#[test]
+fn test_v2_query_responses() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Setup contract, fund owner1, authorize agent1 with metadata and appoint a manager
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            withdraw_timelock: Some(WithdrawTimelock { threshold: Uint128::new(100), delay_seconds: 60 }),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(500, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { allowance: Some(Uint128::new(50)), metadata: Some(Binary::from(b"v1")), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AddManager { manager: "manager1".to_string() }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RequestWithdraw { amount: Uint128::new(200), denom: None }).unwrap();
+    let v2 = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, msg: QueryMsg| -> cosmwasm_std::Binary {
+        query(deps.as_ref(), mock_env(), msg).unwrap()
+    };
+    let owner = || "owner1".to_string();
+    let spender = || "agent1".to_string();
+
+    // Authorization queries name the pair and wrap the optional terms
+    let response: IsAuthorizedResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::IsAuthorizedV2 { owner: owner(), spender: spender() })).unwrap();
+    assert_eq!(response, IsAuthorizedResponse { owner: owner(), spender: spender(), authorized: true });
+    let response: AuthorizationResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::AuthorizationV2 { owner: owner(), spender: spender() })).unwrap();
+    assert_eq!(response.authorization.unwrap().allowance, Some(Uint128::new(50)));
+    let response: AuthorizationResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::AuthorizationV2 { owner: owner(), spender: "agent2".to_string() })).unwrap();
+    assert_eq!(response.authorization, None);
+    let response: AgentMetadataResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::AgentMetadataV2 { owner: owner(), spender: spender() })).unwrap();
+    assert_eq!(response.metadata, Some(Binary::from(b"v1")));
+    let response: EffectiveAllowanceResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::EffectiveAllowanceV2 { owner: owner(), spender: spender() })).unwrap();
+    assert_eq!(response.allowance, Some(Uint128::new(50)));
+
+    // Account queries wrap their values
+    let response: NonceResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::NonceV2 { owner: owner() })).unwrap();
+    assert_eq!(response, NonceResponse { owner: owner(), nonce: 0 });
+    let response: LastActivityResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::LastActivityV2 { owner: owner() })).unwrap();
+    assert_eq!(response.height, Some(mock_env().block.height));
+    let response: LastActivityResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::LastActivityV2 { owner: "nobody".to_string() })).unwrap();
+    assert_eq!(response.height, None);
+    let response: DepositResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::DepositV2 { id: 1 })).unwrap();
+    assert_eq!(response.deposit.unwrap().amount, Uint128::new(500));
+    let response: DepositResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::DepositV2 { id: 2 })).unwrap();
+    assert_eq!(response.deposit, None);
+    let response: WithdrawReadyAtResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::WithdrawReadyAtV2 { owner: owner() })).unwrap();
+    assert_eq!(response.ready_at, Some(mock_env().block.time.plus_seconds(60)));
+
+    // List queries wrap their pages
+    let response: OwnersForSpenderResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::OwnersForSpenderV2 { spender: spender(), start_after: None, limit: None })).unwrap();
+    assert_eq!(response.owners, vec![owner()]);
+    let response: ManagersResponse = cosmwasm_std::from_json(v2(&deps, QueryMsg::ManagersV2 { owner: owner(), start_after: None, limit: None })).unwrap();
+    assert_eq!(response.managers, vec!["manager1".to_string()]);
+}
+
+/// ## Minimal Events Test
+/// Tests that with minimal events enabled, SpendFrom responses carry only the action
+/// and a hashed reference, with no raw addresses or amounts.
+#[test]
+fn test_minimal_events() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract with minimal events, fund owner1 and authorize agent1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            minimal_events: Some(true),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFrom {
+        owner: "owner1".to_string(),
+        amount: Uint128::new(123),
+        secret: None,
+        denom: None,
+    }).unwrap();
+
+    // Only the action and the reference are emitted
+    assert!(res.events.is_empty());
+    let keys: Vec<&str> = res.attributes.iter().map(|attr| attr.key.as_str()).collect();
+    assert_eq!(keys, vec!["action", "ref"]);
+    for attr in &res.attributes {
+        assert!(!attr.value.contains("owner1"));
+        assert!(!attr.value.contains("agent1"));
+        assert!(!attr.value.contains("123"));
+    }
+
+    // The reference is the hash of the spend's details
+    let preimage = format!("owner1:agent1:{}:123:{}", denom, mock_env().block.height);
+    assert_eq!(res.attributes[1].value, Binary::from(&Sha256::digest(preimage.as_bytes())[..]).to_base64());
+
+    // The spend itself happened as usual
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "agent1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(123));
+
+    // Paying an outside wallet is just as terse, apart from the bank transfer itself
+    let res = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFromTo {
+        owner: "owner1".to_string(),
+        recipient: "vendor".to_string(),
+        amount: Uint128::new(45),
+    }).unwrap();
+    assert!(res.events.is_empty());
+    let keys: Vec<&str> = res.attributes.iter().map(|attr| attr.key.as_str()).collect();
+    assert_eq!(keys, vec!["action", "ref"]);
+    assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "vendor".to_string(), amount: coins(45, denom) }));
+}
+
+/// ## Owner Available Test
+/// Tests that OwnerAvailable reports the whole balance as spendable by the owner,
+/// and nothing while the owner is frozen.
+#[test]
+fn test_owner_available() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund owner1
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(500, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let available = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> OwnerAvailableResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::OwnerAvailable { owner: "owner1".to_string() }).unwrap()).unwrap()
+    };
+
+    // Nothing is locked
+    assert_eq!(available(&deps), OwnerAvailableResponse {
+        total: Uint128::new(500),
+        spendable_by_owner: Uint128::new(500),
+        locked_reserve: Uint128::new(0),
+        locked_pending: Uint128::new(0),
+        savings: Uint128::new(0),
+    });
+
+    // A freeze locks the whole balance
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::FreezeAccount { account: "owner1".to_string() }).unwrap();
+    let breakdown = available(&deps);
+    assert_eq!(breakdown.total, Uint128::new(500));
+    assert_eq!(breakdown.spendable_by_owner, Uint128::new(0));
+}
+
+/// ## Spend From To Test
+/// Tests that an authorized spender can pay a third party, with the bank transfer
+/// targeting the recipient and the owner debited.
+#[test]
+fn test_spend_from_to() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract, fund owner1 and authorize agent1 with an allowance
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::IncreaseAllowance { spender: "agent1".to_string(), amount: Uint128::new(500) }).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+    let pay = |amount: u128| ExecuteMsg::SpendFromTo {
+        owner: "owner1".to_string(),
+        recipient: "vendor".to_string(),
+        amount: Uint128::new(amount),
+    };
+
+    // agent1 pays the vendor, which is neither the owner nor the spender
+    let res = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), pay(300)).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "vendor".to_string(), amount: coins(300, denom) }));
+    assert!(res.attributes.iter().any(|attr| attr.key == "owner_new_balance" && attr.value == "700"));
+    assert_eq!(balance(&deps, "owner1"), Uint128::new(700));
+    assert_eq!(balance(&deps, "agent1"), Uint128::new(0));
+    assert_eq!(balance(&deps, "vendor"), Uint128::new(0));
+
+    // The payment counts against the allowance
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), pay(201)).unwrap_err();
+    assert!(matches!(err, ContractError::AllowanceExceeded { remaining } if remaining == Uint128::new(200)));
+
+    // Unauthorized senders cannot pay from the owner
+    let err = execute(deps.as_mut(), mock_env(), mock_info("stranger", &[]), pay(1)).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+}
+
+/// ## Seed Sub-Account Test
+/// Tests that seeding a sub-account moves the balance and authorizes the spender
+/// in one step, and that accounts already in use cannot be seeded.
+#[test]
+fn test_seed_sub_account() {
+    let mut deps = mock_dependencies();
+
+    // Setup contract and fund the parent account
+    let admin = "admin";
+    let denom = "ucosm";
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("parent", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user2", &coins(10, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // Seed sub1 with 400 and let agent1 spend up to 250 of it
+    execute(deps.as_mut(), mock_env(), mock_info("parent", &[]), ExecuteMsg::SeedSubAccount {
+        sub_account: "sub1".to_string(),
+        amount: Uint128::new(400),
+        spender: Some("agent1".to_string()),
+        limit: Uint128::new(250),
+    }).unwrap();
+    assert_eq!(balance(&deps, "parent"), Uint128::new(600));
+    assert_eq!(balance(&deps, "sub1"), Uint128::new(400));
+    let allowance: AllowanceInfo = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Allowance {
+        owner: "sub1".to_string(),
+        spender: "agent1".to_string(),
+    }).unwrap()).unwrap();
+    assert_eq!(allowance.amount, Uint128::new(250));
+
+    // Without a named spender the parent itself is authorized
+    execute(deps.as_mut(), mock_env(), mock_info("parent", &[]), ExecuteMsg::SeedSubAccount {
+        sub_account: "sub2".to_string(),
+        amount: Uint128::new(100),
+        spender: None,
+        limit: Uint128::new(100),
+    }).unwrap();
+    let authorized: bool = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::IsAuthorized {
+        owner: "sub2".to_string(),
+        spender: "parent".to_string(),
+    }).unwrap()).unwrap();
+    assert!(authorized);
+
+    // An account someone already uses cannot be seeded, and nothing moves
+    let err = execute(deps.as_mut(), mock_env(), mock_info("parent", &[]), ExecuteMsg::SeedSubAccount {
+        sub_account: "user2".to_string(),
+        amount: Uint128::new(100),
+        spender: None,
+        limit: Uint128::new(100),
+    });
+    assert!(err.is_err());
+    assert_eq!(balance(&deps, "parent"), Uint128::new(500));
+    assert_eq!(balance(&deps, "user2"), Uint128::new(10));
+
+    // A limit above the seeded amount is rejected, so a seed cannot claim an address
+    execute(deps.as_mut(), mock_env(), mock_info("attacker", &coins(1, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), ExecuteMsg::SeedSubAccount {
+        sub_account: "victim".to_string(),
+        amount: Uint128::new(1),
+        spender: None,
+        limit: Uint128::MAX,
+    });
+    assert!(err.is_err());
+
+    // The seeder cannot spend anything the sub-account deposits later
+    execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), ExecuteMsg::SeedSubAccount {
+        sub_account: "victim".to_string(),
+        amount: Uint128::new(1),
+        spender: None,
+        limit: Uint128::new(1),
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("victim", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let spend = |amount: u128| ExecuteMsg::SpendFrom { owner: "victim".to_string(), amount: Uint128::new(amount), secret: None, denom: None };
+    assert!(execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), spend(2)).is_err());
+    execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), spend(1)).unwrap();
+    assert!(execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), spend(1)).is_err());
+    assert_eq!(balance(&deps, "victim"), Uint128::new(1000));
+}
+
+/// ## Denom Aliases Test
+/// Tests that a deposit in an alias of the primary denom is credited to the
+/// primary denom balance, and that unknown denoms are still rejected.
+#[test]
+fn test_denom_aliases() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    let alias = "transfer/channel-0/uatom";
+    let user = "user";
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, denom: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: user.to_string(), denom: Some(denom.to_string()) }).unwrap()).unwrap()
+    };
+
+    // Instantiate with the ibc/ hash as primary denom and its path form as an alias
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            denom_aliases: Some(vec![alias.to_string()]),
+            ..Default::default()
+        }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.denom_aliases, vec![alias.to_string()]);
+
+    // Depositing under the alias credits the canonical denom
+    let res = execute(deps.as_mut(), mock_env(), mock_info(user, &coins(400, alias)), ExecuteMsg::Deposit {}).unwrap();
+    assert!(res.events[0].attributes.iter().any(|a| a.key == "denom" && a.value == denom));
+    execute(deps.as_mut(), mock_env(), mock_info(user, &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(balance(&deps, denom), Uint128::new(500));
+    assert_eq!(balance(&deps, alias), Uint128::new(0));
+
+    // A denom that is neither primary nor an alias is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info(user, &coins(100, "uatom")), ExecuteMsg::Deposit {}).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidDenom { .. }));
+}
+
+/// ## Spender Cap Test
+/// Tests that an owner can authorize spenders up to `max_spenders_per_owner`, is
+/// rejected beyond it, and regains a slot after revoking one.
+#[test]
+fn test_max_spenders_per_owner() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+    let owner = "owner";
+
+    // Instantiate with a cap of three spenders per owner
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            max_spenders_per_owner: Some(3),
+            ..Default::default()
+        }
+    ).unwrap();
+
+    // Authorizing up to the cap succeeds, and re-authorizing an existing spender does not count twice
+    for spender in ["agent1", "agent2", "agent3", "agent3"] {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }).unwrap();
+    }
+
+    // The next new spender is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: "agent4".to_string(), terms: None, owner: None }).unwrap_err();
+    assert!(matches!(err, ContractError::TooManySpenders { max: 3 }));
+
+    // The cap is per owner
+    execute(deps.as_mut(), mock_env(), mock_info("other", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent4".to_string(), terms: None, owner: None }).unwrap();
+
+    // Revoking a spender frees a slot
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: None }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: "agent4".to_string(), terms: None, owner: None }).unwrap();
+
+    // Revoking every spender resets the count entirely
+    execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::RevokeAllSpenders {}).unwrap();
+    for spender in ["agent5", "agent6", "agent7"] {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None }).unwrap();
+    }
+}
+
+/// ## Amount Serialization Test
+/// Tests that amounts cross the JSON boundary as strings, so clients limited to
+/// 53-bit numbers cannot lose precision on large balances.
+#[test]
+fn test_amounts_serialize_as_strings() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+    let large = 9_007_199_254_740_993u128; // 2^53 + 1, not representable as an f64
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+
+    // A message amount is encoded as a string and decodes from one
+    let msg = ExecuteMsg::Withdraw { amount: Uint128::new(large), denom: None };
+    let json = String::from_utf8(cosmwasm_std::to_json_vec(&msg).unwrap()).unwrap();
+    assert_eq!(json, r#"{"withdraw":{"amount":"9007199254740993","denom":null}}"#);
+    let decoded: ExecuteMsg = cosmwasm_std::from_json(json.as_bytes()).unwrap();
+    assert_eq!(decoded, msg);
+
+    // A balance above 2^53 is returned exactly, as a string
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(large, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let raw = query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "user1".to_string(), denom: None }).unwrap();
+    assert_eq!(raw.as_slice(), br#""9007199254740993""#);
+}
+
+/// ## Spend Policy Test
+/// Tests that a configured policy contract is consulted on delegated spends and
+/// can deny one the contract itself would allow, while self-spends skip it, until
+/// the admin clears it.
+#[test]
+fn test_spend_policy() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with a policy contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            policy: Some("policy".to_string()),
+            ..Default::default()
+        }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.policy, Some("policy".to_string()));
+
+    // The mock policy allows spends of at most 100
+    deps.querier.update_wasm(|query| {
+        let cosmwasm_std::WasmQuery::Smart { msg, .. } = query else { panic!("unexpected query") };
+        let PolicyQueryMsg::CheckSpend { amount, .. } = cosmwasm_std::from_json(msg).unwrap();
+        let response = if amount <= Uint128::new(100) {
+            PolicyResponse { allowed: true, reason: None }
+        } else {
+            PolicyResponse { allowed: false, reason: Some("over treasury limit".to_string()) }
+        };
+        cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(cosmwasm_std::to_json_binary(&response).unwrap()))
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = |amount: u128| ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(amount), secret: None, denom: None };
+
+    // A spend within the policy goes through
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(100)).unwrap();
+
+    // A larger one is denied with the policy's reason
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(150)).unwrap_err();
+    assert!(matches!(err, ContractError::PolicyDenied { reason } if reason == "over treasury limit"));
+
+    // The owner spending their own balance is not subject to the policy
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), spend(150)).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(900));
+
+    // The policy cannot be set and cleared in the same update
+    let err = execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UpdateParams { params: ParamUpdate {
+        policy: Some("policy2".to_string()),
+        clear: Some(vec![ClearableParam::Policy]),
+        ..Default::default()
+    } }).unwrap_err();
+    assert!(matches!(err, ContractError::ConflictingConfig { .. }));
+
+    // Once the admin clears the policy, the larger spend is no longer denied
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UpdateParams { params: ParamUpdate {
+        clear: Some(vec![ClearableParam::Policy]),
+        ..Default::default()
+    } }).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.policy, None);
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(150)).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(750));
+}
+
+/// ## Can Spend Test
+/// Tests that the CanSpend precheck allows a valid spend and reports each reason a
+/// SpendFrom would fail, without changing any state.
+#[test]
+fn test_can_spend() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let expiry = mock_env().block.height + 10;
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(500, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { allowance: Some(Uint128::new(300)), expires_at_height: Some(expiry), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+    let can_spend = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: cosmwasm_std::Env, spender: &str, amount: u128| -> CanSpendResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), env, QueryMsg::CanSpend {
+            owner: "owner1".to_string(),
+            spender: spender.to_string(),
+            amount: Uint128::new(amount),
+        }).unwrap()).unwrap()
+    };
+    let denied = |response: CanSpendResponse| -> String {
+        assert!(!response.allowed);
+        response.reason.unwrap()
+    };
+
+    // A spend within the allowance and balance is allowed
+    assert_eq!(can_spend(&deps, mock_env(), "agent1", 300), CanSpendResponse { allowed: true, reason: None });
+
+    // Each failing check is reported
+    assert_eq!(denied(can_spend(&deps, mock_env(), "stranger", 10)), "Unauthorized");
+    assert_eq!(denied(can_spend(&deps, mock_env(), "agent1", 301)), "Spend exceeds the allowance: 300 remaining");
+    let mut expired = mock_env();
+    expired.block.height = expiry;
+    assert_eq!(denied(can_spend(&deps, expired, "agent1", 10)), "Authorization expired");
+
+    // The owner's balance must cover the spend
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(450), denom: None }).unwrap();
+    assert_eq!(denied(can_spend(&deps, mock_env(), "agent1", 100)), "Insufficient balance: have 50, need 100");
+
+    // Freezing the owner or pausing the contract blocks the spend
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::FreezeAccount { account: "owner1".to_string() }).unwrap();
+    assert_eq!(denied(can_spend(&deps, mock_env(), "agent1", 10)), "Account owner1 is frozen");
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UnfreezeAccount { account: "owner1".to_string() }).unwrap();
+    sudo(deps.as_mut(), mock_env(), SudoMsg::ForcePause {}).unwrap();
+    assert_eq!(denied(can_spend(&deps, mock_env(), "agent1", 10)), "Contract is paused");
+    sudo(deps.as_mut(), mock_env(), SudoMsg::ForceUnpause {}).unwrap();
+    assert!(can_spend(&deps, mock_env(), "agent1", 10).allowed);
+}
+
+/// ## Denom Conversion Test
+/// Tests that after the admin announces a new denom, an account can opt in to
+/// converting its primary denom balance once, while others keep the old denom.
+#[test]
+fn test_convert_balance() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+    let new_denom = "ucosm2";
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str, denom: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: Some(denom.to_string()) }).unwrap()).unwrap()
+    };
+
+    // Instantiate the contract and fund two accounts
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(400, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user2", &coins(300, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // Nothing can be converted before a new denom is pending
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::ConvertBalance {}).unwrap_err();
+
+    // Only the admin can announce the new denom
+    let set_pending = ExecuteMsg::SetPendingDenom { denom: new_denom.to_string(), ratio: cosmwasm_std::Decimal::one() };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), set_pending.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), set_pending).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.pending_denom.unwrap().denom, new_denom);
+    assert!(config.accepted_denoms.contains(&new_denom.to_string()));
+
+    // Converting at 1:1 moves the whole balance into the new denom
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::ConvertBalance {}).unwrap();
+    assert_eq!(balance(&deps, "user1", denom), Uint128::zero());
+    assert_eq!(balance(&deps, "user1", new_denom), Uint128::new(400));
+
+    // An account converts only once; accounts that did not opt in are untouched
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::ConvertBalance {}).unwrap_err();
+    assert_eq!(balance(&deps, "user2", denom), Uint128::new(300));
+    assert_eq!(balance(&deps, "user2", new_denom), Uint128::zero());
+}
+
+/// ## Unconverted Accounts Test
+/// Tests that during a denom migration only accounts still holding the old denom
+/// without having converted are listed.
+#[test]
+fn test_unconverted_accounts() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+    let unconverted = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Vec<BalanceEntry> {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::UnconvertedAccounts { start_after: None, limit: None }).unwrap()).unwrap()
+    };
+
+    // Instantiate the contract and fund two accounts
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(400, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user2", &coins(300, denom)), ExecuteMsg::Deposit {}).unwrap();
+
+    // Without a pending denom there is nothing to convert
+    assert!(unconverted(&deps).is_empty());
+
+    // Once a denom is pending and user1 converts, only user2 is listed
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetPendingDenom {
+        denom: "ucosm2".to_string(),
+        ratio: cosmwasm_std::Decimal::one(),
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("user1", &[]), ExecuteMsg::ConvertBalance {}).unwrap();
+    assert_eq!(unconverted(&deps), vec![BalanceEntry { owner: "user2".to_string(), balance: Uint128::new(300) }]);
+}
+
+/// ## Global Spend Cap Test
+/// Tests that delegated spends across all owners stop at the global cap for the
+/// epoch, resume once the epoch rolls over, and stay capped under a maximal epoch.
+#[test]
+fn test_global_spend_cap() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate without a cap, then set one of 300 per hour
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let global_spend_cap = GlobalSpendCap { cap: Uint128::new(300), epoch_seconds: 3600 };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateParams { params: ParamUpdate { global_spend_cap: Some(global_spend_cap.clone()), ..Default::default() } }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.global_spend_cap, Some(global_spend_cap));
+
+    // Two owners each fund an account and authorize their own agent
+    for (owner, agent) in [("owner1", "agent1"), ("owner2", "agent2")] {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), ExecuteMsg::AuthorizeSpender { spender: agent.to_string(), terms: None, owner: None }).unwrap();
+    }
+    let spend = |owner: &str, amount: u128| ExecuteMsg::SpendFrom { owner: owner.to_string(), amount: Uint128::new(amount), secret: None, denom: None };
+
+    // Spends from both owners count towards the same cap
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend("owner1", 200)).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent2", &[]), spend("owner2", 100)).unwrap();
+
+    // The cap is reached, so any further delegated spend is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend("owner1", 1)).unwrap_err();
+    assert!(matches!(err, ContractError::GlobalCapExceeded { remaining } if remaining.is_zero()));
+    let check: CanSpendResponse = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CanSpend { owner: "owner2".to_string(), spender: "agent2".to_string(), amount: Uint128::new(1) }
+    ).unwrap()).unwrap();
+    assert!(!check.allowed);
+
+    // Once the epoch has elapsed the cap resets
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3600);
+    execute(deps.as_mut(), env.clone(), mock_info("agent1", &[]), spend("owner1", 300)).unwrap();
+    let err = execute(deps.as_mut(), env.clone(), mock_info("agent2", &[]), spend("owner2", 1)).unwrap_err();
+    assert!(matches!(err, ContractError::GlobalCapExceeded { .. }));
+
+    // An epoch that never ends keeps counting the 300 already spent rather than overflowing
+    let endless_cap = GlobalSpendCap { cap: Uint128::new(450), epoch_seconds: u64::MAX };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateParams { params: ParamUpdate { global_spend_cap: Some(endless_cap), ..Default::default() } }
+    ).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info("agent1", &[]), spend("owner1", 100)).unwrap();
+    execute(deps.as_mut(), env.clone(), mock_info("agent2", &[]), spend("owner2", 50)).unwrap();
+    let err = execute(deps.as_mut(), env, mock_info("agent2", &[]), spend("owner2", 1)).unwrap_err();
+    assert!(matches!(err, ContractError::GlobalCapExceeded { remaining } if remaining.is_zero()));
+}
+
+/// ## Snapshot Not Implemented Test
+/// Tests that the planned Snapshot message is rejected with NotImplemented until
+/// it gets a real handler.
+#[test]
+fn test_snapshot_not_implemented() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+
+    // Snapshot is a roadmap stub
+    let err = execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::Snapshot {}).unwrap_err();
+    assert!(matches!(err, ContractError::NotImplemented {}));
+}
+
+/// ## Spend Precision Test
+/// Tests that SpendFrom rejects amounts finer than the precision configured for
+/// the denom, while denoms without a configured precision accept any amount.
+#[test]
+fn test_spend_precision() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with ucosm spends limited to whole hundredths of a cosm
+    let denom_precision = DenomPrecision { denom: denom.to_string(), decimals: 6, precision: 2 };
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            decimals: 6,
+            additional_denoms: Some(vec!["uatom".to_string()]),
+            denom_precisions: Some(vec![denom_precision.clone()]),
+            ..Default::default()
+        }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.denom_precisions, vec![denom_precision]);
+
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(100_000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(100_000, "uatom")), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = |amount: u128, denom: &str| ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(amount), secret: None, denom: Some(denom.to_string()) };
+
+    // A multiple of 10^4 ucosm is within precision
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(30_000, denom)).unwrap();
+
+    // A finer amount is rejected
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(30_001, denom)).unwrap_err();
+    assert!(matches!(err, ContractError::PrecisionExceeded {}));
+
+    // uatom has no configured precision
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(30_001, "uatom")).unwrap();
+
+    // Paying out, batching and prechecking enforce the same precision
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFromTo {
+        owner: "owner1".to_string(),
+        recipient: "vendor".to_string(),
+        amount: Uint128::new(10_001),
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::PrecisionExceeded {}));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::BatchSpendFrom {
+        owner: "owner1".to_string(),
+        payouts: vec![("vendor".to_string(), Uint128::new(5_001)), ("vendor".to_string(), Uint128::new(4_999))],
+        denom: None,
+    }).unwrap_err();
+    assert!(matches!(err, ContractError::PrecisionExceeded {}));
+    let can_spend = |amount: u128| -> CanSpendResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::CanSpend {
+            owner: "owner1".to_string(),
+            spender: "agent1".to_string(),
+            amount: Uint128::new(amount),
+        }).unwrap()).unwrap()
+    };
+    assert_eq!(can_spend(10_000), CanSpendResponse { allowed: true, reason: None });
+    assert_eq!(can_spend(10_001).reason.unwrap(), ContractError::PrecisionExceeded {}.to_string());
+
+    // A precision above the denom's decimals is rejected at instantiation
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            decimals: 2,
+            denom_precisions: Some(vec![DenomPrecision { denom: denom.to_string(), decimals: 2, precision: 6 }]),
+            ..Default::default()
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+}
+
+/// ## Spending Blackout Test
+/// Tests that delegated spends are rejected inside an admin-configured blackout
+/// window and allowed outside it, while owners can still spend their own balance.
+#[test]
+fn test_spending_blackout() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = || ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(100), secret: None, denom: None };
+
+    // Block delegated spending for the hour starting an hour from now
+    let start = mock_env().block.time.plus_seconds(3600);
+    let window = BlackoutWindow { start, end: start.plus_seconds(3600) };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SetSpendingBlackouts { windows: vec![window.clone()] }).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetSpendingBlackouts { windows: vec![window.clone()] }).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.spending_blackouts, vec![window]);
+
+    // Before the window the spend goes through
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend()).unwrap();
+
+    // Inside the window it is rejected, but the owner can still spend
+    let mut env = mock_env();
+    env.block.time = start.plus_seconds(60);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("agent1", &[]), spend()).unwrap_err();
+    assert!(matches!(err, ContractError::SpendingBlackout {}));
+    execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), spend()).unwrap();
+
+    // Once the window ends delegated spending resumes
+    env.block.time = start.plus_seconds(3600);
+    execute(deps.as_mut(), env, mock_info("agent1", &[]), spend()).unwrap();
+}
+
+/// ## Stats Test
+/// Tests that the Stats query reports lifetime totals deposited and spent, counting
+/// single, batch and paid-out delegated spends.
+#[test]
+fn test_stats() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let stats: StatsResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats, StatsResponse { total_deposited: Uint128::zero(), total_spent: Uint128::zero() });
+
+    // Deposit 1000 and let an agent spend 300 of it
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("agent1", &[]),
+        ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(300), secret: None, denom: None }
+    ).unwrap();
+
+    let stats: StatsResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats.total_deposited, Uint128::new(1000));
+    assert_eq!(stats.total_spent, Uint128::new(300));
+
+    // Batch spends and direct payouts count too, while the owner's own spends do not
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::BatchSpendFrom {
+        owner: "owner1".to_string(),
+        payouts: vec![("vendor1".to_string(), Uint128::new(100)), ("vendor2".to_string(), Uint128::new(50))],
+        denom: None,
+    }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), ExecuteMsg::SpendFromTo {
+        owner: "owner1".to_string(),
+        recipient: "vendor3".to_string(),
+        amount: Uint128::new(25),
+    }).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner1", &[]),
+        ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(10), secret: None, denom: None }
+    ).unwrap();
+    let stats: StatsResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats.total_spent, Uint128::new(475));
+}
+
+/// ## Next Spend Window Test
+/// Tests that NextSpendWindow returns the current time outside a blackout and the
+/// blackout's end inside one, following back-to-back windows.
+#[test]
+fn test_next_spend_window() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let next_spend_window = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: cosmwasm_std::Env| -> cosmwasm_std::Timestamp {
+        cosmwasm_std::from_json(query(deps.as_ref(), env, QueryMsg::NextSpendWindow {}).unwrap()).unwrap()
+    };
+
+    // Without blackouts spending is permitted now
+    assert_eq!(next_spend_window(&deps, mock_env()), mock_env().block.time);
+
+    // An hour-long blackout starting an hour from now
+    let start = mock_env().block.time.plus_seconds(3600);
+    let window = BlackoutWindow { start, end: start.plus_seconds(3600) };
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetSpendingBlackouts { windows: vec![window.clone()] }).unwrap();
+    assert_eq!(next_spend_window(&deps, mock_env()), mock_env().block.time);
+
+    // During the blackout the next window opens at its end
+    let mut env = mock_env();
+    env.block.time = start.plus_seconds(60);
+    assert_eq!(next_spend_window(&deps, env.clone()), window.end);
+
+    // A second blackout starting right as the first ends pushes it further out
+    let following = BlackoutWindow { start: window.end, end: window.end.plus_seconds(600) };
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::SetSpendingBlackouts { windows: vec![following.clone(), window] }).unwrap();
+    assert_eq!(next_spend_window(&deps, env), following.end);
+}
+
+/// ## Authorization Audit Attributes Test
+/// Tests that authorizing and revoking a spender emit the full audit attribute set,
+/// with `previous_state` reflecting whether the spender was already authorized.
+#[test]
+fn test_authorization_audit_attributes() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let attribute = |res: &cosmwasm_std::Response, key: &str| -> String {
+        res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+    };
+    let authorize = || ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None };
+
+    // First authorization: the spender was not authorized before
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize()).unwrap();
+    assert_eq!(attribute(&res, "action"), "authorize_spender");
+    assert_eq!(attribute(&res, "owner"), "owner1");
+    assert_eq!(attribute(&res, "spender"), "agent1");
+    assert_eq!(attribute(&res, "previous_state"), "false");
+    assert_eq!(attribute(&res, "new_state"), "true");
+    assert_eq!(attribute(&res, "block_height"), mock_env().block.height.to_string());
+
+    // Re-authorizing an already authorized spender
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize()).unwrap();
+    assert_eq!(attribute(&res, "previous_state"), "true");
+    assert_eq!(attribute(&res, "new_state"), "true");
+
+    // Revoking records the transition back to unauthorized
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: None }).unwrap();
+    assert_eq!(attribute(&res, "action"), "revoke_spender");
+    assert_eq!(attribute(&res, "previous_state"), "true");
+    assert_eq!(attribute(&res, "new_state"), "false");
+    assert_eq!(attribute(&res, "block_height"), mock_env().block.height.to_string());
+
+    // Creating an authorization through IncreaseAllowance is audited as a grant
+    let increase = ExecuteMsg::IncreaseAllowance { spender: "agent1".to_string(), amount: Uint128::new(10) };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), increase).unwrap();
+    assert_eq!(attribute(&res, "previous_state"), "false");
+    assert_eq!(attribute(&res, "new_state"), "true");
+
+    // Decreasing it to zero is audited as a removal
+    let decrease = ExecuteMsg::DecreaseAllowance { spender: "agent1".to_string(), amount: Uint128::new(10) };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), decrease).unwrap();
+    assert_eq!(attribute(&res, "previous_state"), "true");
+    assert_eq!(attribute(&res, "new_state"), "false");
+
+    // AuthorizeWithDeposit carries the audit attributes alongside the deposit
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(100, "ucosm")), ExecuteMsg::AuthorizeWithDeposit { spender: "agent1".to_string() }).unwrap();
+    assert_eq!(attribute(&res, "previous_state"), "false");
+    assert_eq!(attribute(&res, "new_state"), "true");
+    assert_eq!(attribute(&res, "block_height"), mock_env().block.height.to_string());
+
+    // Revoking several spenders emits one audit event per pair
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent2".to_string(), terms: None, owner: None }).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RevokeAllSpenders {}).unwrap();
+    let audits: Vec<_> = res.events.iter().filter(|event| event.ty == "authorization_audit").collect();
+    assert_eq!(audits.len(), 2);
+    for (event, spender) in audits.iter().zip(["agent1", "agent2"]) {
+        let event_attribute = |key: &str| event.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone();
+        assert_eq!(event_attribute("owner"), "owner1");
+        assert_eq!(event_attribute("spender"), spender);
+        assert_eq!(event_attribute("previous_state"), "true");
+        assert_eq!(event_attribute("new_state"), "false");
+        assert_eq!(event_attribute("block_height"), mock_env().block.height.to_string());
+    }
+
+    // An admin ban audits every owner it revokes the spender from
+    for owner in ["owner1", "owner2"] {
+        execute(deps.as_mut(), mock_env(), mock_info(owner, &[]), authorize()).unwrap();
+    }
+    let res = execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::GlobalRevokeSpender { spender: "agent1".to_string(), limit: None }).unwrap();
+    assert_eq!(res.events.iter().filter(|event| event.ty == "authorization_audit").count(), 2);
+}
+
+/// ## Withdraw Timelock Test
+/// Tests that withdrawals above the timelock threshold must be requested, cannot be
+/// claimed before the delay has passed and pay out once it has, while smaller
+/// withdrawals stay immediate.
+#[test]
+fn test_withdraw_timelock() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with withdrawals above 500 delayed by a day
+    let withdraw_timelock = WithdrawTimelock { threshold: Uint128::new(500), delay_seconds: 86_400 };
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            withdraw_timelock: Some(withdraw_timelock.clone()),
+            ..Default::default()
+        }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.withdraw_timelock, Some(withdraw_timelock));
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(2000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let ready_at = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> Option<cosmwasm_std::Timestamp> {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::WithdrawReadyAt { owner: "owner1".to_string() }).unwrap()).unwrap()
+    };
+    assert_eq!(ready_at(&deps), None);
+
+    // A small withdrawal still pays out immediately
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(500), denom: None }).unwrap();
+    assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "owner1".to_string(), amount: coins(500, denom) }));
+
+    // A large one must be requested instead
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::Withdraw { amount: Uint128::new(1000), denom: None }).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { threshold } if threshold == Uint128::new(500)));
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RequestWithdraw { amount: Uint128::new(1000), denom: None }).unwrap();
+    let unlock_time = mock_env().block.time.plus_seconds(86_400);
+    assert_eq!(ready_at(&deps), Some(unlock_time));
+
+    // Claiming before the delay has passed fails and debits nothing
+    let mut env = mock_env();
+    env.block.time = unlock_time.minus_seconds(1);
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), ExecuteMsg::ClaimWithdraw {}).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawLocked { unlock_time: t } if t == unlock_time));
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(1500));
+
+    // Once the delay has passed the claim sends the tokens
+    env.block.time = unlock_time;
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), ExecuteMsg::ClaimWithdraw {}).unwrap();
+    assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: "owner1".to_string(), amount: coins(1000, denom) }));
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(500));
+    assert_eq!(ready_at(&deps), None);
+
+    // The request is consumed, so it cannot be claimed twice
+    let err = execute(deps.as_mut(), env, mock_info("owner1", &[]), ExecuteMsg::ClaimWithdraw {}).unwrap_err();
+    assert!(matches!(err, ContractError::NoPendingWithdrawal {}));
+}
+
+/// ## Withdraw Timelock Outflows Test
+/// Tests that the timelock threshold caps an account's immediate outflows over a
+/// window, so neither split withdrawals, SpendFromTo payouts nor moving the balance
+/// into other accounts first get around it.
+#[test]
+fn test_withdraw_timelock_outflows() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with immediate outflows capped at 500 per day
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            withdraw_timelock: Some(WithdrawTimelock { threshold: Uint128::new(500), delay_seconds: 86_400 }),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(2000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let withdraw = |amount: u128| ExecuteMsg::Withdraw { amount: Uint128::new(amount), denom: None };
+    let pay = |amount: u128| ExecuteMsg::SpendFromTo {
+        owner: "owner1".to_string(),
+        recipient: "attacker".to_string(),
+        amount: Uint128::new(amount),
+    };
+
+    // The owner cannot pay out the whole balance to an outside wallet at once
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), pay(2000)).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { threshold } if threshold == Uint128::new(500)));
+
+    // Splitting the outflow counts every part against the same window
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), withdraw(300)).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), pay(200)).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), withdraw(1)).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { .. }));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), pay(1)).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { .. }));
+
+    // A new window allows immediate outflows again
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86_400);
+    execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), withdraw(500)).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(1000));
+
+    // Moving the balance into other accounts first counts against the same window
+    env.block.time = env.block.time.plus_seconds(86_400);
+    let spend = ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(300), secret: None, denom: None };
+    execute(deps.as_mut(), env.clone(), mock_info("agent1", &[]), spend).unwrap();
+    let refund = |amount: u128| ExecuteMsg::Refund { owner: "mule1".to_string(), amount: Uint128::new(amount), denom: None };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), refund(201)).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { .. }));
+    execute(deps.as_mut(), env.clone(), mock_info("owner1", &[]), refund(200)).unwrap();
+    let batch = ExecuteMsg::BatchSpendFrom { owner: "owner1".to_string(), payouts: vec![("mule2".to_string(), Uint128::new(1))], denom: None };
+    let err = execute(deps.as_mut(), env.clone(), mock_info("agent1", &[]), batch).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { .. }));
+    let seed = ExecuteMsg::SeedSubAccount { sub_account: "mule3".to_string(), amount: Uint128::new(1), spender: None, limit: Uint128::new(1) };
+    let err = execute(deps.as_mut(), env, mock_info("owner1", &[]), seed).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawTimelocked { .. }));
+}
+
+/// ## Manager Authorization Test
+/// Tests that a manager appointed by an owner can authorize and revoke spenders for
+/// the owner, but cannot spend or authorize itself, and that other accounts cannot
+/// act for the owner.
+#[test]
+fn test_manager_authorization() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let authorize = |spender: &str| ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: Some("owner1".to_string()) };
+    let spend = || ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(100), secret: None, denom: None };
+
+    // Before being appointed, the manager cannot act for the owner
+    let err = execute(deps.as_mut(), mock_env(), mock_info("manager1", &[]), authorize("agent1")).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // Once appointed, the manager authorizes a spender who can then spend
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AddManager { manager: "manager1".to_string() }).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("manager1", &[]), authorize("agent1")).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "owner" && attr.value == "owner1"));
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend()).unwrap();
+
+    // The manager can neither spend nor make itself a spender
+    let err = execute(deps.as_mut(), mock_env(), mock_info("manager1", &[]), spend()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let err = execute(deps.as_mut(), mock_env(), mock_info("manager1", &[]), authorize("manager1")).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // The manager can revoke the spender again
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager1", &[]),
+        ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: Some("owner1".to_string()) }
+    ).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    // A removed manager loses its rights
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RemoveManager { manager: "manager1".to_string() }).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("manager1", &[]), authorize("agent1")).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+}
+
+/// ## Managers Query Test
+/// Tests that the Managers query lists an owner's appointed managers, dropping
+/// removed ones, and pages through them.
+#[test]
+fn test_managers_query() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let managers = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, start_after: Option<&str>, limit: Option<u32>| -> Vec<String> {
+        cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Managers { owner: "owner1".to_string(), start_after: start_after.map(str::to_string), limit }
+        ).unwrap()).unwrap()
+    };
+
+    // Appoint two managers, plus one for another owner
+    for manager in ["manager1", "manager2"] {
+        execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AddManager { manager: manager.to_string() }).unwrap();
+    }
+    execute(deps.as_mut(), mock_env(), mock_info("owner2", &[]), ExecuteMsg::AddManager { manager: "manager3".to_string() }).unwrap();
+    assert_eq!(managers(&deps, None, None), vec!["manager1", "manager2"]);
+    assert_eq!(managers(&deps, None, Some(1)), vec!["manager1"]);
+    assert_eq!(managers(&deps, Some("manager1"), None), vec!["manager2"]);
+
+    // Removing one leaves the other
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::RemoveManager { manager: "manager1".to_string() }).unwrap();
+    assert_eq!(managers(&deps, None, None), vec!["manager2"]);
+}
+
+/// ## Display Decimals Test
+/// Tests that the primary denom's display decimals are stored at instantiation and
+/// returned by the Config query, that more than 18 are rejected and that a precision
+/// entry for the primary denom must agree with them.
+#[test]
+fn test_display_decimals() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate with 6 display decimals
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            decimals: 6,
+            ..Default::default()
+        }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.decimals, 6);
+
+    // More than 18 decimals are rejected
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            decimals: 19,
+            ..Default::default()
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+
+    // A precision entry claiming different decimals for the primary denom conflicts
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            decimals: 6,
+            denom_precisions: Some(vec![DenomPrecision { denom: "ucosm".to_string(), decimals: 18, precision: 2 }]),
+            ..Default::default()
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::ConflictingConfig { .. }));
+}
+
+/// ## Block Spender Test
+/// Tests that a blocked spender cannot spend despite its authorization, and can
+/// spend again under the same authorization once unblocked.
+#[test]
+fn test_block_spender() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = || ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(100), secret: None, denom: None };
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend()).unwrap();
+
+    // Only the admin can block a spender
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::BlockSpender { spender: "agent1".to_string() }).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::BlockSpender { spender: "agent1".to_string() }).unwrap();
+
+    // The authorization remains, but the blocked spender cannot use it
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend()).unwrap_err();
+    assert!(matches!(err, ContractError::SpenderBlocked {}));
+    let authorized: bool = cosmwasm_std::from_json(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsAuthorized { owner: "owner1".to_string(), spender: "agent1".to_string() }
+    ).unwrap()).unwrap();
+    assert!(authorized);
+
+    // Unblocking restores spending under the existing authorization
+    execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), ExecuteMsg::UnblockSpender { spender: "agent1".to_string() }).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend()).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(800));
+}
+
+/// ## Receive Cap Test
+/// Tests that spends, refunds and sub-account seeds may credit a recipient up to the
+/// receive cap and are rejected when they would push its balance beyond it.
+#[test]
+fn test_receive_cap() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate with accounts capped at 500 received
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            max_received_per_account: Some(Uint128::new(500)),
+            ..Default::default()
+        }
+    ).unwrap();
+    let config: ConfigResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.max_received_per_account, Some(Uint128::new(500)));
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(2000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spend = |amount: u128| ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(amount), secret: None, denom: None };
+
+    // The agent may receive exactly up to the cap
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(300)).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(200)).unwrap();
+
+    // One more unit would push it over, and nothing moves
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), spend(1)).unwrap_err();
+    assert!(matches!(err, ContractError::ReceiveCapExceeded {}));
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(1500));
+
+    // CanSpend reports the same failure
+    let can_spend: CanSpendResponse = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::CanSpend {
+        owner: "owner1".to_string(),
+        spender: "agent1".to_string(),
+        amount: Uint128::new(1),
+    }).unwrap()).unwrap();
+    assert_eq!(can_spend, CanSpendResponse { allowed: false, reason: Some(ContractError::ReceiveCapExceeded {}.to_string()) });
+
+    // Batch payouts are capped per recipient as well
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner1", &[]),
+        ExecuteMsg::BatchSpendFrom { owner: "owner1".to_string(), payouts: vec![("vendor".to_string(), Uint128::new(501))], denom: None }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::ReceiveCapExceeded {}));
+
+    // Refunds and sub-account seeds credit another account, so they are capped too
+    let refund = |amount: u128| ExecuteMsg::Refund { owner: "owner2".to_string(), amount: Uint128::new(amount), denom: None };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), refund(501)).unwrap_err();
+    assert!(matches!(err, ContractError::ReceiveCapExceeded {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), refund(500)).unwrap();
+    let seed = |amount: u128| ExecuteMsg::SeedSubAccount {
+        sub_account: "sub1".to_string(),
+        amount: Uint128::new(amount),
+        spender: None,
+        limit: Uint128::new(1),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), seed(501)).unwrap_err();
+    assert!(matches!(err, ContractError::ReceiveCapExceeded {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), seed(500)).unwrap();
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(500));
+}
+
+/// ## Conflicting Config Test
+/// Tests that instantiate rejects each contradictory parameter combination with
+/// ConflictingConfig naming the conflict, and that UpdateParams cannot create one.
+#[test]
+fn test_conflicting_config() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+    let base = || InstantiateMsg {
+        admin: admin.to_string(),
+        denom: denom.to_string(),
+        ..Default::default()
+    };
+    let conflicts = vec![
+        (
+            InstantiateMsg { oracle_auto_pause: Some(true), ..base() },
+            "oracle_auto_pause requires a price_oracle",
+        ),
+        (
+            InstantiateMsg {
+                additional_denoms: Some(vec!["uatom".to_string()]),
+                denom_aliases: Some(vec!["uatom".to_string()]),
+                ..base()
+            },
+            "denom alias uatom is already an accepted denom",
+        ),
+        (
+            InstantiateMsg {
+                denom_precisions: Some(vec![DenomPrecision { denom: "uatom".to_string(), decimals: 6, precision: 2 }]),
+                ..base()
+            },
+            "precision configured for uatom, which is not an accepted denom",
+        ),
+    ];
+
+    // Each conflicting pair is rejected with its own detail
+    for (msg, expected) in conflicts {
+        let err = instantiate(deps.as_mut(), mock_env(), mock_info(admin, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::ConflictingConfig { ref detail } if detail == expected), "{}", err);
+    }
+
+    // The same flags are accepted once they agree
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            additional_denoms: Some(vec!["uatom".to_string()]),
+            denom_precisions: Some(vec![DenomPrecision { denom: "uatom".to_string(), decimals: 6, precision: 2 }]),
+            ..base()
+        }
+    ).unwrap();
+
+    // UpdateParams cannot enable oracle auto-pause without an oracle either
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateParams { params: ParamUpdate { oracle_auto_pause: Some(true), ..Default::default() } }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::ConflictingConfig { .. }));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        ExecuteMsg::UpdateParams {
+            params: ParamUpdate { price_oracle: Some("oracle".to_string()), oracle_auto_pause: Some(true), ..Default::default() }
+        }
+    ).unwrap();
+}
+
+/// ## Admin Revoke Test
+/// Tests that the admin can revoke any owner's authorization, that the response
+/// marks the revocation as admin-initiated, and that other accounts cannot.
+#[test]
+fn test_admin_revoke() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let revoke = || ExecuteMsg::AdminRevoke { owner: "owner1".to_string(), spender: "agent1".to_string() };
+    let is_authorized = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> bool {
+        cosmwasm_std::from_json(query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsAuthorized { owner: "owner1".to_string(), spender: "agent1".to_string() }
+        ).unwrap()).unwrap()
+    };
+
+    // Only the admin may force a revocation
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), revoke()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    assert!(is_authorized(&deps));
+
+    // The admin revokes the owner's authorization
+    let res = execute(deps.as_mut(), mock_env(), mock_info(admin, &[]), revoke()).unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.key == "action" && attr.value == "admin_revoke"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "initiated_by" && attr.value == "admin"));
+    assert!(res.attributes.iter().any(|attr| attr.key == "previous_state" && attr.value == "true"));
+    assert!(!is_authorized(&deps));
+}
+
+/// ## Deposit Receipt Test
+/// Tests that deposits get sequential ids, returned as the `deposit_id` attribute,
+/// whose receipts can be queried back.
+#[test]
+fn test_deposit_receipts() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let deposit_id = |res: &cosmwasm_std::Response| -> String {
+        res.attributes.iter().find(|attr| attr.key == "deposit_id").unwrap().value.clone()
+    };
+
+    // Two deposits get ids 1 and 2
+    let res = execute(deps.as_mut(), mock_env(), mock_info("user1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(deposit_id(&res), "1");
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user2", &coins(250, denom)),
+        ExecuteMsg::DepositFor { beneficiary: "user3".to_string() }
+    ).unwrap();
+    assert_eq!(deposit_id(&res), "2");
+
+    // The receipt records who deposited for whom
+    let record: Option<DepositRecord> = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Deposit { id: 2 }).unwrap()).unwrap();
+    let record = record.unwrap();
+    assert_eq!(record.id, 2);
+    assert_eq!(record.depositor.as_str(), "user2");
+    assert_eq!(record.beneficiary.as_str(), "user3");
+    assert_eq!(record.amount, Uint128::new(250));
+    assert_eq!(record.time, mock_env().block.time);
+
+    // Unknown ids return none
+    let record: Option<DepositRecord> = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Deposit { id: 3 }).unwrap()).unwrap();
+    assert_eq!(record, None);
+}
+
+/// ## Total Authorizations Test
+/// Tests that the TotalAuthorizations counter follows authorizations across owners
+/// and is not inflated by re-authorizing or double-revoking.
+#[test]
+fn test_total_authorizations() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: "ucosm".to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let total = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> u64 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::TotalAuthorizations {}).unwrap()).unwrap()
+    };
+    let authorize = |spender: &str| ExecuteMsg::AuthorizeSpender { spender: spender.to_string(), terms: None, owner: None };
+    let revoke = || ExecuteMsg::RevokeSpender { spender: "agent1".to_string(), owner: None };
+    assert_eq!(total(&deps), 0);
+
+    // Two authorizations from different owners; re-authorizing does not double count
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize("agent1")).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), authorize("agent1")).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner2", &[]), authorize("agent2")).unwrap();
+    assert_eq!(total(&deps), 2);
+
+    // Revoking one leaves one; revoking it again changes nothing
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), revoke()).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), revoke()).unwrap();
+    assert_eq!(total(&deps), 1);
+}
+
+/// ## Refund Test
+/// Tests that a spender can return part of a spend to the owner's balance, and that
+/// refunds beyond the spender's balance are rejected.
+#[test]
+fn test_refund() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let balance = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: owner.to_string(), denom: None }).unwrap()).unwrap()
+    };
+
+    // The agent spends 300, then refunds 100 of it
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("agent1", &[]),
+        ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(300), secret: None, denom: None }
+    ).unwrap();
+    let refund = |amount: u128| ExecuteMsg::Refund { owner: "owner1".to_string(), amount: Uint128::new(amount), denom: None };
+    execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), refund(100)).unwrap();
+    assert_eq!(balance(&deps, "owner1"), Uint128::new(800));
+    assert_eq!(balance(&deps, "agent1"), Uint128::new(200));
+
+    // The agent cannot refund more than it holds
+    let err = execute(deps.as_mut(), mock_env(), mock_info("agent1", &[]), refund(201)).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientBalance { available, .. } if available == Uint128::new(200)));
+}
+
+/// ## Instantiate With Funds Test
+/// Tests that instantiation rejects attached funds, which no balance would account for.
+#[test]
+fn test_instantiate_with_funds() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+    let msg = InstantiateMsg {
+        admin: admin.to_string(),
+        denom: denom.to_string(),
+        ..Default::default()
+    };
+
+    // Attaching coins fails
+    let err = instantiate(deps.as_mut(), mock_env(), mock_info(admin, &coins(1000, denom)), msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::FundsNotAccepted {}));
+
+    // Without funds the same message succeeds
+    instantiate(deps.as_mut(), mock_env(), mock_info(admin, &[]), msg).unwrap();
+}
+
+/// ## Simulate Batch Spend Test
+/// Tests that SimulateBatchSpend reports a batch within the owner's balance as
+/// succeeding and one beyond it as failing, without changing any balance.
+#[test]
+fn test_simulate_batch_spend() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(500, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "treasurer".to_string(), terms: None, owner: None }).unwrap();
+    let simulate = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, payouts: &[(&str, u128)]| -> SimulateBatchSpendResponse {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::SimulateBatchSpend {
+            owner: "owner1".to_string(),
+            spender: "treasurer".to_string(),
+            payouts: payouts.iter().map(|(recipient, amount)| (recipient.to_string(), Uint128::new(*amount))).collect(),
+        }).unwrap()).unwrap()
+    };
+
+    // A batch the balance covers would succeed
+    assert_eq!(
+        simulate(&deps, &[("vendor1", 200), ("vendor2", 300)]),
+        SimulateBatchSpendResponse { would_succeed: true, total: Uint128::new(500), reason: None }
+    );
+
+    // A batch beyond the balance would fail, with the reason BatchSpendFrom gives
+    let response = simulate(&deps, &[("vendor1", 200), ("vendor2", 301)]);
+    assert!(!response.would_succeed);
+    assert_eq!(response.total, Uint128::new(501));
+    assert_eq!(response.reason.unwrap(), "Insufficient balance: have 500, need 501");
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("treasurer", &[]),
+        ExecuteMsg::BatchSpendFrom {
+            owner: "owner1".to_string(),
+            payouts: vec![("vendor1".to_string(), Uint128::new(200)), ("vendor2".to_string(), Uint128::new(301))],
+            denom: None,
+        }
+    ).unwrap_err();
+    assert_eq!(err.to_string(), "Insufficient balance: have 500, need 501");
+
+    // Simulating moved nothing
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(500));
+}
+
+/// ## Large Amount Attributes Test
+/// Tests that amounts above 2^64 are rendered in full in deposit and spend attributes.
+#[test]
+fn test_large_amount_attributes() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    let attribute = |attributes: &[cosmwasm_std::Attribute], key: &str| -> String {
+        attributes.iter().find(|attribute| attribute.key == key).unwrap().value.clone()
+    };
+
+    // The deposit event carries the full deposited amount
+    let deposited = Uint128::new(u64::MAX as u128 * 4);
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(deposited.u128(), denom)), ExecuteMsg::Deposit {}).unwrap();
+    assert_eq!(attribute(&res.events[0].attributes, "amount"), deposited.to_string());
+    assert_eq!(attribute(&res.attributes, "new_balance"), deposited.to_string());
+
+    // So does a delegated spend, along with the owner's remaining balance
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender { spender: "agent1".to_string(), terms: None, owner: None }).unwrap();
+    let spent = Uint128::new(u64::MAX as u128 + 1);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("agent1", &[]),
+        ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: spent, secret: None, denom: None }
+    ).unwrap();
+    assert_eq!(attribute(&res.attributes, "amount"), "18446744073709551616");
+    assert_eq!(attribute(&res.attributes, "owner_new_balance"), (deposited - spent).to_string());
+}
+
+/// ## Balance Raw Test
+/// Tests that BalanceRaw reads balances without validating the address, returning 0
+/// for strings the strict Balance query rejects or has never seen.
+#[test]
+fn test_balance_raw() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(100, denom)), ExecuteMsg::Deposit {}).unwrap();
+    let balance_raw = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, owner: &str| -> Uint128 {
+        cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::BalanceRaw { owner: owner.to_string() }).unwrap()).unwrap()
+    };
+
+    // A credited address reads the same as through Balance
+    assert_eq!(balance_raw(&deps, "owner1"), Uint128::new(100));
+
+    // A never-seen address reads 0
+    assert_eq!(balance_raw(&deps, "stranger"), Uint128::zero());
+
+    // A malformed address reads 0, where the strict query errors
+    assert_eq!(balance_raw(&deps, "Not An Address"), Uint128::zero());
+    query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "Not An Address".to_string(), denom: None }).unwrap_err();
+}
+
+/// ## Spend Cooldown Test
+/// Tests that a spender under a cooldown can spend once, is rejected until the
+/// cooldown has passed, and can then spend again.
+#[test]
+fn test_spend_cooldown() {
+    let mut deps = mock_dependencies();
+    let admin = "admin";
+    let denom = "ucosm";
+
+    // Instantiate the contract
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(admin, &[]),
+        InstantiateMsg {
+            admin: admin.to_string(),
+            denom: denom.to_string(),
+            ..Default::default()
+        }
+    ).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &coins(1000, denom)), ExecuteMsg::Deposit {}).unwrap();
+    execute(deps.as_mut(), mock_env(), mock_info("owner1", &[]), ExecuteMsg::AuthorizeSpender {
+        spender: "agent1".to_string(),
+        terms: Some(AuthorizationTerms { cooldown_seconds: Some(60), ..Default::default() }),
+        owner: None,
+    }).unwrap();
+    let spend = || ExecuteMsg::SpendFrom { owner: "owner1".to_string(), amount: Uint128::new(10), secret: None, denom: None };
+    let at = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env
+    };
+
+    // The first spend goes through
+    execute(deps.as_mut(), at(0), mock_info("agent1", &[]), spend()).unwrap();
+
+    // An immediate second spend, or one just short of the cooldown, is rejected
+    for seconds in [0, 59] {
+        let err = execute(deps.as_mut(), at(seconds), mock_info("agent1", &[]), spend()).unwrap_err();
+        assert!(matches!(err, ContractError::Cooldown { retry_after } if retry_after.seconds() == at(60).block.time.seconds()));
+    }
+
+    // Once the cooldown has passed the spender can spend again, restarting it
+    execute(deps.as_mut(), at(60), mock_info("agent1", &[]), spend()).unwrap();
+    let err = execute(deps.as_mut(), at(61), mock_info("agent1", &[]), spend()).unwrap_err();
+    assert!(matches!(err, ContractError::Cooldown { .. }));
+    let balance: Uint128 = cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), QueryMsg::Balance { owner: "owner1".to_string(), denom: None }).unwrap()).unwrap();
+    assert_eq!(balance, Uint128::new(980));
 }